@@ -0,0 +1,138 @@
+using System;
+using System.Collections.Generic;
+using System.IO;
+using System.Reflection;
+using System.Runtime.CompilerServices;
+using System.Runtime.InteropServices;
+using System.Runtime.Loader;
+
+namespace NetCoreHost.Helper {
+    // Exposes AssemblyLoadContext operations to native hosts.
+    // All entry points return an HRESULT, strings are platform strings (UTF-16 on Windows, UTF-8 elsewhere) and
+    // contexts are passed around as GCHandles.
+    public static unsafe class AssemblyLoadContextHelper {
+        public delegate int ComponentEntryPoint(IntPtr args, int sizeBytes);
+
+        private static readonly IntPtr UnmanagedCallersOnlyMethod = new IntPtr(-1);
+
+        private const BindingFlags StaticMethods = BindingFlags.Public | BindingFlags.NonPublic | BindingFlags.Static;
+
+        // Delegates handed out to native code have to be kept alive as long as their context.
+        private static readonly ConditionalWeakTable<AssemblyLoadContext, List<Delegate>> Delegates = new();
+
+        private static string? ReadString(IntPtr ptr) => ptr == IntPtr.Zero ? null : Marshal.PtrToStringAuto(ptr);
+
+        private static AssemblyLoadContext GetContext(IntPtr handle) =>
+            (AssemblyLoadContext)GCHandle.FromIntPtr(handle).Target!;
+
+        private static Type GetType(AssemblyLoadContext context, string name) =>
+            Type.GetType(name, context.LoadFromAssemblyName, null, throwOnError: true)!;
+
+        [UnmanagedCallersOnly]
+        public static int CreateContext(IntPtr name, int isCollectible, IntPtr* handle) {
+            try {
+                var context = new AssemblyLoadContext(ReadString(name), isCollectible != 0);
+                *handle = GCHandle.ToIntPtr(GCHandle.Alloc(context));
+                return 0;
+            } catch (Exception e) {
+                return e.HResult;
+            }
+        }
+
+        [UnmanagedCallersOnly]
+        public static int LoadFromPath(IntPtr handle, IntPtr path) {
+            try {
+                GetContext(handle).LoadFromAssemblyPath(ReadString(path)!);
+                return 0;
+            } catch (Exception e) {
+                return e.HResult;
+            }
+        }
+
+        [UnmanagedCallersOnly]
+        public static int LoadFromBytes(IntPtr handle, byte* assembly, nint assemblyLength, byte* symbols, nint symbolsLength) {
+            try {
+                using var assemblyStream = new UnmanagedMemoryStream(assembly, assemblyLength);
+                using var symbolsStream = symbols == null ? null : new UnmanagedMemoryStream(symbols, symbolsLength);
+                GetContext(handle).LoadFromStream(assemblyStream, symbolsStream);
+                return 0;
+            } catch (Exception e) {
+                return e.HResult;
+            }
+        }
+
+        [UnmanagedCallersOnly]
+        public static int GetFunctionPointer(IntPtr handle, IntPtr typeName, IntPtr methodName, IntPtr delegateTypeName, IntPtr* function) {
+            try {
+                var context = GetContext(handle);
+                using var scope = context.EnterContextualReflection();
+                var type = GetType(context, ReadString(typeName)!);
+                var name = ReadString(methodName)!;
+
+                var overloads = Array.FindAll(type.GetMethods(StaticMethods), candidate => candidate.Name == name);
+                if (overloads.Length == 0) {
+                    throw new MissingMethodException(type.FullName, name);
+                }
+
+                if (delegateTypeName == UnmanagedCallersOnlyMethod) {
+                    // There is no delegate type to pick an overload by, so only the ones callable from native code are
+                    // considered and there must be exactly one of them.
+                    var callable = Array.FindAll(overloads, candidate => candidate.GetCustomAttribute<UnmanagedCallersOnlyAttribute>() != null);
+                    if (callable.Length == 0) {
+                        throw new InvalidOperationException($"{type.FullName}.{name} is not annotated with UnmanagedCallersOnly.");
+                    }
+                    if (callable.Length > 1) {
+                        throw new AmbiguousMatchException($"{type.FullName}.{name} has multiple overloads annotated with UnmanagedCallersOnly.");
+                    }
+                    *function = callable[0].MethodHandle.GetFunctionPointer();
+                } else {
+                    var delegateType = delegateTypeName == IntPtr.Zero
+                        ? typeof(ComponentEntryPoint)
+                        : GetType(context, ReadString(delegateTypeName)!);
+                    var parameterTypes = Array.ConvertAll(delegateType.GetMethod("Invoke")!.GetParameters(), parameter => parameter.ParameterType);
+                    var method = type.GetMethod(name, StaticMethods, null, parameterTypes, null)
+                        ?? throw new ArgumentException($"No overload of {type.FullName}.{name} matches {delegateType.FullName}.");
+                    var @delegate = Delegate.CreateDelegate(delegateType, method, throwOnBindFailure: true)!;
+                    var delegates = Delegates.GetOrCreateValue(context);
+                    lock (delegates) {
+                        delegates.Add(@delegate);
+                    }
+                    *function = Marshal.GetFunctionPointerForDelegate(@delegate);
+                }
+                return 0;
+            } catch (Exception e) {
+                return e.HResult;
+            }
+        }
+
+        // Starts unloading a collectible context and replaces its handle with a weak handle, which can be used to
+        // observe whether the context has been collected.
+        [UnmanagedCallersOnly]
+        public static int Unload(IntPtr handle, IntPtr* weakHandle) {
+            try {
+                var gcHandle = GCHandle.FromIntPtr(handle);
+                var context = (AssemblyLoadContext)gcHandle.Target!;
+                context.Unload();
+                *weakHandle = GCHandle.ToIntPtr(GCHandle.Alloc(context, GCHandleType.Weak));
+                gcHandle.Free();
+                return 0;
+            } catch (Exception e) {
+                return e.HResult;
+            }
+        }
+
+        [UnmanagedCallersOnly]
+        public static int IsAlive(IntPtr weakHandle, int collect) {
+            if (collect != 0) {
+                GC.Collect();
+                GC.WaitForPendingFinalizers();
+            }
+            return GCHandle.FromIntPtr(weakHandle).Target == null ? 0 : 1;
+        }
+
+        [UnmanagedCallersOnly]
+        public static void FreeHandle(IntPtr handle) {
+            GCHandle.FromIntPtr(handle).Free();
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Native function pointers dispatching to Rust closures.
+//!
+//! Every slot has its own `extern "system"` trampoline, so that a closure can be handed out as a plain function
+//! pointer with the default managed entry point signature.
+
+use hostfxr_sys::component_entry_point_fn;
+use std::{
+    ffi::c_void,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+type EntryPoint = Arc<dyn Fn(*const c_void, i32) -> i32 + Send + Sync>;
+
+/// The maximum number of closures which can be registered at the same time.
+pub const MAX_CLOSURES: usize = 32;
+
+static CLOSURES: Mutex<[Option<EntryPoint>; MAX_CLOSURES]> =
+    Mutex::new([const { None }; MAX_CLOSURES]);
+
+fn call(slot: usize, args: *const c_void, size: i32) -> i32 {
+    let closure = CLOSURES.lock().unwrap_or_else(PoisonError::into_inner)[slot].clone();
+    let closure = closure.expect("called a closure which is no longer registered");
+    closure(args, size)
+}
+
+macro_rules! trampolines {
+    ($($slot:literal)*) => {
+        const TRAMPOLINES: [component_entry_point_fn; MAX_CLOSURES] = [$({
+            unsafe extern "system" fn trampoline(args: *const c_void, size: i32) -> i32 {
+                call($slot, args, size)
+            }
+            trampoline
+        }),*];
+    };
+}
+
+trampolines!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31);
+
+/// Stores the given closure in a free slot and returns the function pointer dispatching to it.
+pub(crate) fn register(
+    closure: impl Fn(*const c_void, i32) -> i32 + Send + Sync + 'static,
+) -> Option<component_entry_point_fn> {
+    let mut closures = CLOSURES.lock().unwrap_or_else(PoisonError::into_inner);
+    let slot = closures.iter().position(Option::is_none)?;
+    closures[slot] = Some(Arc::new(closure));
+    Some(TRAMPOLINES[slot])
+}
+
+/// Frees all closure slots.
+pub(crate) fn clear() {
+    *CLOSURES.lock().unwrap_or_else(PoisonError::into_inner) = [const { None }; MAX_CLOSURES];
+}
@@ -0,0 +1,244 @@
+use crate::{
+    closures,
+    exports::{FIELD_SEPARATOR, RECORD_SEPARATOR},
+};
+use hostfxr_sys::dlopen2::{self, raw::Library};
+use std::{
+    env::{
+        self,
+        consts::{DLL_PREFIX, DLL_SUFFIX},
+    },
+    ffi::c_void,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// The environment variable which can be used to override the path returned by [`MockHostfxr::library_path`].
+pub const LIBRARY_PATH_ENV_VAR: &str = "NETCOREHOST_MOCK_LIBRARY";
+
+macro_rules! call {
+    ($lib:expr, $name:ident: fn($($arg_ty:ty),*) $(-> $ret:ty)?, $($arg:expr),*) => {{
+        let f = unsafe {
+            $lib.symbol::<unsafe extern "C" fn($($arg_ty),*) $(-> $ret)?>(stringify!($name))
+        }
+        .expect(concat!("mock library does not export ", stringify!($name)));
+        unsafe { f($($arg),*) }
+    }};
+}
+
+/// A handle to the mock hostfxr library used to script its behaviour and inspect the calls it received.
+///
+/// The library keeps its state globally, so all handles (and all [`Hostfxr`] instances loaded from the same path)
+/// share the same state.
+///
+/// [`Hostfxr`]: https://docs.rs/netcorehost/latest/netcorehost/hostfxr/struct.Hostfxr.html
+#[derive(Debug)]
+pub struct MockHostfxr {
+    lib: Library,
+    path: PathBuf,
+}
+
+impl MockHostfxr {
+    /// Returns the file name of the mock library on the current platform (e.g. `libnetcorehost_mock.so`).
+    #[must_use]
+    pub fn library_file_name() -> String {
+        format!("{DLL_PREFIX}netcorehost_mock{DLL_SUFFIX}")
+    }
+
+    /// Locates the mock library built by cargo.
+    ///
+    /// The path can be overridden using the [`NETCOREHOST_MOCK_LIBRARY`](LIBRARY_PATH_ENV_VAR) environment variable.
+    /// Otherwise the library is searched for next to the current executable and in its parent directory, which
+    /// covers test binaries in `target/<profile>/deps`.
+    pub fn library_path() -> io::Result<PathBuf> {
+        if let Some(path) = env::var_os(LIBRARY_PATH_ENV_VAR).filter(|p| !p.is_empty()) {
+            return Ok(PathBuf::from(path));
+        }
+
+        let exe = env::current_exe()?;
+        let file_name = Self::library_file_name();
+        exe.ancestors()
+            .skip(1)
+            .take(2)
+            .map(|dir| dir.join(&file_name))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{file_name} not found next to {}", exe.display()),
+                )
+            })
+    }
+
+    /// Locates the mock library using [`MockHostfxr::library_path`] and loads it.
+    pub fn load() -> Result<Self, dlopen2::Error> {
+        let path = Self::library_path().map_err(dlopen2::Error::OpeningLibraryError)?;
+        Self::load_from_path(path)
+    }
+
+    /// Loads the mock library from the given path.
+    pub fn load_from_path(path: impl AsRef<Path>) -> Result<Self, dlopen2::Error> {
+        let path = path.as_ref();
+        Ok(Self {
+            lib: Library::open(path)?,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Returns the path the mock library was loaded from.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Resets the library to its initial state, closing all host contexts and forgetting all recorded calls,
+    /// forced statuses, properties, functions and SDKs.
+    pub fn reset(&self) {
+        call!(self.lib, netcorehost_mock_reset: fn(),);
+        closures::clear();
+    }
+
+    /// Forces the given hostfxr export or runtime delegate (e.g. `hostfxr_initialize_for_runtime_config` or
+    /// `load_assembly_and_get_function_pointer`) to return the given status code without doing anything else.
+    /// Negative status codes are reported to the registered error writer.
+    pub fn set_status(&self, function: &str, status: u32) {
+        #[allow(clippy::cast_possible_wrap)]
+        let status = status as i32;
+        call!(
+            self.lib,
+            netcorehost_mock_set_status: fn(*const u8, usize, i32),
+            function.as_ptr(),
+            function.len(),
+            status
+        );
+    }
+
+    /// Removes a status forced using [`MockHostfxr::set_status`].
+    pub fn clear_status(&self, function: &str) {
+        call!(
+            self.lib,
+            netcorehost_mock_clear_status: fn(*const u8, usize),
+            function.as_ptr(),
+            function.len()
+        );
+    }
+
+    /// Sets the exit code returned by `hostfxr_run_app`, `hostfxr_main` and `hostfxr_main_startupinfo`.
+    pub fn set_exit_code(&self, exit_code: i32) {
+        call!(self.lib, netcorehost_mock_set_exit_code: fn(i32), exit_code);
+    }
+
+    /// Sets a runtime property that host contexts initialized afterwards start with.
+    pub fn set_runtime_property(&self, key: &str, value: &str) {
+        call!(
+            self.lib,
+            netcorehost_mock_set_runtime_property: fn(*const u8, usize, *const u8, usize),
+            key.as_ptr(),
+            key.len(),
+            value.as_ptr(),
+            value.len()
+        );
+    }
+
+    /// Adds an SDK directory reported by `hostfxr_get_available_sdks`.
+    pub fn add_available_sdk(&self, sdk_dir: &str) {
+        call!(
+            self.lib,
+            netcorehost_mock_add_available_sdk: fn(*const u8, usize),
+            sdk_dir.as_ptr(),
+            sdk_dir.len()
+        );
+    }
+
+    /// Registers the function returned by the runtime delegates for the given assembly qualified type name and method.
+    ///
+    /// # Safety
+    /// The function has to match the signature the host will call it with.
+    pub unsafe fn register_function(
+        &self,
+        type_name: &str,
+        method_name: &str,
+        function: *const (),
+    ) {
+        call!(
+            self.lib,
+            netcorehost_mock_register_function: fn(*const u8, usize, *const u8, usize, *const c_void),
+            type_name.as_ptr(),
+            type_name.len(),
+            method_name.as_ptr(),
+            method_name.len(),
+            function.cast::<c_void>()
+        );
+    }
+
+    /// Registers a closure with the default managed entry point signature
+    /// (`public delegate int ComponentEntryPoint(IntPtr args, int sizeBytes)`) for the given assembly qualified type
+    /// name and method.
+    ///
+    /// At most [`MAX_CLOSURES`](crate::MAX_CLOSURES) closures can be registered until the next
+    /// [`reset`](MockHostfxr::reset).
+    pub fn register_closure(
+        &self,
+        type_name: &str,
+        method_name: &str,
+        closure: impl Fn(*const c_void, i32) -> i32 + Send + Sync + 'static,
+    ) {
+        let function = closures::register(closure).expect("all closure slots are in use");
+        unsafe { self.register_function(type_name, method_name, function as *const ()) };
+    }
+
+    /// Returns the number of host contexts which are currently open.
+    #[must_use]
+    pub fn active_contexts(&self) -> usize {
+        call!(self.lib, netcorehost_mock_active_contexts: fn() -> usize,)
+    }
+
+    /// Returns all calls received since the last [`reset`](MockHostfxr::reset) in the order they were made.
+    #[must_use]
+    pub fn calls(&self) -> Vec<MockCall> {
+        let mut buffer = Vec::new();
+        loop {
+            let len = call!(
+                self.lib,
+                netcorehost_mock_calls: fn(*mut u8, usize) -> usize,
+                buffer.as_mut_ptr(),
+                buffer.len()
+            );
+            if len <= buffer.len() {
+                buffer.truncate(len);
+                break;
+            }
+            // more calls were recorded than fit into the buffer, so retry with a larger one.
+            buffer.resize(len, 0);
+        }
+
+        let calls = String::from_utf8_lossy(&buffer);
+        calls
+            .split(RECORD_SEPARATOR)
+            .filter(|call| !call.is_empty())
+            .map(|call| {
+                let mut fields = call.split(FIELD_SEPARATOR).map(str::to_owned);
+                MockCall {
+                    function: fields.next().unwrap_or_default(),
+                    args: fields.collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the names of all functions called since the last [`reset`](MockHostfxr::reset) in the order they
+    /// were called.
+    #[must_use]
+    pub fn called_functions(&self) -> Vec<String> {
+        self.calls().into_iter().map(|call| call.function).collect()
+    }
+}
+
+/// A call received by the mock library.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MockCall {
+    /// The name of the called export or runtime delegate.
+    pub function: String,
+    /// The string arguments of the call.
+    pub args: Vec<String>,
+}
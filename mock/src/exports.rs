@@ -0,0 +1,588 @@
+//! The C ABI exported by the mock library.
+//!
+//! The `hostfxr_*` functions mirror the real hostfxr exports, the `netcorehost_mock_*` functions are used by
+//! [`MockHostfxr`](crate::MockHostfxr) to script the behaviour of the library.
+
+use coreclr_hosting_shared::{StatusCode, char_t, size_t};
+use hostfxr_sys::{
+    hostfxr_error_writer_fn, hostfxr_get_available_sdks_result_fn, hostfxr_handle,
+    hostfxr_initialize_parameters,
+};
+use std::{
+    collections::HashMap,
+    ffi::c_void,
+    iter, ptr, slice,
+    sync::{LazyLock, Mutex, MutexGuard, PoisonError},
+};
+
+#[allow(clippy::cast_possible_wrap)]
+const COR_E_MISSINGMETHOD: i32 = 0x8013_1513_u32 as i32;
+
+const LOAD_ASSEMBLY_AND_GET_FUNCTION_POINTER: i32 = 5;
+const GET_FUNCTION_POINTER: i32 = 6;
+const LOAD_ASSEMBLY: i32 = 7;
+const LOAD_ASSEMBLY_BYTES: i32 = 8;
+
+/// Separates the fields of a recorded call in the serialized call log.
+pub(crate) const FIELD_SEPARATOR: char = '\u{1f}';
+/// Separates the recorded calls in the serialized call log.
+pub(crate) const RECORD_SEPARATOR: char = '\u{1e}';
+
+#[allow(clippy::cast_possible_wrap)]
+const fn status(code: StatusCode) -> i32 {
+    code as u32 as i32
+}
+
+/// Converts the given string to a nul-terminated platform string.
+pub(crate) fn to_pd(s: &str) -> Vec<char_t> {
+    #[cfg(windows)]
+    {
+        s.encode_utf16().chain(iter::once(0)).collect()
+    }
+    #[cfg(not(windows))]
+    {
+        #[allow(clippy::cast_possible_wrap)]
+        s.bytes()
+            .map(|b| b as char_t)
+            .chain(iter::once(0))
+            .collect()
+    }
+}
+
+/// Converts the given nul-terminated platform string to a [`String`], treating null as the empty string.
+unsafe fn from_pd(s: *const char_t) -> String {
+    if s.is_null() {
+        return String::new();
+    }
+    let mut len = 0;
+    while unsafe { *s.add(len) } != 0 {
+        len += 1;
+    }
+    let chars = unsafe { slice::from_raw_parts(s, len) };
+    #[cfg(windows)]
+    {
+        String::from_utf16_lossy(chars)
+    }
+    #[cfg(not(windows))]
+    {
+        #[allow(clippy::cast_sign_loss)]
+        let bytes = chars.iter().map(|&c| c as u8).collect::<Vec<_>>();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
+
+unsafe fn from_utf8(s: *const u8, len: usize) -> String {
+    if s.is_null() {
+        return String::new();
+    }
+    String::from_utf8_lossy(unsafe { slice::from_raw_parts(s, len) }).into_owned()
+}
+
+unsafe fn from_argv(argc: i32, argv: *const *const char_t) -> Vec<String> {
+    let argc = usize::try_from(argc).unwrap_or_default();
+    (0..argc)
+        .map(|i| unsafe { from_pd(*argv.add(i)) })
+        .collect()
+}
+
+struct Context {
+    properties: Vec<(String, Vec<char_t>, Vec<char_t>)>,
+    for_command_line: bool,
+}
+
+impl Context {
+    fn new(properties: &[(String, String)], for_command_line: bool) -> Self {
+        Self {
+            properties: properties
+                .iter()
+                .map(|(key, value)| (key.clone(), to_pd(key), to_pd(value)))
+                .collect(),
+            for_command_line,
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    statuses: HashMap<String, i32>,
+    exit_code: i32,
+    properties: Vec<(String, String)>,
+    functions: HashMap<(String, String), usize>,
+    sdks: Vec<Vec<char_t>>,
+    calls: Vec<Vec<String>>,
+    // boxed so that the handles handed out stay valid when the vec reallocates.
+    #[allow(clippy::vec_box)]
+    contexts: Vec<Box<Context>>,
+    error_writer: Option<hostfxr_error_writer_fn>,
+}
+
+// SAFETY: the raw pointers in the state point into the state itself and are only accessed while holding the lock.
+unsafe impl Send for State {}
+
+impl State {
+    fn context(&mut self, handle: hostfxr_handle) -> Option<&mut Context> {
+        if handle.is_null() {
+            return self.contexts.first_mut().map(|c| &mut **c);
+        }
+        self.contexts
+            .iter_mut()
+            .find(|c| ptr::eq(&raw const ***c, handle.cast()))
+            .map(|c| &mut **c)
+    }
+}
+
+static STATE: LazyLock<Mutex<State>> = LazyLock::new(Mutex::default);
+
+fn state() -> MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Records a call to the given function and returns the forced status for it, if there is one.
+/// Forced failures are reported to the registered error writer.
+fn enter(function: &str, args: Vec<String>) -> Result<MutexGuard<'static, State>, i32> {
+    let mut state = state();
+    state
+        .calls
+        .push(iter::once(function.to_owned()).chain(args).collect());
+
+    let Some(&status) = state.statuses.get(function) else {
+        return Ok(state);
+    };
+    let error_writer = state.error_writer;
+    drop(state);
+
+    if let Some(error_writer) = error_writer.filter(|_| status < 0) {
+        let message = to_pd(&format!("{function} failed with status {status:#010X}"));
+        error_writer(message.as_ptr());
+    }
+    Err(status)
+}
+
+unsafe fn initialize(
+    function: &str,
+    args: Vec<String>,
+    for_command_line: bool,
+    host_context_handle: *mut hostfxr_handle,
+) -> i32 {
+    let mut state = match enter(function, args) {
+        Ok(state) => state,
+        Err(status) => return status,
+    };
+
+    let context = Box::new(Context::new(&state.properties, for_command_line));
+    let code = if state.contexts.is_empty() {
+        StatusCode::Success
+    } else {
+        StatusCode::Success_HostAlreadyInitialized
+    };
+    unsafe { *host_context_handle = ptr::from_ref(&*context).cast() };
+    state.contexts.push(context);
+    status(code)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn hostfxr_main(argc: i32, argv: *const *const char_t) -> i32 {
+    let args = unsafe { from_argv(argc, argv) };
+    enter("hostfxr_main", args).map_or_else(|status| status, |state| state.exit_code)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn hostfxr_main_startupinfo(
+    argc: i32,
+    argv: *const *const char_t,
+    host_path: *const char_t,
+    dotnet_root: *const char_t,
+    app_path: *const char_t,
+) -> i32 {
+    let mut args = unsafe { from_argv(argc, argv) };
+    args.extend(unsafe { [from_pd(host_path), from_pd(dotnet_root), from_pd(app_path)] });
+    enter("hostfxr_main_startupinfo", args).map_or_else(|status| status, |state| state.exit_code)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn hostfxr_get_available_sdks(
+    exe_dir: *const char_t,
+    result: hostfxr_get_available_sdks_result_fn,
+) -> i32 {
+    let state = match enter(
+        "hostfxr_get_available_sdks",
+        vec![unsafe { from_pd(exe_dir) }],
+    ) {
+        Ok(state) => state,
+        Err(status) => return status,
+    };
+    let sdks = state.sdks.clone();
+    drop(state);
+
+    let sdk_ptrs = sdks.iter().map(Vec::as_ptr).collect::<Vec<_>>();
+    result(
+        i32::try_from(sdk_ptrs.len()).unwrap_or(i32::MAX),
+        sdk_ptrs.as_ptr(),
+    );
+    status(StatusCode::Success)
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn hostfxr_set_error_writer(
+    error_writer: Option<hostfxr_error_writer_fn>,
+) -> Option<hostfxr_error_writer_fn> {
+    let mut state = state();
+    state
+        .calls
+        .push(vec!["hostfxr_set_error_writer".to_owned()]);
+    std::mem::replace(&mut state.error_writer, error_writer)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn hostfxr_initialize_for_dotnet_command_line(
+    argc: i32,
+    argv: *const *const char_t,
+    _parameters: *const hostfxr_initialize_parameters,
+    host_context_handle: *mut hostfxr_handle,
+) -> i32 {
+    let args = unsafe { from_argv(argc, argv) };
+    unsafe {
+        initialize(
+            "hostfxr_initialize_for_dotnet_command_line",
+            args,
+            true,
+            host_context_handle,
+        )
+    }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn hostfxr_initialize_for_runtime_config(
+    runtime_config_path: *const char_t,
+    _parameters: *const hostfxr_initialize_parameters,
+    host_context_handle: *mut hostfxr_handle,
+) -> i32 {
+    let args = vec![unsafe { from_pd(runtime_config_path) }];
+    unsafe {
+        initialize(
+            "hostfxr_initialize_for_runtime_config",
+            args,
+            false,
+            host_context_handle,
+        )
+    }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn hostfxr_get_runtime_property_value(
+    host_context_handle: hostfxr_handle,
+    name: *const char_t,
+    value: *mut *const char_t,
+) -> i32 {
+    let name = unsafe { from_pd(name) };
+    let mut state = match enter("hostfxr_get_runtime_property_value", vec![name.clone()]) {
+        Ok(state) => state,
+        Err(status) => return status,
+    };
+    let Some(context) = state.context(host_context_handle) else {
+        return status(StatusCode::HostInvalidState);
+    };
+    match context.properties.iter().find(|(key, ..)| *key == name) {
+        Some((_, _, property)) => {
+            unsafe { *value = property.as_ptr() };
+            status(StatusCode::Success)
+        }
+        None => status(StatusCode::HostPropertyNotFound),
+    }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn hostfxr_set_runtime_property_value(
+    host_context_handle: hostfxr_handle,
+    name: *const char_t,
+    value: *const char_t,
+) -> i32 {
+    let name = unsafe { from_pd(name) };
+    let new_value = (!value.is_null()).then(|| unsafe { from_pd(value) });
+    let mut args = vec![name.clone()];
+    args.extend(new_value.clone());
+    let mut state = match enter("hostfxr_set_runtime_property_value", args) {
+        Ok(state) => state,
+        Err(status) => return status,
+    };
+    let Some(context) = state.context(host_context_handle) else {
+        return status(StatusCode::HostInvalidState);
+    };
+
+    context.properties.retain(|(key, ..)| *key != name);
+    if let Some(new_value) = new_value {
+        let key = to_pd(&name);
+        context.properties.push((name, key, to_pd(&new_value)));
+    }
+    status(StatusCode::Success)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn hostfxr_get_runtime_properties(
+    host_context_handle: hostfxr_handle,
+    count: *mut size_t,
+    keys: *mut *const char_t,
+    values: *mut *const char_t,
+) -> i32 {
+    let mut state = match enter("hostfxr_get_runtime_properties", Vec::new()) {
+        Ok(state) => state,
+        Err(status) => return status,
+    };
+    let Some(context) = state.context(host_context_handle) else {
+        return status(StatusCode::HostInvalidState);
+    };
+
+    let len = context.properties.len();
+    if keys.is_null() || values.is_null() || unsafe { *count } < len {
+        unsafe { *count = len };
+        return status(StatusCode::HostApiBufferTooSmall);
+    }
+    for (i, (_, key, value)) in context.properties.iter().enumerate() {
+        unsafe {
+            *keys.add(i) = key.as_ptr();
+            *values.add(i) = value.as_ptr();
+        }
+    }
+    unsafe { *count = len };
+    status(StatusCode::Success)
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn hostfxr_run_app(host_context_handle: hostfxr_handle) -> i32 {
+    let mut state = match enter("hostfxr_run_app", Vec::new()) {
+        Ok(state) => state,
+        Err(status) => return status,
+    };
+    match state.context(host_context_handle) {
+        Some(context) if context.for_command_line => state.exit_code,
+        _ => status(StatusCode::HostInvalidState),
+    }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn hostfxr_get_runtime_delegate(
+    host_context_handle: hostfxr_handle,
+    r#type: i32,
+    delegate: *mut *const (),
+) -> i32 {
+    let mut state = match enter("hostfxr_get_runtime_delegate", vec![r#type.to_string()]) {
+        Ok(state) => state,
+        Err(status) => return status,
+    };
+    if state.context(host_context_handle).is_none() {
+        return status(StatusCode::HostInvalidState);
+    }
+
+    let function: *const () = match r#type {
+        LOAD_ASSEMBLY_AND_GET_FUNCTION_POINTER => load_assembly_and_get_function_pointer as _,
+        GET_FUNCTION_POINTER => get_function_pointer as _,
+        LOAD_ASSEMBLY => load_assembly as _,
+        LOAD_ASSEMBLY_BYTES => load_assembly_bytes as _,
+        _ => return status(StatusCode::HostApiUnsupportedScenario),
+    };
+    unsafe { *delegate = function };
+    status(StatusCode::Success)
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn hostfxr_close(host_context_handle: hostfxr_handle) -> i32 {
+    let mut state = match enter("hostfxr_close", Vec::new()) {
+        Ok(state) => state,
+        Err(status) => return status,
+    };
+    let len = state.contexts.len();
+    state
+        .contexts
+        .retain(|c| !ptr::eq(&raw const **c, host_context_handle.cast()));
+    if state.contexts.len() == len {
+        status(StatusCode::InvalidArgFailure)
+    } else {
+        status(StatusCode::Success)
+    }
+}
+
+unsafe fn delegate_type_name(delegate_type_name: *const char_t) -> String {
+    // UNMANAGED_CALLERS_ONLY_METHOD is a sentinel pointer which must not be dereferenced.
+    if delegate_type_name as usize == usize::MAX {
+        "UnmanagedCallersOnly".to_owned()
+    } else {
+        unsafe { from_pd(delegate_type_name) }
+    }
+}
+
+unsafe fn resolve_function(
+    function: &str,
+    args: Vec<String>,
+    type_name: String,
+    method_name: String,
+    delegate: *mut *const c_void,
+) -> i32 {
+    let state = match enter(function, args) {
+        Ok(state) => state,
+        Err(status) => return status,
+    };
+    match state.functions.get(&(type_name, method_name)) {
+        Some(&function) => {
+            unsafe { *delegate = function as *const c_void };
+            status(StatusCode::Success)
+        }
+        None => COR_E_MISSINGMETHOD,
+    }
+}
+
+unsafe extern "system" fn load_assembly_and_get_function_pointer(
+    assembly_path: *const char_t,
+    type_name: *const char_t,
+    method_name: *const char_t,
+    delegate_type_name: *const char_t,
+    _reserved: *const c_void,
+    delegate: *mut *const c_void,
+) -> i32 {
+    let (type_name, method_name) = unsafe { (from_pd(type_name), from_pd(method_name)) };
+    let args = vec![
+        unsafe { from_pd(assembly_path) },
+        type_name.clone(),
+        method_name.clone(),
+        unsafe { self::delegate_type_name(delegate_type_name) },
+    ];
+    unsafe {
+        resolve_function(
+            "load_assembly_and_get_function_pointer",
+            args,
+            type_name,
+            method_name,
+            delegate,
+        )
+    }
+}
+
+unsafe extern "system" fn get_function_pointer(
+    type_name: *const char_t,
+    method_name: *const char_t,
+    delegate_type_name: *const char_t,
+    _load_context: *const c_void,
+    _reserved: *const c_void,
+    delegate: *mut *const c_void,
+) -> i32 {
+    let (type_name, method_name) = unsafe { (from_pd(type_name), from_pd(method_name)) };
+    let args = vec![type_name.clone(), method_name.clone(), unsafe {
+        self::delegate_type_name(delegate_type_name)
+    }];
+    unsafe {
+        resolve_function(
+            "get_function_pointer",
+            args,
+            type_name,
+            method_name,
+            delegate,
+        )
+    }
+}
+
+unsafe extern "system" fn load_assembly(
+    assembly_path: *const char_t,
+    _load_context: *const c_void,
+    _reserved: *const c_void,
+) -> i32 {
+    let args = vec![unsafe { from_pd(assembly_path) }];
+    enter("load_assembly", args).map_or_else(|status| status, |_| status(StatusCode::Success))
+}
+
+unsafe extern "system" fn load_assembly_bytes(
+    _assembly_bytes: *const u8,
+    assembly_bytes_len: usize,
+    symbols_bytes: *const u8,
+    symbols_bytes_len: usize,
+    _load_context: *const c_void,
+    _reserved: *const c_void,
+) -> i32 {
+    let symbols = if symbols_bytes.is_null() {
+        "null".to_owned()
+    } else {
+        symbols_bytes_len.to_string()
+    };
+    let args = vec![assembly_bytes_len.to_string(), symbols];
+    enter("load_assembly_bytes", args).map_or_else(|status| status, |_| status(StatusCode::Success))
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn netcorehost_mock_reset() {
+    *state() = State::default();
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn netcorehost_mock_set_status(
+    function: *const u8,
+    function_len: usize,
+    status: i32,
+) {
+    let function = unsafe { from_utf8(function, function_len) };
+    state().statuses.insert(function, status);
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn netcorehost_mock_clear_status(function: *const u8, function_len: usize) {
+    let function = unsafe { from_utf8(function, function_len) };
+    state().statuses.remove(&function);
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn netcorehost_mock_set_exit_code(exit_code: i32) {
+    state().exit_code = exit_code;
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn netcorehost_mock_set_runtime_property(
+    key: *const u8,
+    key_len: usize,
+    value: *const u8,
+    value_len: usize,
+) {
+    let key = unsafe { from_utf8(key, key_len) };
+    let value = unsafe { from_utf8(value, value_len) };
+    let mut state = state();
+    state.properties.retain(|(k, _)| *k != key);
+    state.properties.push((key, value));
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn netcorehost_mock_add_available_sdk(sdk: *const u8, sdk_len: usize) {
+    let sdk = unsafe { from_utf8(sdk, sdk_len) };
+    state().sdks.push(to_pd(&sdk));
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn netcorehost_mock_register_function(
+    type_name: *const u8,
+    type_name_len: usize,
+    method_name: *const u8,
+    method_name_len: usize,
+    function: *const c_void,
+) {
+    let type_name = unsafe { from_utf8(type_name, type_name_len) };
+    let method_name = unsafe { from_utf8(method_name, method_name_len) };
+    state()
+        .functions
+        .insert((type_name, method_name), function as usize);
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn netcorehost_mock_active_contexts() -> usize {
+    state().contexts.len()
+}
+
+/// Writes the serialized call log to the given buffer if it is large enough and returns its length.
+#[unsafe(no_mangle)]
+unsafe extern "C" fn netcorehost_mock_calls(buffer: *mut u8, buffer_len: usize) -> usize {
+    let calls = state()
+        .calls
+        .iter()
+        .map(|call| call.join(&FIELD_SEPARATOR.to_string()))
+        .collect::<Vec<_>>()
+        .join(&RECORD_SEPARATOR.to_string());
+
+    if !buffer.is_null() && buffer_len >= calls.len() {
+        unsafe { ptr::copy_nonoverlapping(calls.as_ptr(), buffer, calls.len()) };
+    }
+    calls.len()
+}
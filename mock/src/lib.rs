@@ -0,0 +1,19 @@
+#![warn(clippy::pedantic, clippy::cargo, missing_docs, unsafe_op_in_unsafe_fn)]
+#![allow(
+    clippy::missing_safety_doc,
+    clippy::missing_errors_doc,
+    clippy::missing_panics_doc,
+    clippy::module_name_repetitions,
+    clippy::multiple_crate_versions,
+    clippy::doc_markdown,
+    clippy::similar_names
+)]
+#![doc = include_str!("../README.md")]
+
+mod exports;
+
+mod control;
+pub use control::*;
+
+mod closures;
+pub use closures::MAX_CLOSURES;
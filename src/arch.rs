@@ -0,0 +1,324 @@
+use std::{
+    env,
+    fmt::{self, Display},
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use thiserror::Error;
+
+/// A processor architecture as named by .NET (e.g. in runtime identifiers or `DOTNET_ROOT_<ARCH>`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Architecture {
+    /// 32-bit x86.
+    X86,
+    /// 64-bit x86.
+    X64,
+    /// 32-bit ARM.
+    Arm,
+    /// 64-bit ARM.
+    Arm64,
+    /// 64-bit LoongArch.
+    LoongArch64,
+    /// 64-bit RISC-V.
+    RiscV64,
+    /// 64-bit IBM Z.
+    S390x,
+    /// 64-bit little-endian PowerPC.
+    Ppc64le,
+}
+
+impl Architecture {
+    /// All known architectures.
+    pub const ALL: [Self; 8] = [
+        Self::X86,
+        Self::X64,
+        Self::Arm,
+        Self::Arm64,
+        Self::LoongArch64,
+        Self::RiscV64,
+        Self::S390x,
+        Self::Ppc64le,
+    ];
+
+    /// Returns the architecture of the current process or [`None`] if it is not supported by .NET.
+    #[must_use]
+    pub fn current() -> Option<Self> {
+        match env::consts::ARCH {
+            "x86" => Some(Self::X86),
+            "x86_64" => Some(Self::X64),
+            "arm" => Some(Self::Arm),
+            "aarch64" => Some(Self::Arm64),
+            "loongarch64" => Some(Self::LoongArch64),
+            "riscv64" => Some(Self::RiscV64),
+            "s390x" => Some(Self::S390x),
+            "powerpc64" if cfg!(target_endian = "little") => Some(Self::Ppc64le),
+            _ => None,
+        }
+    }
+
+    /// Returns the name .NET uses for this architecture (e.g. `x64` or `arm64`).
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::X86 => "x86",
+            Self::X64 => "x64",
+            Self::Arm => "arm",
+            Self::Arm64 => "arm64",
+            Self::LoongArch64 => "loongarch64",
+            Self::RiscV64 => "riscv64",
+            Self::S390x => "s390x",
+            Self::Ppc64le => "ppc64le",
+        }
+    }
+
+    /// Returns the name of the architecture-specific dotnet root environment variable (e.g. `DOTNET_ROOT_X64`).
+    #[must_use]
+    pub fn dotnet_root_env_var(&self) -> String {
+        format!("DOTNET_ROOT_{}", self.name().to_ascii_uppercase())
+    }
+
+    fn from_elf_machine(machine: u16, is_64_bit: bool, is_little_endian: bool) -> Option<Self> {
+        match (machine, is_64_bit) {
+            (3, false) => Some(Self::X86),
+            (62, true) => Some(Self::X64),
+            (40, false) => Some(Self::Arm),
+            (183, true) => Some(Self::Arm64),
+            (258, true) => Some(Self::LoongArch64),
+            (243, true) => Some(Self::RiscV64),
+            (22, true) => Some(Self::S390x),
+            (21, true) if is_little_endian => Some(Self::Ppc64le),
+            _ => None,
+        }
+    }
+
+    fn from_pe_machine(machine: u16) -> Option<Self> {
+        match machine {
+            0x014C => Some(Self::X86),
+            0x8664 => Some(Self::X64),
+            0x01C4 => Some(Self::Arm),
+            0xAA64 => Some(Self::Arm64),
+            0x6264 => Some(Self::LoongArch64),
+            0x5064 => Some(Self::RiscV64),
+            _ => None,
+        }
+    }
+
+    fn from_mach_cpu_type(cpu_type: u32) -> Option<Self> {
+        match cpu_type {
+            0x0000_0007 => Some(Self::X86),
+            0x0100_0007 => Some(Self::X64),
+            0x0000_000C => Some(Self::Arm),
+            0x0100_000C => Some(Self::Arm64),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Architecture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Architecture {
+    type Err = UnknownArchitectureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|arch| arch.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| UnknownArchitectureError(s.to_owned()))
+    }
+}
+
+/// Error returned when parsing an unknown architecture name.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Unknown architecture '{0}'.")]
+pub struct UnknownArchitectureError(String);
+
+/// Reads the header of the given ELF, PE or Mach-O binary and returns the architectures it was built for.
+///
+/// Universal (fat) Mach-O binaries can contain code for multiple architectures, all other formats contain exactly one.
+/// Architectures not supported by .NET are omitted.
+pub fn read_binary_architectures(path: impl AsRef<Path>) -> io::Result<Vec<Architecture>> {
+    let mut file = File::open(path)?;
+    let mut magic = [0u8; 4];
+    file.read_exact(&mut magic)?;
+
+    match magic {
+        [0x7F, b'E', b'L', b'F'] => {
+            read_elf_architecture(&mut file).map(|arch| arch.into_iter().collect())
+        }
+        [b'M', b'Z', _, _] => {
+            read_pe_architecture(&mut file).map(|arch| arch.into_iter().collect())
+        }
+        [0xCA, 0xFE, 0xBA, 0xBE | 0xBF] => read_fat_mach_architectures(&mut file, magic[3] == 0xBF),
+        _ => {
+            let magic = u32::from_le_bytes(magic);
+            match magic {
+                0xFEED_FACE | 0xFEED_FACF => {
+                    let cpu_type = read_u32(&mut file, true)?;
+                    Ok(Architecture::from_mach_cpu_type(cpu_type)
+                        .into_iter()
+                        .collect())
+                }
+                0xCEFA_EDFE | 0xCFFA_EDFE => {
+                    let cpu_type = read_u32(&mut file, false)?;
+                    Ok(Architecture::from_mach_cpu_type(cpu_type)
+                        .into_iter()
+                        .collect())
+                }
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "not an ELF, PE or Mach-O binary",
+                )),
+            }
+        }
+    }
+}
+
+fn read_elf_architecture(file: &mut File) -> io::Result<Option<Architecture>> {
+    let mut ident = [0u8; 2];
+    file.read_exact(&mut ident)?;
+    let is_64_bit = ident[0] == 2;
+    let is_little_endian = ident[1] == 1;
+
+    file.seek(SeekFrom::Start(18))?;
+    let machine = read_u16(file, is_little_endian)?;
+    Ok(Architecture::from_elf_machine(
+        machine,
+        is_64_bit,
+        is_little_endian,
+    ))
+}
+
+fn read_pe_architecture(file: &mut File) -> io::Result<Option<Architecture>> {
+    file.seek(SeekFrom::Start(0x3C))?;
+    let pe_offset = read_u32(file, true)?;
+    file.seek(SeekFrom::Start(u64::from(pe_offset)))?;
+
+    let mut signature = [0u8; 4];
+    file.read_exact(&mut signature)?;
+    if signature != *b"PE\0\0" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid PE signature",
+        ));
+    }
+
+    let machine = read_u16(file, true)?;
+    Ok(Architecture::from_pe_machine(machine))
+}
+
+fn read_fat_mach_architectures(file: &mut File, is_64_bit: bool) -> io::Result<Vec<Architecture>> {
+    let count = read_u32(file, false)?;
+    let entry_size = if is_64_bit { 32 } else { 20 };
+
+    let mut architectures = Vec::new();
+    for i in 0..u64::from(count.min(64)) {
+        file.seek(SeekFrom::Start(8 + i * entry_size))?;
+        let cpu_type = read_u32(file, false)?;
+        architectures.extend(Architecture::from_mach_cpu_type(cpu_type));
+    }
+    Ok(architectures)
+}
+
+fn read_u16(file: &mut File, little_endian: bool) -> io::Result<u16> {
+    let mut buf = [0u8; 2];
+    file.read_exact(&mut buf)?;
+    Ok(if little_endian {
+        u16::from_le_bytes(buf)
+    } else {
+        u16::from_be_bytes(buf)
+    })
+}
+
+fn read_u32(file: &mut File, little_endian: bool) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    file.read_exact(&mut buf)?;
+    Ok(if little_endian {
+        u32::from_le_bytes(buf)
+    } else {
+        u32::from_be_bytes(buf)
+    })
+}
+
+/// Checks whether the given library was built for the architecture of the current process.
+///
+/// Fails with [`CheckArchitectureError::UnreadableLibrary`] if the header of the library could not be read.
+/// If the architecture of the current process is not supported by .NET, every library is assumed to be compatible.
+pub fn check_library_architecture(path: impl AsRef<Path>) -> Result<(), CheckArchitectureError> {
+    let path = path.as_ref();
+    let Some(expected) = Architecture::current() else {
+        return Ok(());
+    };
+    let found = read_binary_architectures(path).map_err(|source| UnreadableLibraryError {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    // An empty list means the library was built for an architecture .NET does not support,
+    // which can never match the current process.
+    if found.contains(&expected) {
+        Ok(())
+    } else {
+        Err(ArchitectureMismatchError {
+            path: path.to_path_buf(),
+            expected,
+            found,
+        }
+        .into())
+    }
+}
+
+/// Enum for errors that can occur while checking the architecture of a library using [`check_library_architecture`].
+#[derive(Debug, Error)]
+pub enum CheckArchitectureError {
+    /// The library was built for a different architecture than the current process.
+    #[error(transparent)]
+    ArchitectureMismatch(#[from] ArchitectureMismatchError),
+    /// The header of the library could not be read.
+    #[error(transparent)]
+    UnreadableLibrary(#[from] UnreadableLibraryError),
+}
+
+/// Error returned when the header of a library could not be read to determine its architecture.
+#[derive(Debug, Error)]
+#[error("Failed to read the header of the library {}.", .path.display())]
+pub struct UnreadableLibraryError {
+    /// The path to the library.
+    pub path: PathBuf,
+    /// The error that occured while reading the header.
+    #[source]
+    pub source: io::Error,
+}
+
+/// Error returned when a library was built for a different architecture than the current process.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error(
+    "The library {} was built for {} but the current process is {expected}.",
+    .path.display(),
+    display_architectures(.found)
+)]
+pub struct ArchitectureMismatchError {
+    /// The path to the library.
+    pub path: PathBuf,
+    /// The architecture of the current process.
+    pub expected: Architecture,
+    /// The architectures the library was built for.
+    /// Empty if the library was built for an architecture not supported by .NET.
+    pub found: Vec<Architecture>,
+}
+
+fn display_architectures(architectures: &[Architecture]) -> String {
+    if architectures.is_empty() {
+        return "an unknown architecture".to_owned();
+    }
+    architectures
+        .iter()
+        .map(Architecture::name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
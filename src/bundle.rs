@@ -0,0 +1,585 @@
+use flate2::read::DeflateDecoder;
+use std::{
+    borrow::Cow,
+    fs::{self, File},
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    path::{Component, Path, PathBuf},
+};
+use thiserror::Error;
+
+use crate::error::HostingError;
+#[cfg(feature = "netcore3_0")]
+use crate::{
+    error::HostingCallError,
+    hostfxr::{HostfxrContext, InitializedForCommandLine},
+};
+#[cfg(feature = "netcore2_1")]
+use crate::{
+    hostfxr::{AppOrHostingResult, Hostfxr},
+    pdcstring::{PdCStr, PdCString},
+};
+
+/// Signature placed directly after the bundle header offset in the apphost (SHA-256 of ".net core bundle").
+const BUNDLE_SIGNATURE: [u8; 32] = [
+    0x8b, 0x12, 0x02, 0xb9, 0x6a, 0x61, 0x20, 0x38, 0x72, 0x7b, 0x93, 0x02, 0x14, 0xd7, 0xa0, 0x32,
+    0x13, 0xf5, 0xb9, 0xe6, 0xef, 0xae, 0x33, 0x18, 0xee, 0x3b, 0x2d, 0xce, 0x24, 0xb3, 0x6a, 0xae,
+];
+
+/// The highest bundle format major version this module is able to read.
+pub const MAX_SUPPORTED_MAJOR_VERSION: u32 = 6;
+
+const RUNTIME_CONFIG_SUFFIX: &str = ".runtimeconfig.json";
+
+/// A single-file bundle produced by `dotnet publish -p:PublishSingleFile=true`.
+///
+/// The bundle consists of the apphost followed by the embedded files, a header and a manifest describing the
+/// embedded files. The offset of the header is stored in a placeholder inside the apphost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bundle {
+    path: PathBuf,
+    header_offset: u64,
+    header: BundleHeader,
+    files: Vec<BundleFileEntry>,
+}
+
+/// The header of a single-file bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleHeader {
+    /// The major version of the bundle format.
+    pub major_version: u32,
+    /// The minor version of the bundle format.
+    pub minor_version: u32,
+    /// The unique id of the bundle, used as the name of the extraction directory.
+    pub bundle_id: String,
+    /// The location of the `.deps.json` of the app inside the bundle (format version 2 and later).
+    pub deps_json: Option<BundleLocation>,
+    /// The location of the `.runtimeconfig.json` of the app inside the bundle (format version 2 and later).
+    pub runtime_config_json: Option<BundleLocation>,
+    /// The flags of the bundle (format version 2 and later).
+    pub flags: BundleFlags,
+}
+
+/// The location of a file inside a bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BundleLocation {
+    /// The offset of the file from the start of the bundle.
+    pub offset: u64,
+    /// The size of the file in bytes.
+    pub size: u64,
+}
+
+/// Flags of a single-file bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BundleFlags(pub u64);
+
+impl BundleFlags {
+    const NETCOREAPP3_COMPAT_MODE: u64 = 1;
+
+    /// Returns whether the bundle was created in .NET Core 3 compatibility mode, in which all files are extracted to disk before running the app.
+    #[must_use]
+    pub const fn netcoreapp3_compat_mode(&self) -> bool {
+        self.0 & Self::NETCOREAPP3_COMPAT_MODE != 0
+    }
+}
+
+/// The type of a file embedded in a bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BundleFileType {
+    /// A file of unknown type.
+    Unknown,
+    /// A managed assembly.
+    Assembly,
+    /// A native library.
+    NativeBinary,
+    /// The `.deps.json` of the app.
+    DepsJson,
+    /// The `.runtimeconfig.json` of the app.
+    RuntimeConfigJson,
+    /// A symbol file.
+    Symbols,
+}
+
+impl BundleFileType {
+    fn from_raw(raw: u8) -> Self {
+        match raw {
+            1 => Self::Assembly,
+            2 => Self::NativeBinary,
+            3 => Self::DepsJson,
+            4 => Self::RuntimeConfigJson,
+            5 => Self::Symbols,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// An entry of the manifest of a bundle describing an embedded file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BundleFileEntry {
+    /// The offset of the file from the start of the bundle.
+    pub offset: u64,
+    /// The uncompressed size of the file in bytes.
+    pub size: u64,
+    /// The compressed size of the file in bytes, or `0` if the file is stored uncompressed.
+    pub compressed_size: u64,
+    /// The type of the file.
+    pub file_type: BundleFileType,
+    /// The path of the file relative to the app directory, using `/` as a separator.
+    pub relative_path: String,
+}
+
+impl BundleFileEntry {
+    /// Returns whether the file is stored compressed.
+    #[must_use]
+    pub const fn is_compressed(&self) -> bool {
+        self.compressed_size != 0
+    }
+
+    /// Returns the number of bytes the file occupies inside the bundle.
+    #[must_use]
+    pub const fn stored_size(&self) -> u64 {
+        if self.is_compressed() {
+            self.compressed_size
+        } else {
+            self.size
+        }
+    }
+}
+
+impl Bundle {
+    /// Opens the single-file bundle at the given path and reads its header and manifest.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, BundleError> {
+        let path = path.as_ref();
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let header_offset = find_header_offset(&mut reader)?;
+        reader.seek(SeekFrom::Start(header_offset))?;
+        let (header, files) = read_header_and_manifest(&mut reader)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            header_offset,
+            header,
+            files,
+        })
+    }
+
+    /// Returns the path to the bundle.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the offset of the bundle header from the start of the bundle.
+    #[must_use]
+    pub const fn header_offset(&self) -> u64 {
+        self.header_offset
+    }
+
+    /// Returns the header of the bundle.
+    #[must_use]
+    pub const fn header(&self) -> &BundleHeader {
+        &self.header
+    }
+
+    /// Returns the manifest entries of the files embedded in the bundle.
+    #[must_use]
+    pub fn files(&self) -> &[BundleFileEntry] {
+        &self.files
+    }
+
+    /// Returns the manifest entry of the file with the given relative path.
+    #[must_use]
+    pub fn file(&self, relative_path: &str) -> Option<&BundleFileEntry> {
+        self.files.iter().find(|f| f.relative_path == relative_path)
+    }
+
+    /// Returns the name of the bundled app, derived from its embedded `.runtimeconfig.json`.
+    #[must_use]
+    pub fn app_name(&self) -> Option<&str> {
+        self.files
+            .iter()
+            .find(|f| f.file_type == BundleFileType::RuntimeConfigJson)
+            .and_then(|f| f.relative_path.strip_suffix(RUNTIME_CONFIG_SUFFIX))
+    }
+
+    /// Returns the path of the main assembly as passed to the hosting components when running the bundle in-place,
+    /// i.e. `<AppName>.dll` next to the bundle.
+    ///
+    /// The name of the app is taken from the manifest and falls back to the file name of the bundle.
+    #[must_use]
+    pub fn app_path(&self) -> PathBuf {
+        let app_name = self.app_name().map_or_else(
+            || {
+                self.path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy())
+                    .unwrap_or_default()
+            },
+            Cow::Borrowed,
+        );
+        self.path.with_file_name(format!("{app_name}.dll"))
+    }
+
+    /// Reads the contents of the given embedded file, decompressing it if necessary.
+    pub fn read_file(&self, entry: &BundleFileEntry) -> Result<Vec<u8>, BundleError> {
+        let mut reader = File::open(&self.path)?;
+        let file_len = reader.metadata()?.len();
+        if entry
+            .offset
+            .checked_add(entry.stored_size())
+            .is_none_or(|end| end > file_len)
+        {
+            return Err(BundleError::Malformed(
+                "embedded file extends past the end of the bundle",
+            ));
+        }
+
+        reader.seek(SeekFrom::Start(entry.offset))?;
+        let stored = reader.take(entry.stored_size());
+
+        // Only the stored bytes are known to exist, so do not trust the manifest beyond that.
+        let capacity = usize::try_from(entry.size.min(entry.stored_size())).unwrap_or(0);
+        let mut contents = Vec::with_capacity(capacity);
+        // Read one byte more than expected so that oversized files are detected without reading them completely.
+        let limit = entry.size.saturating_add(1);
+        if entry.is_compressed() {
+            DeflateDecoder::new(stored)
+                .take(limit)
+                .read_to_end(&mut contents)?;
+        } else {
+            stored.take(limit).read_to_end(&mut contents)?;
+        }
+
+        if contents.len() as u64 != entry.size {
+            return Err(BundleError::Malformed(
+                "embedded file has an unexpected size",
+            ));
+        }
+        Ok(contents)
+    }
+
+    /// Extracts the given embedded file into the given directory, preserving its relative path.
+    /// Returns the path of the extracted file.
+    pub fn extract_file(
+        &self,
+        entry: &BundleFileEntry,
+        dir: impl AsRef<Path>,
+    ) -> Result<PathBuf, BundleError> {
+        let relative_path = Path::new(&entry.relative_path);
+        if !relative_path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(BundleError::Malformed(
+                "embedded file path escapes the app directory",
+            ));
+        }
+
+        let path = dir.as_ref().join(relative_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, self.read_file(entry)?)?;
+        Ok(path)
+    }
+
+    /// Extracts all embedded files into the given directory.
+    pub fn extract_to(&self, dir: impl AsRef<Path>) -> Result<ExtractedBundle, BundleError> {
+        let dir = dir.as_ref();
+        let app_name = self
+            .app_name()
+            .ok_or(BundleError::Malformed("bundle contains no runtime config"))?;
+
+        for entry in &self.files {
+            self.extract_file(entry, dir)?;
+        }
+
+        Ok(ExtractedBundle {
+            dir: dir.to_path_buf(),
+            app_name: app_name.to_owned(),
+        })
+    }
+}
+
+/// The contents of a bundle extracted to disk using [`Bundle::extract_to`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedBundle {
+    dir: PathBuf,
+    app_name: String,
+}
+
+impl ExtractedBundle {
+    /// Returns the directory the bundle was extracted to.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the name of the bundled app.
+    #[must_use]
+    pub fn app_name(&self) -> &str {
+        &self.app_name
+    }
+
+    /// Returns the path to the main assembly of the extracted app.
+    #[must_use]
+    pub fn app_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.dll", self.app_name))
+    }
+
+    /// Returns the path to the `.runtimeconfig.json` of the extracted app.
+    #[must_use]
+    pub fn runtime_config_path(&self) -> PathBuf {
+        self.dir
+            .join(format!("{}{RUNTIME_CONFIG_SUFFIX}", self.app_name))
+    }
+}
+
+#[cfg(feature = "netcore2_1")]
+impl Hostfxr {
+    /// Runs the given single-file bundle in-place, without extracting it first.
+    ///
+    /// # Arguments
+    ///  * `bundle`:
+    ///    The bundle to run. It is used as the host path, the app path is [`Bundle::app_path`].
+    ///  * `args`:
+    ///    The command line arguments for the managed application.
+    ///
+    /// This function does not return until the application completes execution.
+    /// It will shutdown CoreCLR after the application executes.
+    /// If the application is successfully executed, this value will return the exit code of the application.
+    /// Otherwise, it will return an error code indicating the failure.
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore2_1")))]
+    pub fn run_bundle<'a>(
+        &self,
+        bundle: &Bundle,
+        args: impl IntoIterator<Item = &'a PdCStr>,
+    ) -> Result<AppOrHostingResult, BundleError> {
+        let to_pdcstring =
+            |path: &Path| PdCString::from_os_str(path).map_err(|_| HostingError::InvalidArgFailure);
+        let bundle_path = to_pdcstring(bundle.path())?;
+        let app_path = to_pdcstring(&bundle.app_path())?;
+        let dotnet_root = to_pdcstring(&self.get_dotnet_root())?;
+        let header_offset = i64::try_from(bundle.header_offset())
+            .map_err(|_| BundleError::Malformed("bundle header offset out of range"))?;
+
+        let args = std::iter::once(bundle_path.as_ptr())
+            .chain(args.into_iter().map(|s| s.as_ptr()))
+            .collect::<Vec<_>>();
+
+        let result = unsafe {
+            self.lib.hostfxr_main_bundle_startupinfo(
+                args.len().try_into().unwrap(),
+                args.as_ptr(),
+                bundle_path.as_ptr(),
+                dotnet_root.as_ptr(),
+                app_path.as_ptr(),
+                header_offset,
+            )
+        }
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_main_bundle_startupinfo",
+        })?;
+
+        Ok(AppOrHostingResult::from(result))
+    }
+}
+
+#[cfg(feature = "netcore3_0")]
+impl Hostfxr {
+    /// Initializes the hosting components for running the app of a bundle extracted using [`Bundle::extract_to`].
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
+    pub fn initialize_for_extracted_bundle(
+        &self,
+        bundle: &ExtractedBundle,
+    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingCallError> {
+        let app_path = PdCString::from_os_str(bundle.app_path()).map_err(|_| {
+            HostingCallError::new(
+                HostingError::InvalidArgFailure,
+                "initialize_for_extracted_bundle",
+            )
+            .with_hostfxr_version(self.version.clone())
+        })?;
+        self.initialize_for_dotnet_command_line(app_path)
+    }
+}
+
+fn find_header_offset(reader: &mut (impl Read + Seek)) -> Result<u64, BundleError> {
+    const CHUNK_SIZE: usize = 64 * 1024;
+    const OVERLAP: usize = BUNDLE_SIGNATURE.len() - 1;
+
+    reader.seek(SeekFrom::Start(0))?;
+    let mut buffer = vec![0u8; CHUNK_SIZE + OVERLAP];
+    let mut buffer_start = 0u64;
+    let mut filled = 0;
+
+    loop {
+        let read = reader.read(&mut buffer[filled..])?;
+        if read == 0 {
+            return Err(BundleError::NotABundle);
+        }
+        filled += read;
+
+        if let Some(position) = buffer[..filled]
+            .windows(BUNDLE_SIGNATURE.len())
+            .position(|window| window == BUNDLE_SIGNATURE)
+        {
+            let signature_offset = buffer_start + position as u64;
+            let Some(offset_position) = signature_offset.checked_sub(8) else {
+                return Err(BundleError::NotABundle);
+            };
+            reader.seek(SeekFrom::Start(offset_position))?;
+            let header_offset = read_u64(reader)?;
+            // An apphost which was not bundled still contains the placeholder with a zero offset.
+            if header_offset == 0 {
+                return Err(BundleError::NotABundle);
+            }
+            return Ok(header_offset);
+        }
+
+        if filled > OVERLAP {
+            let discard = filled - OVERLAP;
+            buffer.copy_within(discard..filled, 0);
+            buffer_start += discard as u64;
+            filled = OVERLAP;
+        }
+    }
+}
+
+fn read_header_and_manifest(
+    reader: &mut impl Read,
+) -> Result<(BundleHeader, Vec<BundleFileEntry>), BundleError> {
+    let major_version = read_u32(reader)?;
+    let minor_version = read_u32(reader)?;
+    if major_version == 0 || major_version > MAX_SUPPORTED_MAJOR_VERSION {
+        return Err(BundleError::UnsupportedVersion {
+            major: major_version,
+            minor: minor_version,
+        });
+    }
+
+    let file_count = usize::try_from(read_i32(reader)?)
+        .map_err(|_| BundleError::Malformed("negative file count"))?;
+    let bundle_id = read_string(reader)?;
+
+    let (deps_json, runtime_config_json, flags) = if major_version >= 2 {
+        let deps_json = read_location(reader)?;
+        let runtime_config_json = read_location(reader)?;
+        let flags = BundleFlags(read_u64(reader)?);
+        (deps_json, runtime_config_json, flags)
+    } else {
+        (None, None, BundleFlags::default())
+    };
+
+    let mut files = Vec::with_capacity(file_count.min(1024));
+    for _ in 0..file_count {
+        let offset = read_u64(reader)?;
+        let size = read_u64(reader)?;
+        let compressed_size = if major_version >= 6 {
+            read_u64(reader)?
+        } else {
+            0
+        };
+        let file_type = BundleFileType::from_raw(read_u8(reader)?);
+        let relative_path = read_string(reader)?;
+        files.push(BundleFileEntry {
+            offset,
+            size,
+            compressed_size,
+            file_type,
+            relative_path,
+        });
+    }
+
+    let header = BundleHeader {
+        major_version,
+        minor_version,
+        bundle_id,
+        deps_json,
+        runtime_config_json,
+        flags,
+    };
+    Ok((header, files))
+}
+
+fn read_location(reader: &mut impl Read) -> Result<Option<BundleLocation>, BundleError> {
+    let offset = read_u64(reader)?;
+    let size = read_u64(reader)?;
+    Ok((offset != 0).then_some(BundleLocation { offset, size }))
+}
+
+fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+    let mut buf = [0; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_i32(reader: &mut impl Read) -> io::Result<i32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(i32::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// The maximum length of a string in the bundle manifest.
+/// Strings are bundle ids and relative paths, so anything longer than this is treated as malformed.
+const MAX_STRING_LENGTH: usize = 64 * 1024;
+
+/// Reads a string prefixed with its 7-bit encoded length, as written by .NET's `BinaryWriter`.
+fn read_string(reader: &mut impl Read) -> Result<String, BundleError> {
+    let mut len = 0usize;
+    for shift in (0..35).step_by(7) {
+        let byte = read_u8(reader)?;
+        // the fifth byte may only contribute the remaining 4 bits of a 32-bit length
+        if shift == 28 && byte > 0x0F {
+            return Err(BundleError::Malformed("invalid string length"));
+        }
+        len |= usize::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            if len > MAX_STRING_LENGTH {
+                return Err(BundleError::Malformed("string is too long"));
+            }
+            let mut buf = vec![0; len];
+            reader.read_exact(&mut buf)?;
+            return String::from_utf8(buf)
+                .map_err(|_| BundleError::Malformed("string is not valid utf-8"));
+        }
+    }
+    Err(BundleError::Malformed("invalid string length"))
+}
+
+/// Enum for errors that can occur while reading a single-file bundle.
+#[derive(Debug, Error)]
+pub enum BundleError {
+    /// The file is not a single-file bundle.
+    #[error("The file is not a single-file bundle.")]
+    NotABundle,
+    /// The bundle uses a format version which is not supported.
+    #[error("Unsupported bundle format version {major}.{minor}.")]
+    UnsupportedVersion {
+        /// The major version of the bundle format.
+        major: u32,
+        /// The minor version of the bundle format.
+        minor: u32,
+    },
+    /// The bundle header or manifest is malformed.
+    #[error("Malformed bundle: {0}.")]
+    Malformed(&'static str),
+    /// An error occured inside the hosting components.
+    #[error(transparent)]
+    Hosting(#[from] HostingError),
+    /// An io error occured while reading the bundle.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
@@ -0,0 +1,146 @@
+use crate::{
+    bindings::hostfxr::hostfxr_initialize_parameters,
+    error::HostingError,
+    pdcstring::{PdCStr, PdCString},
+};
+use std::{fmt, sync::Arc};
+
+/// An error returned by a hosting call together with the context it failed in.
+///
+/// In addition to the [`HostingError`] code, this includes the name of the failed hosting call, the paths passed to
+/// it, the messages reported through the error writer while it ran and the version of the hostfxr library.
+/// The error code can be matched on using [`code`](HostingCallError::code) and the error converts into a
+/// [`HostingError`] using [`From`], so `?` still works in functions returning a [`HostingError`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostingCallError {
+    code: HostingError,
+    operation: &'static str,
+    args: Vec<(&'static str, PdCString)>,
+    messages: Vec<String>,
+    hostfxr_version: Option<Arc<str>>,
+}
+
+impl HostingCallError {
+    pub(crate) const fn new(code: HostingError, operation: &'static str) -> Self {
+        Self {
+            code,
+            operation,
+            args: Vec::new(),
+            messages: Vec::new(),
+            hostfxr_version: None,
+        }
+    }
+
+    pub(crate) fn with_arg(mut self, name: &'static str, value: &PdCStr) -> Self {
+        self.args.push((name, value.to_owned()));
+        self
+    }
+
+    /// Adds the paths set in the given initialization parameters as arguments.
+    pub(crate) unsafe fn with_parameters(
+        mut self,
+        parameters: *const hostfxr_initialize_parameters,
+    ) -> Self {
+        let Some(parameters) = (unsafe { parameters.as_ref() }) else {
+            return self;
+        };
+        if !parameters.host_path.is_null() {
+            self = self.with_arg("host_path", unsafe {
+                PdCStr::from_str_ptr(parameters.host_path)
+            });
+        }
+        if !parameters.dotnet_root.is_null() {
+            self = self.with_arg("dotnet_root", unsafe {
+                PdCStr::from_str_ptr(parameters.dotnet_root)
+            });
+        }
+        self
+    }
+
+    pub(crate) fn with_messages(mut self, messages: Vec<String>) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    pub(crate) fn with_hostfxr_version(mut self, version: Option<Arc<str>>) -> Self {
+        self.hostfxr_version = version;
+        self
+    }
+
+    /// Returns the plain error code.
+    pub const fn code(&self) -> HostingError {
+        self.code
+    }
+
+    /// Returns the name of the failed hosting call, e.g. `hostfxr_initialize_for_runtime_config`.
+    #[must_use]
+    pub const fn operation(&self) -> &'static str {
+        self.operation
+    }
+
+    /// Returns the arguments passed to the failed hosting call as pairs of argument name and value.
+    #[must_use]
+    pub fn args(&self) -> &[(&'static str, PdCString)] {
+        &self.args
+    }
+
+    /// Returns the value of the argument with the given name, if it was passed to the failed hosting call.
+    #[must_use]
+    pub fn arg(&self, name: &str) -> Option<&PdCStr> {
+        self.args
+            .iter()
+            .find(|(arg_name, _)| *arg_name == name)
+            .map(|(_, value)| value.as_ref())
+    }
+
+    /// Returns the messages reported through the error writer while the hosting call ran.
+    #[must_use]
+    pub fn messages(&self) -> &[String] {
+        &self.messages
+    }
+
+    /// Returns the version of the hostfxr library the call was made to, if it is known.
+    ///
+    /// The version is taken from the `host/fxr/<version>` directory hostfxr was loaded from, so it is [`None`] for
+    /// hostfxr libraries loaded from a custom layout, like the directory of a self-contained app.
+    /// See [`Hostfxr::version`](crate::hostfxr::Hostfxr::version).
+    #[must_use]
+    pub fn hostfxr_version(&self) -> Option<&str> {
+        self.hostfxr_version.as_deref()
+    }
+}
+
+impl fmt::Display for HostingCallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed", self.operation)?;
+        if let Some(version) = &self.hostfxr_version {
+            write!(f, " (hostfxr {version})")?;
+        }
+        write!(f, ": {}", self.code)?;
+        for (name, value) in &self.args {
+            write!(f, "\n  {name}: {}", value.to_string_lossy())?;
+        }
+        for message in &self.messages {
+            write!(f, "\n  {message}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for HostingCallError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.code)
+    }
+}
+
+impl From<HostingCallError> for HostingError {
+    fn from(err: HostingCallError) -> Self {
+        err.code
+    }
+}
+
+impl PartialEq<HostingError> for HostingCallError {
+    fn eq(&self, other: &HostingError) -> bool {
+        self.code == *other
+    }
+}
@@ -487,6 +487,17 @@ pub enum HostingError {
     #[display("Managed feature support for native hosting is disabled")]
     HostFeatureDisabled,
 
+    /// The loaded hostfxr library does not export the function required for the operation.
+    /// This usually means that the installed hosting components are older than the version the function was introduced in.
+    ///
+    /// This error is never returned by the hosting components themselves and shares its status code with
+    /// [`HostingError::HostApiUnsupportedVersion`].
+    #[display("The loaded hostfxr library does not export {name}.")]
+    MissingExport {
+        /// The name of the missing export.
+        name: &'static str,
+    },
+
     /// Unknown error status code.
     #[display("Unknown error status code: {_0:#08X}")]
     Unknown(u32),
@@ -654,7 +665,7 @@ impl HostingError {
             Self::BundleExtractionFailure => bindings::StatusCode::BundleExtractionFailure as u32,
             Self::BundleExtractionIOError => bindings::StatusCode::BundleExtractionIOError as u32,
             Self::LibHostDuplicateProperty => bindings::StatusCode::LibHostDuplicateProperty as u32,
-            Self::HostApiUnsupportedVersion => {
+            Self::HostApiUnsupportedVersion | Self::MissingExport { .. } => {
                 bindings::StatusCode::HostApiUnsupportedVersion as u32
             }
             Self::HostInvalidState => bindings::StatusCode::HostInvalidState as u32,
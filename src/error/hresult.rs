@@ -0,0 +1,338 @@
+use std::fmt;
+
+/// A raw `HRESULT` status code as returned by the .NET runtime and by managed code.
+///
+/// An `HRESULT` is made up of a severity bit, a facility and a facility-specific code. Common CoreCLR (`COR_E_*`)
+/// and Win32 codes are available as associated constants and can be mapped to the name of the .NET exception type
+/// the runtime uses for them with [`exception_type`](HResult::exception_type).
+///
+/// Source: [https://github.com/dotnet/runtime/blob/main/src/coreclr/inc/corerror.xml](https://github.com/dotnet/runtime/blob/main/src/coreclr/inc/corerror.xml)
+///
+/// # Example
+/// ```
+/// # use netcorehost::error::{Facility, HResult};
+/// let hr = HResult::from(0x8013_1522_u32);
+/// assert_eq!(hr, HResult::COR_E_TYPELOAD);
+/// assert!(hr.is_failure());
+/// assert_eq!(hr.facility(), Facility::Urt);
+/// assert_eq!(hr.exception_type(), Some("System.TypeLoadException"));
+/// assert_eq!(hr.to_string(), "0x80131522 (COR_E_TYPELOAD, System.TypeLoadException)");
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct HResult(pub u32);
+
+/// The facility of an [`HResult`], which identifies the subsystem the status code originates from.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Facility {
+    /// `FACILITY_NULL`, used for general status codes like `E_FAIL` or `E_POINTER`.
+    Null,
+    /// `FACILITY_RPC`
+    Rpc,
+    /// `FACILITY_DISPATCH`, used for COM dispatch errors.
+    Dispatch,
+    /// `FACILITY_STORAGE`
+    Storage,
+    /// `FACILITY_ITF`, used for interface-specific errors.
+    Itf,
+    /// `FACILITY_WIN32`, used for Win32 error codes wrapped in an `HRESULT`.
+    Win32,
+    /// `FACILITY_WINDOWS`
+    Windows,
+    /// `FACILITY_SECURITY`
+    Security,
+    /// `FACILITY_CONTROL`
+    Control,
+    /// `FACILITY_URT`, used by the .NET runtime for the `COR_E_*` codes.
+    Urt,
+    /// Some other facility.
+    Other(u16),
+}
+
+impl Facility {
+    /// Creates a new [`Facility`] from its raw value.
+    #[must_use]
+    pub const fn from_value(value: u16) -> Self {
+        match value {
+            0 => Self::Null,
+            1 => Self::Rpc,
+            2 => Self::Dispatch,
+            3 => Self::Storage,
+            4 => Self::Itf,
+            7 => Self::Win32,
+            8 => Self::Windows,
+            9 => Self::Security,
+            10 => Self::Control,
+            0x13 => Self::Urt,
+            other => Self::Other(other),
+        }
+    }
+
+    /// Returns the raw value of this facility.
+    #[must_use]
+    pub const fn value(&self) -> u16 {
+        match self {
+            Self::Null => 0,
+            Self::Rpc => 1,
+            Self::Dispatch => 2,
+            Self::Storage => 3,
+            Self::Itf => 4,
+            Self::Win32 => 7,
+            Self::Windows => 8,
+            Self::Security => 9,
+            Self::Control => 10,
+            Self::Urt => 0x13,
+            Self::Other(other) => *other,
+        }
+    }
+}
+
+macro_rules! known_hresults {
+    ($($(#[$meta:meta])* $name:ident = $value:literal => $exception:expr,)*) => {
+        impl HResult {
+            $(
+                $(#[$meta])*
+                pub const $name: Self = Self($value);
+            )*
+        }
+
+        /// The known codes as `(value, name, exception type)`. For codes with several names the first one wins.
+        const KNOWN_HRESULTS: &[(u32, &str, Option<&str>)] = &[
+            $(($value, stringify!($name), $exception),)*
+        ];
+    };
+}
+
+known_hresults! {
+    /// The operation completed successfully.
+    S_OK = 0x0000_0000 => None,
+    /// `System.Exception`
+    COR_E_EXCEPTION = 0x8013_1500 => Some("System.Exception"),
+    /// `System.SystemException`
+    COR_E_SYSTEM = 0x8013_1501 => Some("System.SystemException"),
+    /// `System.ApplicationException`
+    COR_E_APPLICATION = 0x8013_1600 => Some("System.ApplicationException"),
+    /// `System.ArgumentException`, also returned for invalid method signatures or if a method could not be found.
+    COR_E_ARGUMENT = 0x8007_0057 => Some("System.ArgumentException"),
+    /// `System.ArgumentOutOfRangeException`
+    COR_E_ARGUMENTOUTOFRANGE = 0x8013_1502 => Some("System.ArgumentOutOfRangeException"),
+    /// `System.ArithmeticException`
+    COR_E_ARITHMETIC = 0x8007_0216 => Some("System.ArithmeticException"),
+    /// `System.ArrayTypeMismatchException`
+    COR_E_ARRAYTYPEMISMATCH = 0x8013_1503 => Some("System.ArrayTypeMismatchException"),
+    /// `System.BadImageFormatException`
+    COR_E_BADIMAGEFORMAT = 0x8007_000B => Some("System.BadImageFormatException"),
+    /// `System.IO.DirectoryNotFoundException`
+    COR_E_DIRECTORYNOTFOUND = 0x8007_0003 => Some("System.IO.DirectoryNotFoundException"),
+    /// `System.DivideByZeroException`
+    COR_E_DIVIDEBYZERO = 0x8002_0012 => Some("System.DivideByZeroException"),
+    /// `System.DllNotFoundException`
+    COR_E_DLLNOTFOUND = 0x8013_1524 => Some("System.DllNotFoundException"),
+    /// `System.IO.EndOfStreamException`
+    COR_E_ENDOFSTREAM = 0x8007_0026 => Some("System.IO.EndOfStreamException"),
+    /// `System.EntryPointNotFoundException`
+    COR_E_ENTRYPOINTNOTFOUND = 0x8013_1523 => Some("System.EntryPointNotFoundException"),
+    /// `System.IO.FileLoadException`
+    COR_E_FILELOAD = 0x8013_1621 => Some("System.IO.FileLoadException"),
+    /// `System.IO.FileNotFoundException`, also returned if the assembly of a type could not be found.
+    COR_E_FILENOTFOUND = 0x8007_0002 => Some("System.IO.FileNotFoundException"),
+    /// `System.FormatException`
+    COR_E_FORMAT = 0x8013_1537 => Some("System.FormatException"),
+    /// `System.IndexOutOfRangeException`
+    COR_E_INDEXOUTOFRANGE = 0x8013_1508 => Some("System.IndexOutOfRangeException"),
+    /// `System.InsufficientExecutionStackException`
+    COR_E_INSUFFICIENTEXECUTIONSTACK = 0x8013_1578 => Some("System.InsufficientExecutionStackException"),
+    /// `System.InvalidCastException`
+    COR_E_INVALIDCAST = 0x8000_4002 => Some("System.InvalidCastException"),
+    /// `System.InvalidOperationException`, also returned for invalid assembly paths or if a method is not annotated
+    /// with `UnmanagedCallersOnly`.
+    COR_E_INVALIDOPERATION = 0x8013_1509 => Some("System.InvalidOperationException"),
+    /// `System.IO.IOException`
+    COR_E_IO = 0x8013_1620 => Some("System.IO.IOException"),
+    /// `System.Collections.Generic.KeyNotFoundException`
+    COR_E_KEYNOTFOUND = 0x8013_1577 => Some("System.Collections.Generic.KeyNotFoundException"),
+    /// `System.MissingFieldException`
+    COR_E_MISSINGFIELD = 0x8013_1511 => Some("System.MissingFieldException"),
+    /// `System.MissingMemberException`
+    COR_E_MISSINGMEMBER = 0x8013_1512 => Some("System.MissingMemberException"),
+    /// `System.MissingMethodException`
+    COR_E_MISSINGMETHOD = 0x8013_1513 => Some("System.MissingMethodException"),
+    /// `System.NotImplementedException`
+    COR_E_NOTIMPL = 0x8000_4001 => Some("System.NotImplementedException"),
+    /// `System.NotSupportedException`
+    COR_E_NOTSUPPORTED = 0x8013_1515 => Some("System.NotSupportedException"),
+    /// `System.NullReferenceException`, also returned for null arguments.
+    COR_E_NULLREFERENCE = 0x8000_4003 => Some("System.NullReferenceException"),
+    /// `System.ObjectDisposedException`
+    COR_E_OBJECTDISPOSED = 0x8013_1622 => Some("System.ObjectDisposedException"),
+    /// `System.OperationCanceledException`
+    COR_E_OPERATIONCANCELED = 0x8013_153B => Some("System.OperationCanceledException"),
+    /// `System.OutOfMemoryException`
+    COR_E_OUTOFMEMORY = 0x8007_000E => Some("System.OutOfMemoryException"),
+    /// `System.OverflowException`
+    COR_E_OVERFLOW = 0x8013_1516 => Some("System.OverflowException"),
+    /// `System.IO.PathTooLongException`
+    COR_E_PATHTOOLONG = 0x8007_00CE => Some("System.IO.PathTooLongException"),
+    /// `System.PlatformNotSupportedException`
+    COR_E_PLATFORMNOTSUPPORTED = 0x8013_1539 => Some("System.PlatformNotSupportedException"),
+    /// `System.RankException`
+    COR_E_RANK = 0x8013_1517 => Some("System.RankException"),
+    /// `System.Reflection.AmbiguousMatchException`
+    COR_E_AMBIGUOUSMATCH = 0x8000_211D => Some("System.Reflection.AmbiguousMatchException"),
+    /// `System.Reflection.TargetInvocationException`
+    COR_E_TARGETINVOCATION = 0x8013_1604 => Some("System.Reflection.TargetInvocationException"),
+    /// `System.Security.SecurityException`
+    COR_E_SECURITY = 0x8013_150A => Some("System.Security.SecurityException"),
+    /// `System.StackOverflowException`
+    COR_E_STACKOVERFLOW = 0x8007_03E9 => Some("System.StackOverflowException"),
+    /// `System.TimeoutException`
+    COR_E_TIMEOUT = 0x8013_1505 => Some("System.TimeoutException"),
+    /// `System.TypeInitializationException`
+    COR_E_TYPEINITIALIZATION = 0x8013_1534 => Some("System.TypeInitializationException"),
+    /// `System.TypeLoadException`, also returned if a type could not be found.
+    COR_E_TYPELOAD = 0x8013_1522 => Some("System.TypeLoadException"),
+    /// `System.UnauthorizedAccessException`
+    COR_E_UNAUTHORIZEDACCESS = 0x8007_0005 => Some("System.UnauthorizedAccessException"),
+    /// Unspecified failure (`System.Runtime.InteropServices.COMException`).
+    E_FAIL = 0x8000_4005 => Some("System.Runtime.InteropServices.COMException"),
+    /// Catastrophic failure (`System.Runtime.InteropServices.COMException`).
+    E_UNEXPECTED = 0x8000_FFFF => Some("System.Runtime.InteropServices.COMException"),
+    /// Same as [`COR_E_NOTIMPL`](HResult::COR_E_NOTIMPL).
+    E_NOTIMPL = 0x8000_4001 => Some("System.NotImplementedException"),
+    /// Same as [`COR_E_INVALIDCAST`](HResult::COR_E_INVALIDCAST).
+    E_NOINTERFACE = 0x8000_4002 => Some("System.InvalidCastException"),
+    /// Same as [`COR_E_NULLREFERENCE`](HResult::COR_E_NULLREFERENCE).
+    E_POINTER = 0x8000_4003 => Some("System.NullReferenceException"),
+    /// Same as [`COR_E_UNAUTHORIZEDACCESS`](HResult::COR_E_UNAUTHORIZEDACCESS).
+    E_ACCESSDENIED = 0x8007_0005 => Some("System.UnauthorizedAccessException"),
+    /// Same as [`COR_E_OUTOFMEMORY`](HResult::COR_E_OUTOFMEMORY).
+    E_OUTOFMEMORY = 0x8007_000E => Some("System.OutOfMemoryException"),
+    /// Same as [`COR_E_ARGUMENT`](HResult::COR_E_ARGUMENT).
+    E_INVALIDARG = 0x8007_0057 => Some("System.ArgumentException"),
+}
+
+impl HResult {
+    /// Creates a new [`HResult`] from the raw status code.
+    #[must_use]
+    pub const fn from_status_code(code: u32) -> Self {
+        Self(code)
+    }
+
+    /// Creates a failing [`HResult`] wrapping the given Win32 error code (`HRESULT_FROM_WIN32`).
+    #[must_use]
+    pub const fn from_win32(code: u16) -> Self {
+        Self(0x8007_0000 | code as u32)
+    }
+
+    /// Returns the underlying status code value.
+    #[must_use]
+    pub const fn value(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns whether the severity bit is set, i.e. whether the code signals a failure.
+    #[must_use]
+    pub const fn is_failure(&self) -> bool {
+        self.0 & 0x8000_0000 != 0
+    }
+
+    /// Returns whether the severity bit is not set, i.e. whether the code signals success.
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        !self.is_failure()
+    }
+
+    /// Returns whether the customer bit is set, i.e. whether the code is not defined by Microsoft.
+    #[must_use]
+    pub const fn is_customer(&self) -> bool {
+        self.0 & 0x2000_0000 != 0
+    }
+
+    /// Returns the facility of this code.
+    #[must_use]
+    pub const fn facility(&self) -> Facility {
+        Facility::from_value(((self.0 >> 16) & 0x07FF) as u16)
+    }
+
+    /// Returns the facility-specific part of this code.
+    #[must_use]
+    pub const fn code(&self) -> u16 {
+        (self.0 & 0xFFFF) as u16
+    }
+
+    /// Returns the wrapped Win32 error code if this code belongs to [`Facility::Win32`].
+    #[must_use]
+    pub const fn win32_code(&self) -> Option<u16> {
+        if matches!(self.facility(), Facility::Win32) {
+            Some(self.code())
+        } else {
+            None
+        }
+    }
+
+    fn known(self) -> Option<&'static (u32, &'static str, Option<&'static str>)> {
+        KNOWN_HRESULTS.iter().find(|(value, _, _)| *value == self.0)
+    }
+
+    /// Returns the symbolic name of this code (e.g. `COR_E_TYPELOAD`) if it is known.
+    #[must_use]
+    pub fn name(&self) -> Option<&'static str> {
+        self.known().map(|(_, name, _)| *name)
+    }
+
+    /// Returns the full name of the .NET exception type corresponding to this code
+    /// (e.g. `System.TypeLoadException`) if it is known.
+    #[must_use]
+    pub fn exception_type(&self) -> Option<&'static str> {
+        self.known().and_then(|(_, _, exception)| *exception)
+    }
+
+    /// Returns whether this code has a known meaning.
+    #[must_use]
+    pub fn is_known(&self) -> bool {
+        self.known().is_some()
+    }
+}
+
+impl fmt::Display for HResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#010X}", self.0)?;
+        match self.known() {
+            Some((_, name, Some(exception))) => write!(f, " ({name}, {exception})"),
+            Some((_, name, None)) => write!(f, " ({name})"),
+            None => write!(
+                f,
+                " (facility {:#X}, code {:#06X})",
+                self.facility().value(),
+                self.code()
+            ),
+        }
+    }
+}
+
+impl From<u32> for HResult {
+    fn from(code: u32) -> Self {
+        Self(code)
+    }
+}
+
+impl From<i32> for HResult {
+    #[allow(clippy::cast_sign_loss)]
+    fn from(code: i32) -> Self {
+        Self(code as u32)
+    }
+}
+
+impl From<HResult> for u32 {
+    fn from(code: HResult) -> Self {
+        code.0
+    }
+}
+
+impl From<HResult> for i32 {
+    #[allow(clippy::cast_possible_wrap)]
+    fn from(code: HResult) -> Self {
+        code.0 as i32
+    }
+}
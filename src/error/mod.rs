@@ -1,5 +1,14 @@
 mod hosting_result;
 pub use hosting_result::*;
 
+mod hresult;
+pub use hresult::*;
+
+#[cfg(feature = "netcore3_0")]
+mod hosting_call;
+#[cfg(feature = "netcore3_0")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
+pub use hosting_call::*;
+
 mod univ;
 pub use univ::*;
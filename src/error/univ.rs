@@ -6,6 +6,11 @@ pub enum Error {
     /// An error from the native hosting components.
     #[error(transparent)]
     Hosting(#[from] crate::error::HostingError),
+    /// An error from a hosting call including the context it failed in.
+    #[error(transparent)]
+    #[cfg(feature = "netcore3_0")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
+    HostingCall(#[from] crate::error::HostingCallError),
     /// An error while loading a function pointer to a managed method.
     #[error(transparent)]
     #[cfg(feature = "netcore3_0")]
@@ -21,6 +26,13 @@ pub enum Error {
 #[cfg(feature = "nethost")]
 impl From<crate::dlopen2::Error> for Error {
     fn from(err: crate::dlopen2::Error) -> Self {
-        Self::LoadHostfxr(crate::nethost::LoadHostfxrError::DlOpen(err))
+        Self::LoadHostfxr(crate::nethost::LoadHostfxrError::from(err))
+    }
+}
+
+#[cfg(feature = "nethost")]
+impl From<crate::hostfxr::LoadFromPathError> for Error {
+    fn from(err: crate::hostfxr::LoadFromPathError) -> Self {
+        Self::LoadHostfxr(crate::nethost::LoadHostfxrError::from(err))
     }
 }
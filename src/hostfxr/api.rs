@@ -0,0 +1,188 @@
+use crate::{
+    bindings::hostfxr::hostfxr_delegate_type,
+    error::{HostingError, HostingSuccess},
+    hostfxr::{
+        AppExit, Hostfxr, HostfxrContext, InitializedForCommandLine, InitializedForRuntimeConfig,
+        RawFnPtr,
+    },
+    pdcstring::{PdCStr, PdCString},
+};
+use std::collections::HashMap;
+
+/// The hostfxr functionality needed to initialize host contexts.
+///
+/// This trait is implemented by [`Hostfxr`] and, with the `fake` feature, by `FakeHostfxr`, which allows writing hosts
+/// that can be tested without loading a hostfxr library by being generic over this trait.
+///
+/// The methods of this trait return plain [`HostingError`]s, so the context of a failed call attached by the inherent
+/// methods of [`Hostfxr`] as a [`HostingCallError`] (the operation, its arguments and the messages reported by
+/// hostfxr) is not available through it. Call the inherent methods if that context is needed.
+///
+/// [`HostingCallError`]: crate::error::HostingCallError
+pub trait HostfxrApi {
+    /// The context type returned by [`initialize_for_runtime_config`](HostfxrApi::initialize_for_runtime_config).
+    type RuntimeConfigContext: HostfxrContextApi;
+    /// The context type returned by [`initialize_for_dotnet_command_line_with_args`](HostfxrApi::initialize_for_dotnet_command_line_with_args).
+    type CommandLineContext: HostfxrCommandLineContextApi;
+
+    /// Initializes the hosting components using a `.runtimeconfig.json` file.
+    ///
+    /// See [`Hostfxr::initialize_for_runtime_config`].
+    fn initialize_for_runtime_config(
+        &self,
+        runtime_config_path: &PdCStr,
+    ) -> Result<Self::RuntimeConfigContext, HostingError>;
+
+    /// Initializes the hosting components for a dotnet command line running an application.
+    ///
+    /// See [`Hostfxr::initialize_for_dotnet_command_line`].
+    fn initialize_for_dotnet_command_line(
+        &self,
+        app_path: &PdCStr,
+    ) -> Result<Self::CommandLineContext, HostingError> {
+        self.initialize_for_dotnet_command_line_with_args(app_path, &[])
+    }
+
+    /// Initializes the hosting components for a dotnet command line running an application with the given arguments.
+    ///
+    /// See [`Hostfxr::initialize_for_dotnet_command_line_with_args`].
+    fn initialize_for_dotnet_command_line_with_args(
+        &self,
+        app_path: &PdCStr,
+        args: &[&PdCStr],
+    ) -> Result<Self::CommandLineContext, HostingError>;
+}
+
+/// The functionality of an initialized host context.
+///
+/// This trait is implemented by [`HostfxrContext`] and, with the `fake` feature, by `FakeHostfxrContext`.
+pub trait HostfxrContextApi {
+    /// Gets whether the context is the primary hostfxr context.
+    ///
+    /// See [`HostfxrContext::is_primary`].
+    fn is_primary(&self) -> bool;
+
+    /// Gets the runtime property value for the given key of this host context.
+    ///
+    /// See [`HostfxrContext::get_runtime_property_value`].
+    fn get_runtime_property_value(&self, name: &PdCStr) -> Result<PdCString, HostingError>;
+
+    /// Sets the value of a runtime property for this host context.
+    ///
+    /// See [`HostfxrContext::set_runtime_property_value`].
+    fn set_runtime_property_value(
+        &mut self,
+        name: &PdCStr,
+        value: &PdCStr,
+    ) -> Result<(), HostingError>;
+
+    /// Remove a runtime property for this host context.
+    ///
+    /// See [`HostfxrContext::remove_runtime_property_value`].
+    fn remove_runtime_property_value(&mut self, name: &PdCStr) -> Result<(), HostingError>;
+
+    /// Get all runtime properties for this host context.
+    ///
+    /// See [`HostfxrContext::runtime_properties`].
+    fn runtime_properties(&self) -> Result<HashMap<PdCString, PdCString>, HostingError>;
+
+    /// Gets a delegate from the currently loaded `CoreCLR` or from a newly created one.
+    ///
+    /// See [`HostfxrContext::get_runtime_delegate`].
+    fn get_runtime_delegate(&self, r#type: hostfxr_delegate_type)
+    -> Result<RawFnPtr, HostingError>;
+
+    /// Closes an initialized host context.
+    ///
+    /// See [`HostfxrContext::close`].
+    fn close(self) -> Result<HostingSuccess, HostingError>
+    where
+        Self: Sized;
+}
+
+/// The functionality of a host context initialized for the dotnet command line.
+pub trait HostfxrCommandLineContextApi: HostfxrContextApi {
+    /// Load the dotnet runtime and run the application.
+    ///
+    /// See [`HostfxrContext::run_app`].
+    #[must_use]
+    fn run_app(self) -> AppExit
+    where
+        Self: Sized;
+}
+
+// the context of `HostingCallError` is dropped here, see the documentation of `HostfxrApi`.
+impl HostfxrApi for Hostfxr {
+    type RuntimeConfigContext = HostfxrContext<InitializedForRuntimeConfig>;
+    type CommandLineContext = HostfxrContext<InitializedForCommandLine>;
+
+    fn initialize_for_runtime_config(
+        &self,
+        runtime_config_path: &PdCStr,
+    ) -> Result<Self::RuntimeConfigContext, HostingError> {
+        Hostfxr::initialize_for_runtime_config(self, runtime_config_path).map_err(Into::into)
+    }
+
+    fn initialize_for_dotnet_command_line(
+        &self,
+        app_path: &PdCStr,
+    ) -> Result<Self::CommandLineContext, HostingError> {
+        Hostfxr::initialize_for_dotnet_command_line(self, app_path).map_err(Into::into)
+    }
+
+    fn initialize_for_dotnet_command_line_with_args(
+        &self,
+        app_path: &PdCStr,
+        args: &[&PdCStr],
+    ) -> Result<Self::CommandLineContext, HostingError> {
+        Hostfxr::initialize_for_dotnet_command_line_with_args(self, app_path, args.iter())
+            .map_err(Into::into)
+    }
+}
+
+impl<I> HostfxrContextApi for HostfxrContext<I> {
+    fn is_primary(&self) -> bool {
+        HostfxrContext::is_primary(self)
+    }
+
+    fn get_runtime_property_value(&self, name: &PdCStr) -> Result<PdCString, HostingError> {
+        HostfxrContext::get_runtime_property_value(self, name).map(PdCStr::to_owned)
+    }
+
+    fn set_runtime_property_value(
+        &mut self,
+        name: &PdCStr,
+        value: &PdCStr,
+    ) -> Result<(), HostingError> {
+        HostfxrContext::set_runtime_property_value(self, name, value)
+    }
+
+    fn remove_runtime_property_value(&mut self, name: &PdCStr) -> Result<(), HostingError> {
+        HostfxrContext::remove_runtime_property_value(self, name)
+    }
+
+    fn runtime_properties(&self) -> Result<HashMap<PdCString, PdCString>, HostingError> {
+        let properties = HostfxrContext::runtime_properties(self)?;
+        Ok(properties
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), value.to_owned()))
+            .collect())
+    }
+
+    fn get_runtime_delegate(
+        &self,
+        r#type: hostfxr_delegate_type,
+    ) -> Result<RawFnPtr, HostingError> {
+        HostfxrContext::get_runtime_delegate(self, r#type)
+    }
+
+    fn close(self) -> Result<HostingSuccess, HostingError> {
+        HostfxrContext::close(self)
+    }
+}
+
+impl HostfxrCommandLineContextApi for HostfxrContext<InitializedForCommandLine> {
+    fn run_app(self) -> AppExit {
+        HostfxrContext::run_app(self)
+    }
+}
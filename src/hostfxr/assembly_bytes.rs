@@ -0,0 +1,133 @@
+#[cfg(feature = "net8_0")]
+use crate::error::HostingError;
+#[cfg(feature = "net8_0")]
+use thiserror::Error;
+
+/// The contents of an assembly and optionally its symbols (`.pdb`), which can be loaded from memory.
+///
+/// # Example
+/// ```
+/// # use netcorehost::hostfxr::AssemblyBytes;
+/// let assembly = std::fs::read("Plugin.dll").unwrap_or_default();
+/// let symbols = std::fs::read("Plugin.pdb").ok();
+///
+/// let bytes = AssemblyBytes::new(&assembly);
+/// assert_eq!(bytes.symbols(), None);
+/// let bytes = AssemblyBytes::with_optional_symbols(&assembly, symbols.as_deref());
+/// # let _ = bytes;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+pub struct AssemblyBytes<'a> {
+    assembly: &'a [u8],
+    symbols: Option<&'a [u8]>,
+}
+
+impl<'a> AssemblyBytes<'a> {
+    /// Creates a new [`AssemblyBytes`] for an assembly without symbols.
+    #[must_use]
+    pub const fn new(assembly: &'a [u8]) -> Self {
+        Self {
+            assembly,
+            symbols: None,
+        }
+    }
+
+    /// Creates a new [`AssemblyBytes`] for an assembly with the given symbols.
+    #[must_use]
+    pub const fn with_symbols(assembly: &'a [u8], symbols: &'a [u8]) -> Self {
+        Self {
+            assembly,
+            symbols: Some(symbols),
+        }
+    }
+
+    /// Creates a new [`AssemblyBytes`] for an assembly with symbols if they are present.
+    #[must_use]
+    pub const fn with_optional_symbols(assembly: &'a [u8], symbols: Option<&'a [u8]>) -> Self {
+        Self { assembly, symbols }
+    }
+
+    /// Returns the contents of the assembly.
+    #[must_use]
+    pub const fn assembly(&self) -> &'a [u8] {
+        self.assembly
+    }
+
+    /// Returns the contents of the symbols, if any.
+    #[must_use]
+    pub const fn symbols(&self) -> Option<&'a [u8]> {
+        self.symbols
+    }
+}
+
+impl<'a> From<&'a [u8]> for AssemblyBytes<'a> {
+    fn from(assembly: &'a [u8]) -> Self {
+        Self::new(assembly)
+    }
+}
+
+impl<'a, const N: usize> From<&'a [u8; N]> for AssemblyBytes<'a> {
+    fn from(assembly: &'a [u8; N]) -> Self {
+        Self::new(assembly)
+    }
+}
+
+impl<'a> From<&'a Vec<u8>> for AssemblyBytes<'a> {
+    fn from(assembly: &'a Vec<u8>) -> Self {
+        Self::new(assembly)
+    }
+}
+
+/// Embeds an assembly and optionally its symbols in the binary and evaluates to an
+/// [`AssemblyBytes`](crate::hostfxr::AssemblyBytes) referencing them.
+///
+/// The paths are resolved relative to the current file like with [`include_bytes`].
+///
+/// # Example
+/// ```ignore
+/// # use netcorehost::{include_assembly, hostfxr::AssemblyBytes};
+/// static PLUGIN: AssemblyBytes<'static> = include_assembly!("../managed/Plugin.dll", "../managed/Plugin.pdb");
+/// static DEPENDENCY: AssemblyBytes<'static> = include_assembly!("../managed/Dependency.dll");
+/// ```
+#[macro_export]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+macro_rules! include_assembly {
+    ($assembly:expr $(,)?) => {
+        $crate::hostfxr::AssemblyBytes::new(::core::include_bytes!($assembly))
+    };
+    ($assembly:expr, $symbols:expr $(,)?) => {
+        $crate::hostfxr::AssemblyBytes::with_symbols(
+            ::core::include_bytes!($assembly),
+            ::core::include_bytes!($symbols),
+        )
+    };
+}
+
+/// An error returned when one of several assemblies could not be loaded using
+/// [`HostfxrContext::load_assemblies_from_memory`].
+///
+/// [`HostfxrContext::load_assemblies_from_memory`]: crate::hostfxr::HostfxrContext::load_assemblies_from_memory
+#[cfg(feature = "net8_0")]
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[error("Failed to load assembly {index}: {source}")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net8_0")))]
+pub struct LoadAssembliesError {
+    pub(crate) index: usize,
+    #[source]
+    pub(crate) source: HostingError,
+}
+
+#[cfg(feature = "net8_0")]
+impl LoadAssembliesError {
+    /// Returns the index of the assembly that failed to load. All assemblies before it have been loaded.
+    #[must_use]
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the error returned while loading the assembly.
+    pub const fn error(&self) -> HostingError {
+        self.source
+    }
+}
@@ -4,8 +4,9 @@ use crate::{
     },
     error::{HostingError, HostingResult, HostingSuccess},
     hostfxr::{
-        AppOrHostingResult, AssemblyDelegateLoader, DelegateLoader, Hostfxr, HostfxrLibrary,
-        RawFnPtr, SharedHostfxrLibrary,
+        AppExit, AppExitClassification, AppOrHostingResult, AssemblyDelegateLoader, DelegateLoader,
+        Hostfxr, HostfxrLibrary, RawFnPtr, SharedHostfxrLibrary, diagnostics::hosting_call,
+        error_writer::count_reported_errors, prepare_current_thread,
     },
     pdcstring::PdCString,
 };
@@ -15,6 +16,7 @@ use crate::bindings::hostfxr::get_function_pointer_fn;
 #[cfg(feature = "net8_0")]
 use crate::{
     bindings::hostfxr::{load_assembly_bytes_fn, load_assembly_fn},
+    hostfxr::{AssemblyBytes, LoadAssembliesError, LoadedAssembly},
     pdcstring::PdCStr,
 };
 
@@ -22,9 +24,12 @@ use std::{
     cell::Cell,
     ffi::c_void,
     fmt::{self, Debug},
+    io,
     marker::PhantomData,
     mem::{self, ManuallyDrop, MaybeUninit},
     ptr::NonNull,
+    sync::mpsc,
+    thread::{self, JoinHandle},
 };
 
 #[cfg(feature = "net8_0")]
@@ -180,15 +185,22 @@ impl<I> HostfxrContext<I> {
         &self,
         r#type: hostfxr_delegate_type,
     ) -> Result<RawFnPtr, HostingError> {
+        prepare_current_thread(&self.hostfxr);
         let mut delegate = MaybeUninit::uninit();
-        let result = unsafe {
-            self.hostfxr.hostfxr_get_runtime_delegate(
-                self.handle.as_raw(),
-                r#type,
-                delegate.as_mut_ptr(),
-            )
-        }
-        .unwrap();
+        let result = hosting_call!(
+            "hostfxr_get_runtime_delegate",
+            delegate_type = format_args!("{type:?}");
+            unsafe {
+                self.hostfxr.hostfxr_get_runtime_delegate(
+                    self.handle.as_raw(),
+                    r#type,
+                    delegate.as_mut_ptr(),
+                )
+            }
+        )
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_get_runtime_delegate",
+        })?;
 
         HostingResult::from(result).into_result()?;
 
@@ -260,6 +272,7 @@ impl<I> HostfxrContext<I> {
     ) -> Result<(), HostingError> {
         let assembly_path = assembly_path.as_ref();
         let load_assembly = self.get_load_assembly_delegate()?;
+        prepare_current_thread(&self.hostfxr);
         let result = unsafe { load_assembly(assembly_path.as_ptr(), ptr::null(), ptr::null()) };
         HostingResult::from(result).into_result()?;
         Ok(())
@@ -273,27 +286,101 @@ impl<I> HostfxrContext<I> {
     ///
     /// [`AssemblyDependencyResolver`]: https://learn.microsoft.com/en-us/dotnet/api/system.runtime.loader.assemblydependencyresolver
     /// [`AssemblyLoadContext.Resolving`]: https://learn.microsoft.com/en-us/dotnet/api/system.runtime.loader.assemblyloadcontext.resolving?view=net-7.0
+    ///
+    /// Symbols are only passed to the runtime if `symbols_bytes` is not empty.
+    /// The name of the assembly is read from its metadata after it was loaded and the returned [`LoadedAssembly`] can
+    /// be used to load function pointers without repeating it.
     #[cfg(feature = "net8_0")]
     pub fn load_assembly_from_bytes(
         &self,
         assembly_bytes: impl AsRef<[u8]>,
         symbols_bytes: impl AsRef<[u8]>,
-    ) -> Result<(), HostingError> {
+    ) -> Result<LoadedAssembly, HostingError> {
         let symbols_bytes = symbols_bytes.as_ref();
-        let assembly_bytes = assembly_bytes.as_ref();
+        self.load_assembly_from_memory(AssemblyBytes::with_optional_symbols(
+            assembly_bytes.as_ref(),
+            (!symbols_bytes.is_empty()).then_some(symbols_bytes),
+        ))
+    }
+
+    /// Loads the given assembly in the default load context from memory.
+    /// See [`load_assembly_from_bytes`](HostfxrContext::load_assembly_from_bytes) for details.
+    #[cfg(feature = "net8_0")]
+    pub fn load_assembly_from_memory(
+        &self,
+        assembly: AssemblyBytes<'_>,
+    ) -> Result<LoadedAssembly, HostingError> {
+        let loader = self.get_delegate_loader()?;
         let load_assembly_bytes = self.get_load_assembly_bytes_delegate()?;
+        prepare_current_thread(&self.hostfxr);
+        Self::load_assembly_bytes(load_assembly_bytes, &loader, assembly)
+    }
+
+    /// Loads the given assemblies in the default load context from memory in order, so dependencies have to come
+    /// before the assemblies depending on them.
+    /// See [`load_assembly_from_bytes`](HostfxrContext::load_assembly_from_bytes) for details.
+    ///
+    /// Loading stops at the first assembly that fails to load, the assemblies loaded before it stay loaded.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use netcorehost::{include_assembly, nethost, pdcstr};
+    /// let hostfxr = nethost::load_hostfxr()?;
+    /// let context = hostfxr.initialize_for_runtime_config(pdcstr!("Test.runtimeconfig.json"))?;
+    /// # #[cfg(any())]
+    /// context.load_assemblies_from_memory([
+    ///     include_assembly!("Dependency.dll"),
+    ///     include_assembly!("Plugin.dll", "Plugin.pdb"),
+    /// ])?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "net8_0")]
+    pub fn load_assemblies_from_memory<'a>(
+        &self,
+        assemblies: impl IntoIterator<Item = AssemblyBytes<'a>>,
+    ) -> Result<Vec<LoadedAssembly>, LoadAssembliesError> {
+        let mut assemblies = assemblies.into_iter().enumerate().peekable();
+        if assemblies.peek().is_none() {
+            return Ok(Vec::new());
+        }
+        let delegates = self
+            .get_delegate_loader()
+            .and_then(|loader| Ok((loader, self.get_load_assembly_bytes_delegate()?)));
+        let (loader, load_assembly_bytes) = delegates.map_err(|err| LoadAssembliesError {
+            index: 0,
+            source: err,
+        })?;
+        prepare_current_thread(&self.hostfxr);
+        assemblies
+            .map(|(index, assembly)| {
+                Self::load_assembly_bytes(load_assembly_bytes, &loader, assembly)
+                    .map_err(|source| LoadAssembliesError { index, source })
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "net8_0")]
+    fn load_assembly_bytes(
+        load_assembly_bytes: load_assembly_bytes_fn,
+        loader: &DelegateLoader,
+        assembly: AssemblyBytes<'_>,
+    ) -> Result<LoadedAssembly, HostingError> {
+        let assembly_bytes = assembly.assembly();
+        let (symbols_ptr, symbols_len) = assembly.symbols().map_or((ptr::null(), 0), |symbols| {
+            (symbols.as_ptr(), symbols.len())
+        });
         let result = unsafe {
             load_assembly_bytes(
                 assembly_bytes.as_ptr(),
                 assembly_bytes.len(),
-                symbols_bytes.as_ptr(),
-                symbols_bytes.len(),
+                symbols_ptr,
+                symbols_len,
                 ptr::null_mut(),
                 ptr::null_mut(),
             )
         };
         HostingResult::from(result).into_result()?;
-        Ok(())
+        Ok(LoadedAssembly::new(loader.clone(), assembly_bytes))
     }
 
     /// Closes an initialized host context.
@@ -306,20 +393,121 @@ impl<I> HostfxrContext<I> {
 
     /// Internal non-consuming version of [`close`](HostfxrContext::close)
     unsafe fn close_raw(&self) -> Result<HostingSuccess, HostingError> {
-        let result = unsafe { self.hostfxr.hostfxr_close(self.handle.as_raw()) }.unwrap();
+        let result = hosting_call!(
+            "hostfxr_close";
+            unsafe { self.hostfxr.hostfxr_close(self.handle.as_raw()) }
+        )
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_close",
+        })?;
         HostingResult::from(result).into_result()
     }
 }
 
+/// The name of the thread spawned by [`HostfxrContext::run_app_on_thread`].
+const APP_THREAD_NAME: &str = "dotnet-app";
+
 impl HostfxrContext<InitializedForCommandLine> {
     /// Load the dotnet runtime and run the application.
     ///
     /// # Return value
-    /// If the app was successfully run, the exit code of the application. Otherwise, the error code result.
+    /// If the app was successfully run, the exit code of the application. Otherwise, the error of the hosting
+    /// components. As both are reported using the same value, the result is classified using
+    /// [`AppExitClassification::Heuristic`], see [`AppExit::classify`] for details.
+    /// If hostfxr does not export `hostfxr_run_app`, [`HostingError::MissingExport`] is returned.
     #[must_use]
-    pub fn run_app(self) -> AppOrHostingResult {
-        let result = unsafe { self.hostfxr.hostfxr_run_app(self.handle.as_raw()) }.unwrap();
-        AppOrHostingResult::from(result)
+    pub fn run_app(self) -> AppExit {
+        self.run_app_with_classification(AppExitClassification::Heuristic)
+    }
+
+    /// Load the dotnet runtime and run the application on a new thread named `dotnet-app` with the given stack size
+    /// in bytes. The result can be retrieved by joining the returned handle.
+    ///
+    /// Managed code runs on the stack of the thread which calls [`run_app`](HostfxrContext::run_app), so apps which
+    /// recurse deeply need a larger stack than Rust threads have by default.
+    ///
+    /// If the `utils` feature is enabled on unix platforms, the alternate signal stack of the new thread is set to
+    /// [`altstack::RECOMMENDED_SIZE`](crate::utils::altstack::RECOMMENDED_SIZE) while the app runs, so that the
+    /// runtime can handle signals on it. Use [`run_app_on_thread_with_altstack`](HostfxrContext::run_app_on_thread_with_altstack) to
+    /// choose a different configuration.
+    ///
+    /// # Errors
+    /// Returns an error if the thread could not be spawned or its alternate signal stack could not be configured.
+    pub fn run_app_on_thread(self, stack_size: usize) -> io::Result<JoinHandle<AppExit>> {
+        #[cfg(all(unix, feature = "utils"))]
+        return self.run_app_on_thread_with_altstack(
+            stack_size,
+            crate::utils::altstack::State::Enabled {
+                size: crate::utils::altstack::RECOMMENDED_SIZE,
+            },
+        );
+        #[cfg(not(all(unix, feature = "utils")))]
+        return self.spawn_app_thread(stack_size, || Ok(()));
+    }
+
+    /// Load the dotnet runtime and run the application on a new thread with the given stack size in bytes and the
+    /// given alternate signal stack configuration.
+    /// See [`run_app_on_thread`](HostfxrContext::run_app_on_thread) for details.
+    ///
+    /// # Errors
+    /// Returns an error if the thread could not be spawned or its alternate signal stack could not be configured.
+    #[cfg(all(unix, feature = "utils"))]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(all(unix, feature = "utils"))))]
+    pub fn run_app_on_thread_with_altstack(
+        self,
+        stack_size: usize,
+        altstack: crate::utils::altstack::State,
+    ) -> io::Result<JoinHandle<AppExit>> {
+        self.spawn_app_thread(stack_size, move || {
+            crate::utils::altstack::AltStackGuard::install(altstack)
+        })
+    }
+
+    /// Spawns the app thread, which runs `setup` and keeps its result alive while running the app.
+    fn spawn_app_thread<G>(
+        self,
+        stack_size: usize,
+        setup: impl FnOnce() -> io::Result<G> + Send + 'static,
+    ) -> io::Result<JoinHandle<AppExit>> {
+        let (setup_sender, setup_receiver) = mpsc::sync_channel(1);
+        let handle = thread::Builder::new()
+            .name(APP_THREAD_NAME.to_owned())
+            .stack_size(stack_size)
+            .spawn(move || {
+                let _guard = match setup() {
+                    Ok(guard) => {
+                        let _ = setup_sender.send(Ok(()));
+                        guard
+                    }
+                    Err(err) => {
+                        let _ = setup_sender.send(Err(err));
+                        // the handle is dropped by the spawning thread in this case, so the result is never observed.
+                        return AppExit::HostingFailed(HostingError::HostInvalidState);
+                    }
+                };
+                self.run_app()
+            })?;
+
+        match setup_receiver.recv() {
+            Ok(Ok(())) => Ok(handle),
+            Ok(Err(err)) => Err(err),
+            Err(_) => Err(io::Error::other("app thread exited during setup")),
+        }
+    }
+
+    /// Load the dotnet runtime and run the application.
+    /// Like [`run_app`](HostfxrContext::run_app), but classifies the result using the given mode.
+    #[must_use]
+    pub fn run_app_with_classification(self, mode: AppExitClassification) -> AppExit {
+        prepare_current_thread(&self.hostfxr);
+        let (result, reported_errors) =
+            count_reported_errors(|| unsafe { self.hostfxr.hostfxr_run_app(self.handle.as_raw()) });
+        match result {
+            Some(result) => AppOrHostingResult::from(result).classify(reported_errors > 0, mode),
+            None => AppExit::HostingFailed(HostingError::MissingExport {
+                name: "hostfxr_run_app",
+            }),
+        }
     }
 }
 
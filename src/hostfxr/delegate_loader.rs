@@ -3,15 +3,18 @@ use crate::{
         char_t,
         hostfxr::{component_entry_point_fn, load_assembly_and_get_function_pointer_fn},
     },
-    error::{HostingError, HostingResult, HostingSuccess},
+    error::{HResult, HostingError, HostingResult, HostingSuccess},
     pdcstring::{PdCStr, PdCString},
 };
 use fn_ptr::{WithAbi, abi::System};
-use num_enum::TryFromPrimitive;
-use std::{convert::TryFrom, mem::MaybeUninit, path::Path, ptr};
+use std::{mem::MaybeUninit, path::Path, ptr};
 use thiserror::Error;
 
-use super::{FnPtr, ManagedFunction, RawFnPtr, SharedHostfxrLibrary};
+use super::{
+    FnPtr, FunctionResolutionError, ManagedFunction, RawFnPtr, ResolutionErrorKind,
+    SharedHostfxrLibrary, diagnostics::hosting_call, prepare_current_thread,
+    resolution::FunctionRequest,
+};
 
 #[cfg(feature = "net5_0")]
 use crate::bindings::hostfxr::{UNMANAGED_CALLERS_ONLY_METHOD, get_function_pointer_fn};
@@ -23,7 +26,10 @@ pub type ManagedFunctionWithUnknownSignature = ManagedFunction<RawFnPtr>;
 
 /// A struct for loading pointers to managed functions for a given [`HostfxrContext`].
 ///
+/// Type names can be given as [`AssemblyQualifiedName`]s to validate them before they are passed to the runtime.
+///
 /// [`HostfxrContext`]: super::HostfxrContext
+/// [`AssemblyQualifiedName`]: crate::type_name::AssemblyQualifiedName
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
 pub struct DelegateLoader {
     pub(crate) get_load_assembly_and_get_function_pointer:
@@ -49,66 +55,86 @@ impl Clone for DelegateLoader {
 impl DelegateLoader {
     unsafe fn load_assembly_and_get_function_pointer_raw(
         &self,
-        assembly_path: *const char_t,
-        type_name: *const char_t,
-        method_name: *const char_t,
+        request: &FunctionRequest<'_>,
         delegate_type_name: *const char_t,
     ) -> Result<RawFnPtr, GetManagedFunctionError> {
+        let Some(assembly_path) = request.assembly_path else {
+            unreachable!("requests for loading an assembly have an assembly path")
+        };
+        Self::validate_assembly_path(assembly_path, request)?;
+        prepare_current_thread(&self.hostfxr);
         let mut delegate = MaybeUninit::uninit();
 
-        let result = unsafe {
-            (self.get_load_assembly_and_get_function_pointer)(
-                assembly_path,
-                type_name,
-                method_name,
-                delegate_type_name,
-                ptr::null(),
-                delegate.as_mut_ptr(),
-            )
-        };
-        GetManagedFunctionError::from_status_code(result)?;
+        let result = hosting_call!(
+            "load_assembly_and_get_function_pointer",
+            assembly_path = assembly_path,
+            type_name = request.type_name,
+            method_name = request.method_name;
+            unsafe {
+                (self.get_load_assembly_and_get_function_pointer)(
+                    assembly_path.as_ptr(),
+                    request.type_name.as_ptr(),
+                    request.method_name.as_ptr(),
+                    delegate_type_name,
+                    ptr::null(),
+                    delegate.as_mut_ptr(),
+                )
+            }
+        );
+        Self::check_status(result, request)?;
 
         Ok(unsafe { delegate.assume_init() }.cast())
     }
 
+    fn check_status(
+        result: i32,
+        request: &FunctionRequest<'_>,
+    ) -> Result<HostingSuccess, GetManagedFunctionError> {
+        match HostingResult::known_from_status_code(result as u32) {
+            Ok(HostingResult(Ok(code))) => Ok(code),
+            Ok(HostingResult(Err(code))) => Err(GetManagedFunctionError::Hosting(code)),
+            Err(code) => Err(request.diagnose(HResult(code)).into()),
+        }
+    }
+
     fn validate_assembly_path(
-        assembly_path: impl AsRef<PdCStr>,
+        assembly_path: &PdCStr,
+        request: &FunctionRequest<'_>,
     ) -> Result<(), GetManagedFunctionError> {
-        #[cfg(windows)]
-        let assembly_path = assembly_path.as_ref().to_os_string();
-
-        #[cfg(not(windows))]
-        let assembly_path = <std::ffi::OsStr as std::os::unix::prelude::OsStrExt>::from_bytes(
-            assembly_path.as_ref().as_slice(),
-        );
-
-        if Path::new(&assembly_path).exists() {
+        if Path::new(&assembly_path.to_os_string()).exists() {
             Ok(())
         } else {
-            Err(GetManagedFunctionError::AssemblyNotFound)
+            Err(request
+                .error(ResolutionErrorKind::AssemblyNotFound, None)
+                .into())
         }
     }
 
     #[cfg(feature = "net5_0")]
     unsafe fn get_function_pointer_raw(
         &self,
-        type_name: *const char_t,
-        method_name: *const char_t,
+        request: &FunctionRequest<'_>,
         delegate_type_name: *const char_t,
     ) -> Result<RawFnPtr, GetManagedFunctionError> {
+        prepare_current_thread(&self.hostfxr);
         let mut delegate = MaybeUninit::uninit();
 
-        let result = unsafe {
-            (self.get_function_pointer)(
-                type_name,
-                method_name,
-                delegate_type_name,
-                ptr::null(),
-                ptr::null(),
-                delegate.as_mut_ptr(),
-            )
-        };
-        GetManagedFunctionError::from_status_code(result)?;
+        let result = hosting_call!(
+            "get_function_pointer",
+            type_name = request.type_name,
+            method_name = request.method_name;
+            unsafe {
+                (self.get_function_pointer)(
+                    request.type_name.as_ptr(),
+                    request.method_name.as_ptr(),
+                    delegate_type_name,
+                    ptr::null(),
+                    ptr::null(),
+                    delegate.as_mut_ptr(),
+                )
+            }
+        );
+        Self::check_status(result, request)?;
 
         Ok(unsafe { delegate.assume_init() }.cast())
     }
@@ -132,18 +158,20 @@ impl DelegateLoader {
     pub fn load_assembly_and_get_function<F: FnPtr + WithAbi<System>>(
         &self,
         assembly_path: &PdCStr,
-        type_name: &PdCStr,
+        type_name: impl AsRef<PdCStr>,
         method_name: &PdCStr,
-        delegate_type_name: &PdCStr,
+        delegate_type_name: impl AsRef<PdCStr>,
     ) -> Result<ManagedFunction<<F as WithAbi<System>>::F>, GetManagedFunctionError> {
-        Self::validate_assembly_path(assembly_path)?;
+        let delegate_type_name = delegate_type_name.as_ref();
+        let request = FunctionRequest {
+            assembly_path: Some(assembly_path),
+            type_name: type_name.as_ref(),
+            method_name,
+            unmanaged_callers_only: false,
+            delegate_type_name: Some(delegate_type_name),
+        };
         let function = unsafe {
-            self.load_assembly_and_get_function_pointer_raw(
-                assembly_path.as_ptr(),
-                type_name.as_ptr(),
-                method_name.as_ptr(),
-                delegate_type_name.as_ptr(),
-            )
+            self.load_assembly_and_get_function_pointer_raw(&request, delegate_type_name.as_ptr())
         }?;
         Ok(ManagedFunction(unsafe {
             <<F as WithAbi<System>>::F>::from_ptr(function)
@@ -168,18 +196,18 @@ impl DelegateLoader {
     pub fn load_assembly_and_get_function_with_default_signature(
         &self,
         assembly_path: &PdCStr,
-        type_name: &PdCStr,
+        type_name: impl AsRef<PdCStr>,
         method_name: &PdCStr,
     ) -> Result<ManagedFunctionWithDefaultSignature, GetManagedFunctionError> {
-        Self::validate_assembly_path(assembly_path)?;
-        let function = unsafe {
-            self.load_assembly_and_get_function_pointer_raw(
-                assembly_path.as_ptr(),
-                type_name.as_ptr(),
-                method_name.as_ptr(),
-                ptr::null(),
-            )
-        }?;
+        let request = FunctionRequest {
+            assembly_path: Some(assembly_path),
+            type_name: type_name.as_ref(),
+            method_name,
+            unmanaged_callers_only: false,
+            delegate_type_name: None,
+        };
+        let function =
+            unsafe { self.load_assembly_and_get_function_pointer_raw(&request, ptr::null()) }?;
         Ok(ManagedFunction(unsafe { FnPtr::from_ptr(function) }))
     }
 
@@ -207,17 +235,18 @@ impl DelegateLoader {
     >(
         &self,
         assembly_path: &PdCStr,
-        type_name: &PdCStr,
+        type_name: impl AsRef<PdCStr>,
         method_name: &PdCStr,
     ) -> Result<ManagedFunction<<F as WithAbi<System>>::F>, GetManagedFunctionError> {
-        Self::validate_assembly_path(assembly_path)?;
+        let request = FunctionRequest {
+            assembly_path: Some(assembly_path),
+            type_name: type_name.as_ref(),
+            method_name,
+            unmanaged_callers_only: true,
+            delegate_type_name: None,
+        };
         let function = unsafe {
-            self.load_assembly_and_get_function_pointer_raw(
-                assembly_path.as_ptr(),
-                type_name.as_ptr(),
-                method_name.as_ptr(),
-                UNMANAGED_CALLERS_ONLY_METHOD,
-            )
+            self.load_assembly_and_get_function_pointer_raw(&request, UNMANAGED_CALLERS_ONLY_METHOD)
         }?;
         Ok(ManagedFunction(unsafe {
             <<F as WithAbi<System>>::F>::from_ptr(function)
@@ -238,17 +267,20 @@ impl DelegateLoader {
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
     pub fn get_function<F: FnPtr + WithAbi<System>>(
         &self,
-        type_name: &PdCStr,
+        type_name: impl AsRef<PdCStr>,
         method_name: &PdCStr,
-        delegate_type_name: &PdCStr,
+        delegate_type_name: impl AsRef<PdCStr>,
     ) -> Result<ManagedFunction<<F as WithAbi<System>>::F>, GetManagedFunctionError> {
-        let function = unsafe {
-            self.get_function_pointer_raw(
-                type_name.as_ptr(),
-                method_name.as_ptr(),
-                delegate_type_name.as_ptr(),
-            )
-        }?;
+        let delegate_type_name = delegate_type_name.as_ref();
+        let request = FunctionRequest {
+            assembly_path: None,
+            type_name: type_name.as_ref(),
+            method_name,
+            unmanaged_callers_only: false,
+            delegate_type_name: Some(delegate_type_name),
+        };
+        let function =
+            unsafe { self.get_function_pointer_raw(&request, delegate_type_name.as_ptr()) }?;
         Ok(ManagedFunction(unsafe {
             <<F as WithAbi<System>>::F>::from_ptr(function)
         }))
@@ -266,12 +298,17 @@ impl DelegateLoader {
     #[cfg(feature = "net5_0")]
     pub fn get_function_with_default_signature(
         &self,
-        type_name: &PdCStr,
+        type_name: impl AsRef<PdCStr>,
         method_name: &PdCStr,
     ) -> Result<ManagedFunctionWithDefaultSignature, GetManagedFunctionError> {
-        let function = unsafe {
-            self.get_function_pointer_raw(type_name.as_ptr(), method_name.as_ptr(), ptr::null())
-        }?;
+        let request = FunctionRequest {
+            assembly_path: None,
+            type_name: type_name.as_ref(),
+            method_name,
+            unmanaged_callers_only: false,
+            delegate_type_name: None,
+        };
+        let function = unsafe { self.get_function_pointer_raw(&request, ptr::null()) }?;
         Ok(ManagedFunction(unsafe { FnPtr::from_ptr(function) }))
     }
 
@@ -290,16 +327,18 @@ impl DelegateLoader {
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
     pub fn get_function_with_unmanaged_callers_only<F: FnPtr + WithAbi<System>>(
         &self,
-        type_name: &PdCStr,
+        type_name: impl AsRef<PdCStr>,
         method_name: &PdCStr,
     ) -> Result<ManagedFunction<<F as WithAbi<System>>::F>, GetManagedFunctionError> {
-        let function = unsafe {
-            self.get_function_pointer_raw(
-                type_name.as_ptr(),
-                method_name.as_ptr(),
-                UNMANAGED_CALLERS_ONLY_METHOD,
-            )
-        }?;
+        let request = FunctionRequest {
+            assembly_path: None,
+            type_name: type_name.as_ref(),
+            method_name,
+            unmanaged_callers_only: true,
+            delegate_type_name: None,
+        };
+        let function =
+            unsafe { self.get_function_pointer_raw(&request, UNMANAGED_CALLERS_ONLY_METHOD) }?;
         Ok(ManagedFunction(unsafe {
             <<F as WithAbi<System>>::F>::from_ptr(function)
         }))
@@ -309,7 +348,10 @@ impl DelegateLoader {
 /// A struct for loading pointers to managed functions for a given [`HostfxrContext`] which automatically loads the
 /// assembly from the given path on the first access.
 ///
+/// Type names can be given as [`AssemblyQualifiedName`]s to validate them before they are passed to the runtime.
+///
 /// [`HostfxrContext`]: super::HostfxrContext
+/// [`AssemblyQualifiedName`]: crate::type_name::AssemblyQualifiedName
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
 #[derive(Clone)]
 pub struct AssemblyDelegateLoader {
@@ -343,9 +385,9 @@ impl AssemblyDelegateLoader {
     ///    Assembly qualified delegate type name for the method signature.
     pub fn get_function<F: FnPtr + WithAbi<System>>(
         &self,
-        type_name: &PdCStr,
+        type_name: impl AsRef<PdCStr>,
         method_name: &PdCStr,
-        delegate_type_name: &PdCStr,
+        delegate_type_name: impl AsRef<PdCStr>,
     ) -> Result<ManagedFunction<<F as WithAbi<System>>::F>, GetManagedFunctionError> {
         self.loader.load_assembly_and_get_function::<F>(
             self.assembly_path.as_ref(),
@@ -369,7 +411,7 @@ impl AssemblyDelegateLoader {
     ///    `public delegate int ComponentEntryPoint(IntPtr args, int sizeBytes);`
     pub fn get_function_with_default_signature(
         &self,
-        type_name: &PdCStr,
+        type_name: impl AsRef<PdCStr>,
         method_name: &PdCStr,
     ) -> Result<ManagedFunctionWithDefaultSignature, GetManagedFunctionError> {
         self.loader
@@ -398,7 +440,7 @@ impl AssemblyDelegateLoader {
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
     pub fn get_function_with_unmanaged_callers_only<F: FnPtr + WithAbi<System>>(
         &self,
-        type_name: &PdCStr,
+        type_name: impl AsRef<PdCStr>,
         method_name: &PdCStr,
     ) -> Result<ManagedFunction<<F as WithAbi<System>>::F>, GetManagedFunctionError> {
         self.loader
@@ -419,24 +461,48 @@ pub enum GetManagedFunctionError {
     Hosting(#[from] HostingError),
 
     /// A type with the specified name could not be found or loaded.
+    #[deprecated(
+        note = "Only returned by `GetManagedFunctionError::from_status_code`, `DelegateLoader` returns `GetManagedFunctionError::Resolution` instead"
+    )]
     #[error("Failed to load the type or method or it has an incompatible signature.")]
     TypeOrMethodNotFound,
 
     /// The specified assembly could not be found.
+    #[deprecated(
+        note = "Only returned by `GetManagedFunctionError::from_status_code`, `DelegateLoader` returns `GetManagedFunctionError::Resolution` instead"
+    )]
     #[error("The specified assembly could not be found.")]
     AssemblyNotFound,
 
     /// The target method is not annotated with [`UnmanagedCallersOnly`](https://docs.microsoft.com/en-us/dotnet/api/system.runtime.interopservices.unmanagedcallersonlyattribute).
+    #[deprecated(
+        note = "Only returned by `GetManagedFunctionError::from_status_code`, `DelegateLoader` returns `GetManagedFunctionError::Resolution` instead"
+    )]
     #[error("The target method is not annotated with UnmanagedCallersOnly.")]
     MethodNotUnmanagedCallersOnly,
 
-    /// Some other unknown error occured.
-    #[error("Unknown error code: {}", format!("{:#08X}", .0))]
-    Other(u32),
+    /// Some other error occured, e.g. an exception thrown by managed code.
+    #[deprecated(
+        note = "Only returned by `GetManagedFunctionError::from_status_code`, `DelegateLoader` returns `GetManagedFunctionError::Resolution` instead"
+    )]
+    #[error("Unknown error code: {0}")]
+    Other(HResult),
+
+    /// The function could not be resolved. Contains the names that were looked up and the reason determined by
+    /// inspecting the metadata of the involved assemblies.
+    #[error(transparent)]
+    Resolution(#[from] FunctionResolutionError),
 }
 
 impl GetManagedFunctionError {
     /// Converts the given staus code to a [`GetManagedFunctionError`].
+    ///
+    /// This only looks at the status code, which cannot tell some failures apart. The functions of
+    /// [`DelegateLoader`] return a [`GetManagedFunctionError::Resolution`] with a more precise reason instead.
+    #[deprecated(
+        note = "The status code cannot tell some failures apart, `DelegateLoader` returns `GetManagedFunctionError::Resolution` instead"
+    )]
+    #[allow(deprecated)]
     pub fn from_status_code(code: i32) -> Result<HostingSuccess, Self> {
         let code = code as u32;
         match HostingResult::known_from_status_code(code) {
@@ -444,29 +510,13 @@ impl GetManagedFunctionError {
             Ok(HostingResult(Err(code))) => return Err(GetManagedFunctionError::Hosting(code)),
             _ => {}
         }
-        match HResult::try_from(code) {
-            Ok(
-                HResult::COR_E_TYPELOAD | HResult::COR_E_MISSINGMETHOD | HResult::COR_E_ARGUMENT,
-            ) => return Err(Self::TypeOrMethodNotFound),
-            Ok(HResult::FILE_NOT_FOUND) => return Err(Self::AssemblyNotFound),
-            Ok(HResult::COR_E_INVALIDOPERATION) => return Err(Self::MethodNotUnmanagedCallersOnly),
-            _ => {}
+        match HResult(code) {
+            HResult::COR_E_TYPELOAD | HResult::COR_E_MISSINGMETHOD | HResult::COR_E_ARGUMENT => {
+                Err(Self::TypeOrMethodNotFound)
+            }
+            HResult::COR_E_FILENOTFOUND => Err(Self::AssemblyNotFound),
+            HResult::COR_E_INVALIDOPERATION => Err(Self::MethodNotUnmanagedCallersOnly),
+            code => Err(Self::Other(code)),
         }
-        Err(Self::Other(code))
     }
 }
-
-#[repr(u32)]
-#[non_exhaustive]
-#[derive(TryFromPrimitive, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[allow(non_camel_case_types)]
-#[rustfmt::skip]
-enum HResult {
-    E_POINTER = 0x8000_4003,                 // System.ArgumentNullException
-    COR_E_ARGUMENTOUTOFRANGE = 0x8013_1502,  // System.ArgumentOutOfRangeException (reserved was not 0)
-    COR_E_TYPELOAD = 0x8013_1522,            // invalid type
-    COR_E_MISSINGMETHOD = 0x8013_1513,       // invalid method
-    /*COR_E_*/FILE_NOT_FOUND = 0x8007_0002,  // assembly with specified name not found (from type name)
-    COR_E_ARGUMENT = 0x8007_0057,            // invalid method signature or method not found
-    COR_E_INVALIDOPERATION = 0x8013_1509,    // invalid assembly path or not unmanaged,
-}
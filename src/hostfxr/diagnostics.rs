@@ -0,0 +1,109 @@
+#[cfg(any(feature = "log", feature = "tracing"))]
+use crate::hostfxr::{GlobalErrorWriter, set_global_error_writer};
+#[cfg(any(feature = "log", feature = "tracing"))]
+use std::sync::Arc;
+
+/// Returns an error writer which forwards the messages reported by hostfxr to the [`log`] facade.
+///
+/// The messages are logged at the error level with the `netcorehost::hostfxr` target.
+///
+/// # Example
+/// ```no_run
+/// # use netcorehost::hostfxr::{log_error_writer, set_global_error_writer};
+/// set_global_error_writer(Some(log_error_writer()));
+/// ```
+#[cfg(feature = "log")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "log")))]
+#[must_use]
+pub fn log_error_writer() -> GlobalErrorWriter {
+    Arc::new(|message| {
+        log::error!(target: "netcorehost::hostfxr", "{}", message.to_string_lossy());
+    })
+}
+
+/// Installs [`log_error_writer`] as the process-wide error writer and returns the previous one.
+#[cfg(feature = "log")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "log")))]
+#[allow(clippy::must_use_candidate)]
+pub fn install_log_error_writer() -> Option<GlobalErrorWriter> {
+    set_global_error_writer(Some(log_error_writer()))
+}
+
+/// Returns an error writer which forwards the messages reported by hostfxr to [`tracing`] as events.
+///
+/// The events are emitted at the error level with the `netcorehost::hostfxr` target, so they are attached to the
+/// span of the failing hosting call.
+#[cfg(feature = "tracing")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "tracing")))]
+#[must_use]
+pub fn tracing_error_writer() -> GlobalErrorWriter {
+    Arc::new(|message| {
+        tracing::error!(target: "netcorehost::hostfxr", "{}", message.to_string_lossy());
+    })
+}
+
+/// Installs [`tracing_error_writer`] as the process-wide error writer and returns the previous one.
+#[cfg(feature = "tracing")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "tracing")))]
+#[allow(clippy::must_use_candidate)]
+pub fn install_tracing_error_writer() -> Option<GlobalErrorWriter> {
+    set_global_error_writer(Some(tracing_error_writer()))
+}
+
+/// Evaluates the given hosting call inside a `tracing` span and reports its status code through `log` and `tracing`
+/// once it returns.
+///
+/// The call has to evaluate to a [`RawStatus`]. The fields are only evaluated if one of the features is enabled.
+macro_rules! hosting_call {
+    ($operation:literal $(, $field:ident = $value:expr)* ; $call:expr) => {{
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::debug_span!(
+            target: "netcorehost::hostfxr",
+            $operation,
+            $($field = %$value,)*
+        )
+        .entered();
+
+        let result = $call;
+
+        #[cfg(any(feature = "log", feature = "tracing"))]
+        if let Some(status) = $crate::hostfxr::diagnostics::RawStatus::status(&result) {
+            #[cfg(feature = "tracing")]
+            if status < 0 {
+                ::tracing::warn!(target: "netcorehost::hostfxr", status = format_args!("{status:#010X}"));
+            } else {
+                ::tracing::debug!(target: "netcorehost::hostfxr", status = format_args!("{status:#010X}"));
+            }
+            #[cfg(feature = "log")]
+            ::log::log!(
+                target: "netcorehost::hostfxr",
+                if status < 0 { ::log::Level::Warn } else { ::log::Level::Debug },
+                concat!($operation, " returned {:#010X}" $(, ", ", stringify!($field), "={}")*),
+                status $(, $value)*
+            );
+        }
+
+        result
+    }};
+}
+pub(crate) use hosting_call;
+
+/// The result of a raw hosting call, which is either a status code or [`None`] if the export is missing.
+#[cfg(any(feature = "log", feature = "tracing"))]
+pub(crate) trait RawStatus {
+    fn status(&self) -> Option<i32>;
+}
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+impl RawStatus for i32 {
+    fn status(&self) -> Option<i32> {
+        Some(*self)
+    }
+}
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+impl RawStatus for Option<i32> {
+    fn status(&self) -> Option<i32> {
+        *self
+    }
+}
@@ -0,0 +1,279 @@
+use crate::{
+    error::{HostingCallError, HostingError},
+    hostfxr::{HostfxrLibrary, SharedHostfxrLibrary},
+    pdcstring::{PdCStr, PdChar},
+};
+use std::{
+    cell::{Cell, RefCell},
+    fmt, ptr,
+    sync::{Arc, PoisonError, RwLock, Weak},
+};
+
+/// A callback registered as the error writer of a single thread using [`Hostfxr::set_error_writer`].
+///
+/// [`Hostfxr::set_error_writer`]: crate::hostfxr::Hostfxr::set_error_writer
+pub(crate) type ErrorWriter = Box<dyn FnMut(&PdCStr)>;
+
+/// A callback registered as the process-wide error writer using [`set_global_error_writer`].
+pub type GlobalErrorWriter = Arc<dyn Fn(&PdCStr) + Send + Sync>;
+
+static GLOBAL_ERROR_WRITER: RwLock<Option<GlobalErrorWriter>> = RwLock::new(None);
+
+thread_local! {
+    static CURRENT_ERROR_WRITER: RefCell<Option<ErrorWriter>> = RefCell::new(None);
+    static REPORTED_ERRORS: Cell<usize> = const { Cell::new(0) };
+    static COLLECTORS: RefCell<Vec<(usize, Vec<String>)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_COLLECTOR_ID: Cell<usize> = const { Cell::new(0) };
+    // the libraries the trampoline has been registered with on this thread. the weak references keep the allocation
+    // alive, so a library loaded after one of them was dropped can never compare equal to it.
+    static REGISTERED_LIBRARIES: RefCell<Vec<Weak<HostfxrLibrary>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Sets the process-wide error writer and returns the previous one.
+///
+/// hostfxr invokes its error writer on the thread performing the failing hosting call. The global error writer
+/// receives the errors reported on all threads that do not have their own error writer registered using
+/// [`Hostfxr::set_error_writer`]. If there is neither a thread-local nor a global error writer, errors are logged
+/// with the `netcorehost::hostfxr` target if the `log` or `tracing` feature is enabled and dropped otherwise.
+///
+/// Prefer [`GlobalErrorWriterGuard`] if the writer should only be installed for a limited scope.
+///
+/// [`Hostfxr::set_error_writer`]: crate::hostfxr::Hostfxr::set_error_writer
+pub fn set_global_error_writer(writer: Option<GlobalErrorWriter>) -> Option<GlobalErrorWriter> {
+    let mut global_writer = GLOBAL_ERROR_WRITER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+    std::mem::replace(&mut *global_writer, writer)
+}
+
+/// Returns the current process-wide error writer, if there is one.
+#[must_use]
+pub fn global_error_writer() -> Option<GlobalErrorWriter> {
+    GLOBAL_ERROR_WRITER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// A guard that installs a process-wide error writer and restores the previous one when dropped.
+///
+/// Guards should be dropped in the reverse order of their creation, otherwise an outer guard restores a writer that
+/// was already replaced.
+#[must_use = "the error writer is uninstalled when the guard is dropped"]
+pub struct GlobalErrorWriterGuard {
+    previous: Option<GlobalErrorWriter>,
+}
+
+impl GlobalErrorWriterGuard {
+    /// Installs the given callback as the process-wide error writer, see [`set_global_error_writer`].
+    pub fn install(writer: impl Fn(&PdCStr) + Send + Sync + 'static) -> Self {
+        Self {
+            previous: set_global_error_writer(Some(Arc::new(writer))),
+        }
+    }
+}
+
+impl Drop for GlobalErrorWriterGuard {
+    fn drop(&mut self) {
+        set_global_error_writer(self.previous.take());
+    }
+}
+
+impl fmt::Debug for GlobalErrorWriterGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GlobalErrorWriterGuard")
+            .field("has_previous", &self.previous.is_some())
+            .finish()
+    }
+}
+
+/// Collects the messages reported through the error writer on the current thread while it is alive.
+///
+/// The messages are collected in addition to being passed on to the registered error writer. Collectors can be
+/// nested, in which case each of them receives all messages reported during its lifetime.
+#[derive(Debug)]
+#[must_use = "messages are only collected while the collector is alive"]
+pub struct ErrorCollector {
+    id: usize,
+    // collectors are bound to the thread they were created on.
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl ErrorCollector {
+    /// Starts collecting the messages reported on the current thread.
+    pub fn new() -> Self {
+        let id = NEXT_COLLECTOR_ID.get();
+        NEXT_COLLECTOR_ID.set(id + 1);
+        COLLECTORS.with_borrow_mut(|collectors| collectors.push((id, Vec::new())));
+        Self {
+            id,
+            _not_send: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns the messages collected so far.
+    #[must_use]
+    pub fn messages(&self) -> Vec<String> {
+        COLLECTORS.with_borrow(|collectors| {
+            collectors
+                .iter()
+                .find(|(id, _)| *id == self.id)
+                .map(|(_, messages)| messages.clone())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Stops collecting and returns the collected messages.
+    #[must_use]
+    pub fn finish(self) -> Vec<String> {
+        self.take_messages()
+    }
+
+    /// Stops collecting and attaches the collected messages to the given error, keeping the rest of its context.
+    ///
+    /// The messages already attached to the error are kept if nothing was collected.
+    #[must_use]
+    pub fn attach(self, error: HostingCallError) -> HostingCallError {
+        let messages = self.take_messages();
+        if messages.is_empty() {
+            error
+        } else {
+            error.with_messages(messages)
+        }
+    }
+
+    fn take_messages(&self) -> Vec<String> {
+        COLLECTORS.with_borrow_mut(|collectors| {
+            collectors
+                .iter()
+                .position(|(id, _)| *id == self.id)
+                .map(|index| collectors.remove(index).1)
+                .unwrap_or_default()
+        })
+    }
+}
+
+impl Default for ErrorCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ErrorCollector {
+    fn drop(&mut self) {
+        self.take_messages();
+    }
+}
+
+/// Runs the given function and attaches the messages reported through the error writer on the current thread to the
+/// error it returns.
+///
+/// The collected messages include those reported by all hosting calls made by the function, not only by the one that
+/// failed. The other context of the error (the operation, its arguments and the hostfxr version) is kept.
+///
+/// # Example
+/// ```no_run
+/// # use netcorehost::{hostfxr::collect_errors, nethost, pdcstr};
+/// let hostfxr = nethost::load_hostfxr().unwrap();
+/// let result = collect_errors(|| hostfxr.initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json")));
+/// if let Err(err) = result {
+///     eprintln!("{} failed with {}", err.operation(), err.code());
+///     if let Some(version) = err.hostfxr_version() {
+///         eprintln!("hostfxr version: {version}");
+///     }
+///     for (name, value) in err.args() {
+///         eprintln!("{name}: {}", value.to_string_lossy());
+///     }
+///     eprintln!("{}", err.messages().join("\n"));
+/// }
+/// ```
+pub fn collect_errors<T>(
+    f: impl FnOnce() -> Result<T, HostingCallError>,
+) -> Result<T, HostingCallError> {
+    let collector = ErrorCollector::new();
+    f().map_err(|error| collector.attach(error))
+}
+
+/// Registers the error writer trampoline with the given library on the current thread, if this did not happen yet.
+///
+/// hostfxr keeps its error writer per thread, so this has to happen on every thread performing hosting calls.
+pub(crate) fn register_error_writer(lib: &SharedHostfxrLibrary) -> Result<(), HostingError> {
+    let registered = REGISTERED_LIBRARIES.with_borrow_mut(|libraries| {
+        libraries.retain(|library| library.strong_count() > 0);
+        libraries
+            .iter()
+            .any(|library| ptr::eq(library.as_ptr(), Arc::as_ptr(lib)))
+    });
+    if registered {
+        return Ok(());
+    }
+    unsafe { lib.hostfxr_set_error_writer(Some(error_writer_trampoline)) }.ok_or(
+        HostingError::MissingExport {
+            name: "hostfxr_set_error_writer",
+        },
+    )?;
+    REGISTERED_LIBRARIES.with_borrow_mut(|libraries| libraries.push(Arc::downgrade(lib)));
+    Ok(())
+}
+
+/// Sets the error writer of the current thread, see [`Hostfxr::set_error_writer`].
+///
+/// [`Hostfxr::set_error_writer`]: crate::hostfxr::Hostfxr::set_error_writer
+pub(crate) fn set_thread_error_writer(
+    lib: &SharedHostfxrLibrary,
+    error_writer: Option<ErrorWriter>,
+) -> Result<(), HostingError> {
+    register_error_writer(lib)?;
+    CURRENT_ERROR_WRITER.with(|current_writer| {
+        *current_writer.borrow_mut() = error_writer;
+    });
+    Ok(())
+}
+
+/// Runs the given function and returns the number of errors reported through the error writer of the current
+/// thread while it ran.
+///
+/// The error writer has to be registered on the current thread, see [`register_error_writer`].
+pub(crate) fn count_reported_errors<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let reported_before = REPORTED_ERRORS.get();
+    let result = f();
+    (result, REPORTED_ERRORS.get() - reported_before)
+}
+
+extern "C" fn error_writer_trampoline(raw_error: *const PdChar) {
+    REPORTED_ERRORS.set(REPORTED_ERRORS.get() + 1);
+    let error_message = unsafe { PdCStr::from_str_ptr(raw_error) };
+
+    COLLECTORS.with_borrow_mut(|collectors| {
+        if !collectors.is_empty() {
+            let message = error_message.to_string_lossy();
+            for (_, messages) in collectors.iter_mut() {
+                messages.push(message.clone());
+            }
+        }
+    });
+
+    let handled = CURRENT_ERROR_WRITER.with(|writer_holder| {
+        if let Some(writer) = writer_holder.borrow_mut().as_mut() {
+            writer(error_message);
+            true
+        } else {
+            false
+        }
+    });
+    if handled {
+        return;
+    }
+
+    if let Some(writer) = global_error_writer() {
+        writer(error_message);
+    } else {
+        // forward the message to the enabled logging facades, it is dropped if there are none.
+        #[cfg(feature = "tracing")]
+        tracing::error!(target: "netcorehost::hostfxr", "{}", error_message.to_string_lossy());
+        #[cfg(feature = "log")]
+        log::error!(target: "netcorehost::hostfxr", "{}", error_message.to_string_lossy());
+        #[cfg(not(any(feature = "log", feature = "tracing")))]
+        let _ = error_message;
+    }
+}
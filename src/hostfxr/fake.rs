@@ -0,0 +1,404 @@
+use crate::{
+    bindings::hostfxr::hostfxr_delegate_type,
+    error::{HostingError, HostingSuccess},
+    hostfxr::{
+        AppExit, HostfxrApi, HostfxrCommandLineContextApi, HostfxrContextApi,
+        InitializedForCommandLine, InitializedForRuntimeConfig, RawFnPtr,
+    },
+    pdcstring::{PdCStr, PdCString},
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{self, Debug},
+    marker::PhantomData,
+    rc::Rc,
+};
+
+use enum_map::EnumMap;
+
+/// An in-memory implementation of [`HostfxrApi`] which records all calls made to it.
+///
+/// Its behaviour can be scripted in a similar way to a real hostfxr:
+/// - Initializing a host context succeeds unless a failure was configured using [`FakeHostfxr::fail`].
+///   The first active context is the primary one.
+/// - New contexts start with the properties configured using [`FakeHostfxr::set_runtime_property`].
+/// - Runtime delegates configured using [`FakeHostfxr::set_runtime_delegate`] are returned as is, all other delegate
+///   types fail with [`HostingError::HostApiUnsupportedScenario`].
+/// - [`run_app`](HostfxrCommandLineContextApi::run_app) returns [`AppExit::AppExited`] with the exit code
+///   configured using [`FakeHostfxr::set_exit_code`], or [`AppExit::HostingFailed`] if a failure was configured.
+///
+/// All clones of a [`FakeHostfxr`] and all contexts created from it share the same state.
+#[derive(Clone, Default)]
+pub struct FakeHostfxr {
+    state: Rc<RefCell<FakeState>>,
+}
+
+#[derive(Default)]
+struct FakeState {
+    properties: HashMap<PdCString, PdCString>,
+    delegates: EnumMap<hostfxr_delegate_type, Option<RawFnPtr>>,
+    exit_code: i32,
+    failures: HashMap<FakeOperation, HostingError>,
+    calls: Vec<FakeCall>,
+    next_context_id: usize,
+    active_contexts: usize,
+}
+
+impl FakeState {
+    fn record(&mut self, call: FakeCall) -> Result<(), HostingError> {
+        let operation = call.operation();
+        self.calls.push(call);
+        match self.failures.get(&operation) {
+            Some(error) => Err(*error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Debug for FakeHostfxr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.borrow();
+        f.debug_struct("FakeHostfxr")
+            .field("calls", &state.calls)
+            .field("active_contexts", &state.active_contexts)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FakeHostfxr {
+    /// Creates a new fake without any properties, delegates or configured failures.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a runtime property that host contexts initialized afterwards start with.
+    pub fn set_runtime_property(&self, name: impl Into<PdCString>, value: impl Into<PdCString>) {
+        self.state
+            .borrow_mut()
+            .properties
+            .insert(name.into(), value.into());
+    }
+
+    /// Sets the function pointer returned for the given runtime delegate type.
+    pub fn set_runtime_delegate(&self, r#type: hostfxr_delegate_type, delegate: RawFnPtr) {
+        self.state.borrow_mut().delegates[r#type] = Some(delegate);
+    }
+
+    /// Sets the exit code returned by [`run_app`](HostfxrCommandLineContextApi::run_app).
+    pub fn set_exit_code(&self, exit_code: i32) {
+        self.state.borrow_mut().exit_code = exit_code;
+    }
+
+    /// Makes all following calls of the given operation fail with the given error.
+    /// The calls are still recorded.
+    pub fn fail(&self, operation: FakeOperation, error: HostingError) {
+        self.state.borrow_mut().failures.insert(operation, error);
+    }
+
+    /// Removes a failure configured using [`FakeHostfxr::fail`].
+    pub fn clear_failure(&self, operation: FakeOperation) {
+        self.state.borrow_mut().failures.remove(&operation);
+    }
+
+    /// Returns the number of host contexts which are currently open.
+    #[must_use]
+    pub fn active_contexts(&self) -> usize {
+        self.state.borrow().active_contexts
+    }
+
+    /// Returns all calls made so far in the order they were made.
+    #[must_use]
+    pub fn calls(&self) -> Vec<FakeCall> {
+        self.state.borrow().calls.clone()
+    }
+
+    /// Returns the operations of all calls made so far in the order they were made.
+    #[must_use]
+    pub fn operations(&self) -> Vec<FakeOperation> {
+        self.state
+            .borrow()
+            .calls
+            .iter()
+            .map(FakeCall::operation)
+            .collect()
+    }
+
+    /// Forgets all recorded calls.
+    pub fn clear_calls(&self) {
+        self.state.borrow_mut().calls.clear();
+    }
+
+    fn initialize<I>(&self, call: FakeCall) -> Result<FakeHostfxrContext<I>, HostingError> {
+        let mut state = self.state.borrow_mut();
+        state.record(call)?;
+
+        let id = state.next_context_id;
+        state.next_context_id += 1;
+        let is_primary = state.active_contexts == 0;
+        state.active_contexts += 1;
+
+        Ok(FakeHostfxrContext {
+            id,
+            is_primary,
+            properties: state.properties.clone(),
+            closed: false,
+            state: Rc::clone(&self.state),
+            context_type: PhantomData,
+        })
+    }
+}
+
+impl HostfxrApi for FakeHostfxr {
+    type RuntimeConfigContext = FakeHostfxrContext<InitializedForRuntimeConfig>;
+    type CommandLineContext = FakeHostfxrContext<InitializedForCommandLine>;
+
+    fn initialize_for_runtime_config(
+        &self,
+        runtime_config_path: &PdCStr,
+    ) -> Result<Self::RuntimeConfigContext, HostingError> {
+        self.initialize(FakeCall::InitializeForRuntimeConfig {
+            runtime_config_path: runtime_config_path.to_owned(),
+        })
+    }
+
+    fn initialize_for_dotnet_command_line_with_args(
+        &self,
+        app_path: &PdCStr,
+        args: &[&PdCStr],
+    ) -> Result<Self::CommandLineContext, HostingError> {
+        self.initialize(FakeCall::InitializeForDotnetCommandLine {
+            app_path: app_path.to_owned(),
+            args: args.iter().map(|&arg| arg.to_owned()).collect(),
+        })
+    }
+}
+
+/// A host context created by a [`FakeHostfxr`].
+///
+/// Like a [`HostfxrContext`](crate::hostfxr::HostfxrContext) the context is closed on drop.
+pub struct FakeHostfxrContext<I> {
+    id: usize,
+    is_primary: bool,
+    properties: HashMap<PdCString, PdCString>,
+    closed: bool,
+    state: Rc<RefCell<FakeState>>,
+    context_type: PhantomData<I>,
+}
+
+impl<I> Debug for FakeHostfxrContext<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FakeHostfxrContext")
+            .field("id", &self.id)
+            .field("is_primary", &self.is_primary)
+            .field("properties", &self.properties)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I> FakeHostfxrContext<I> {
+    /// Gets the id identifying this context in the recorded [`FakeCall`]s.
+    /// Ids are assigned in the order the contexts are initialized, starting at 0.
+    #[must_use]
+    pub const fn id(&self) -> usize {
+        self.id
+    }
+
+    fn record(&self, call: FakeCall) -> Result<(), HostingError> {
+        self.state.borrow_mut().record(call)
+    }
+
+    fn close_raw(&self) -> Result<HostingSuccess, HostingError> {
+        let mut state = self.state.borrow_mut();
+        state.active_contexts -= 1;
+        state.record(FakeCall::Close { context: self.id })?;
+        Ok(HostingSuccess::Success)
+    }
+}
+
+impl<I> HostfxrContextApi for FakeHostfxrContext<I> {
+    fn is_primary(&self) -> bool {
+        self.is_primary
+    }
+
+    fn get_runtime_property_value(&self, name: &PdCStr) -> Result<PdCString, HostingError> {
+        self.record(FakeCall::GetRuntimePropertyValue {
+            context: self.id,
+            name: name.to_owned(),
+        })?;
+        self.properties
+            .get(name)
+            .cloned()
+            .ok_or(HostingError::HostPropertyNotFound)
+    }
+
+    fn set_runtime_property_value(
+        &mut self,
+        name: &PdCStr,
+        value: &PdCStr,
+    ) -> Result<(), HostingError> {
+        self.record(FakeCall::SetRuntimePropertyValue {
+            context: self.id,
+            name: name.to_owned(),
+            value: value.to_owned(),
+        })?;
+        self.properties.insert(name.to_owned(), value.to_owned());
+        Ok(())
+    }
+
+    fn remove_runtime_property_value(&mut self, name: &PdCStr) -> Result<(), HostingError> {
+        self.record(FakeCall::RemoveRuntimePropertyValue {
+            context: self.id,
+            name: name.to_owned(),
+        })?;
+        self.properties.remove(name);
+        Ok(())
+    }
+
+    fn runtime_properties(&self) -> Result<HashMap<PdCString, PdCString>, HostingError> {
+        self.record(FakeCall::GetRuntimeProperties { context: self.id })?;
+        Ok(self.properties.clone())
+    }
+
+    fn get_runtime_delegate(
+        &self,
+        r#type: hostfxr_delegate_type,
+    ) -> Result<RawFnPtr, HostingError> {
+        self.record(FakeCall::GetRuntimeDelegate {
+            context: self.id,
+            r#type,
+        })?;
+        self.state.borrow().delegates[r#type].ok_or(HostingError::HostApiUnsupportedScenario)
+    }
+
+    fn close(mut self) -> Result<HostingSuccess, HostingError> {
+        self.closed = true;
+        self.close_raw()
+    }
+}
+
+impl HostfxrCommandLineContextApi for FakeHostfxrContext<InitializedForCommandLine> {
+    fn run_app(self) -> AppExit {
+        match self.record(FakeCall::RunApp { context: self.id }) {
+            Ok(()) => AppExit::AppExited(self.state.borrow().exit_code),
+            Err(error) => AppExit::HostingFailed(error),
+        }
+    }
+}
+
+impl<I> Drop for FakeHostfxrContext<I> {
+    fn drop(&mut self) {
+        if !self.closed {
+            let _ = self.close_raw();
+        }
+    }
+}
+
+/// A call recorded by a [`FakeHostfxr`].
+///
+/// Calls on a context carry the [`id`](FakeHostfxrContext::id) of that context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FakeCall {
+    /// A call to [`HostfxrApi::initialize_for_runtime_config`].
+    InitializeForRuntimeConfig {
+        /// The path to the `.runtimeconfig.json` file.
+        runtime_config_path: PdCString,
+    },
+    /// A call to [`HostfxrApi::initialize_for_dotnet_command_line`] or
+    /// [`HostfxrApi::initialize_for_dotnet_command_line_with_args`].
+    InitializeForDotnetCommandLine {
+        /// The path to the application.
+        app_path: PdCString,
+        /// The command line arguments for the application.
+        args: Vec<PdCString>,
+    },
+    /// A call to [`HostfxrContextApi::get_runtime_property_value`].
+    GetRuntimePropertyValue {
+        /// The id of the context.
+        context: usize,
+        /// The name of the property.
+        name: PdCString,
+    },
+    /// A call to [`HostfxrContextApi::set_runtime_property_value`].
+    SetRuntimePropertyValue {
+        /// The id of the context.
+        context: usize,
+        /// The name of the property.
+        name: PdCString,
+        /// The new value of the property.
+        value: PdCString,
+    },
+    /// A call to [`HostfxrContextApi::remove_runtime_property_value`].
+    RemoveRuntimePropertyValue {
+        /// The id of the context.
+        context: usize,
+        /// The name of the property.
+        name: PdCString,
+    },
+    /// A call to [`HostfxrContextApi::runtime_properties`].
+    GetRuntimeProperties {
+        /// The id of the context.
+        context: usize,
+    },
+    /// A call to [`HostfxrContextApi::get_runtime_delegate`].
+    GetRuntimeDelegate {
+        /// The id of the context.
+        context: usize,
+        /// The requested delegate type.
+        r#type: hostfxr_delegate_type,
+    },
+    /// A call to [`HostfxrCommandLineContextApi::run_app`].
+    RunApp {
+        /// The id of the context.
+        context: usize,
+    },
+    /// A context was closed, either explicitly or by dropping it.
+    Close {
+        /// The id of the context.
+        context: usize,
+    },
+}
+
+impl FakeCall {
+    /// Returns the operation of this call.
+    #[must_use]
+    pub const fn operation(&self) -> FakeOperation {
+        match self {
+            Self::InitializeForRuntimeConfig { .. } => FakeOperation::InitializeForRuntimeConfig,
+            Self::InitializeForDotnetCommandLine { .. } => {
+                FakeOperation::InitializeForDotnetCommandLine
+            }
+            Self::GetRuntimePropertyValue { .. } => FakeOperation::GetRuntimePropertyValue,
+            Self::SetRuntimePropertyValue { .. } => FakeOperation::SetRuntimePropertyValue,
+            Self::RemoveRuntimePropertyValue { .. } => FakeOperation::RemoveRuntimePropertyValue,
+            Self::GetRuntimeProperties { .. } => FakeOperation::GetRuntimeProperties,
+            Self::GetRuntimeDelegate { .. } => FakeOperation::GetRuntimeDelegate,
+            Self::RunApp { .. } => FakeOperation::RunApp,
+            Self::Close { .. } => FakeOperation::Close,
+        }
+    }
+}
+
+/// The kind of a [`FakeCall`], used to configure failures of a [`FakeHostfxr`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FakeOperation {
+    /// See [`FakeCall::InitializeForRuntimeConfig`].
+    InitializeForRuntimeConfig,
+    /// See [`FakeCall::InitializeForDotnetCommandLine`].
+    InitializeForDotnetCommandLine,
+    /// See [`FakeCall::GetRuntimePropertyValue`].
+    GetRuntimePropertyValue,
+    /// See [`FakeCall::SetRuntimePropertyValue`].
+    SetRuntimePropertyValue,
+    /// See [`FakeCall::RemoveRuntimePropertyValue`].
+    RemoveRuntimePropertyValue,
+    /// See [`FakeCall::GetRuntimeProperties`].
+    GetRuntimeProperties,
+    /// See [`FakeCall::GetRuntimeDelegate`].
+    GetRuntimeDelegate,
+    /// See [`FakeCall::RunApp`].
+    RunApp,
+    /// See [`FakeCall::Close`].
+    Close,
+}
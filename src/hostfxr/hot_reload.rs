@@ -0,0 +1,573 @@
+use crate::{
+    bindings::hostfxr::component_entry_point_fn,
+    hostfxr::{
+        AssemblyBytes, AssemblyLoadContextError, AssemblyLoadContextHandle,
+        AssemblyLoadContextHelper, FnPtr, GetManagedFunctionError, ManagedFnPtr, ManagedFunction,
+        PluginFunction,
+    },
+    pdcstring::{PdCStr, PdCString},
+};
+use fn_ptr::{WithAbi, abi::System};
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    fmt, fs, io, mem,
+    path::PathBuf,
+    sync::{Arc, Condvar, Mutex, PoisonError, RwLock},
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
+};
+use thiserror::Error;
+
+const WATCHER_THREAD_NAME: &str = "netcorehost-hot-reload";
+
+type ReloadFailureCallback = Box<dyn Fn(&ReloadError) + Send + Sync>;
+
+enum Signature {
+    Default,
+    UnmanagedCallersOnly,
+    Delegate(PdCString),
+}
+
+struct Registration {
+    key: String,
+    type_name: PdCString,
+    method_name: PdCString,
+    signature: Signature,
+    /// The type of the function pointer the function was registered as.
+    function_type: TypeId,
+}
+
+/// Returns the type a function registered as `F` is stored as.
+fn function_type<F>() -> TypeId
+where
+    F: FnPtr + WithAbi<System>,
+    <F as WithAbi<System>>::F: 'static,
+{
+    TypeId::of::<<F as WithAbi<System>>::F>()
+}
+
+/// Builder for a [`HotReloadPlugin`], created using [`HotReloadPlugin::builder`].
+#[must_use]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+pub struct HotReloadBuilder {
+    helper: AssemblyLoadContextHelper,
+    assembly_path: PdCString,
+    functions: Vec<Registration>,
+    poll_interval: Duration,
+    on_reload_failure: Option<ReloadFailureCallback>,
+}
+
+impl HotReloadBuilder {
+    /// The default interval in which the plugin assembly is checked for changes.
+    pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    fn register(
+        mut self,
+        key: impl Into<String>,
+        type_name: impl AsRef<PdCStr>,
+        method_name: impl AsRef<PdCStr>,
+        signature: Signature,
+        function_type: TypeId,
+    ) -> Self {
+        self.functions.push(Registration {
+            key: key.into(),
+            type_name: type_name.as_ref().to_owned(),
+            method_name: method_name.as_ref().to_owned(),
+            signature,
+            function_type,
+        });
+        self
+    }
+
+    /// Registers a function of type `F` under the given key, which is resolved like
+    /// [`AssemblyLoadContextHandle::get_function`] every time the plugin is loaded.
+    /// It can only be taken from a [`PluginFunctionTable`] as the same type.
+    pub fn function<F>(
+        self,
+        key: impl Into<String>,
+        type_name: impl AsRef<PdCStr>,
+        method_name: impl AsRef<PdCStr>,
+        delegate_type_name: impl AsRef<PdCStr>,
+    ) -> Self
+    where
+        F: FnPtr + WithAbi<System>,
+        <F as WithAbi<System>>::F: 'static,
+    {
+        let signature = Signature::Delegate(delegate_type_name.as_ref().to_owned());
+        self.register(key, type_name, method_name, signature, function_type::<F>())
+    }
+
+    /// Registers a function under the given key, which is resolved like
+    /// [`AssemblyLoadContextHandle::get_function_with_default_signature`] every time the plugin is loaded.
+    pub fn function_with_default_signature(
+        self,
+        key: impl Into<String>,
+        type_name: impl AsRef<PdCStr>,
+        method_name: impl AsRef<PdCStr>,
+    ) -> Self {
+        self.register(
+            key,
+            type_name,
+            method_name,
+            Signature::Default,
+            TypeId::of::<component_entry_point_fn>(),
+        )
+    }
+
+    /// Registers a function of type `F` under the given key, which is resolved like
+    /// [`AssemblyLoadContextHandle::get_function_with_unmanaged_callers_only`] every time the plugin is loaded.
+    /// It can only be taken from a [`PluginFunctionTable`] as the same type.
+    pub fn function_with_unmanaged_callers_only<F>(
+        self,
+        key: impl Into<String>,
+        type_name: impl AsRef<PdCStr>,
+        method_name: impl AsRef<PdCStr>,
+    ) -> Self
+    where
+        F: FnPtr + WithAbi<System>,
+        <F as WithAbi<System>>::F: 'static,
+    {
+        self.register(
+            key,
+            type_name,
+            method_name,
+            Signature::UnmanagedCallersOnly,
+            function_type::<F>(),
+        )
+    }
+
+    /// Sets the interval in which the plugin assembly is checked for changes.
+    /// Defaults to [`DEFAULT_POLL_INTERVAL`](HotReloadBuilder::DEFAULT_POLL_INTERVAL).
+    pub const fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Sets a callback which is invoked on the watcher thread if reloading the plugin after a change failed.
+    ///
+    /// The previous version of the plugin stays active in this case. If no callback is set, failures are reported
+    /// through `log` and `tracing`.
+    pub fn on_reload_failure(
+        mut self,
+        callback: impl Fn(&ReloadError) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_reload_failure = Some(Box::new(callback));
+        self
+    }
+
+    /// Loads the plugin, resolves all registered functions and starts watching the plugin assembly for changes.
+    pub fn build(self) -> Result<HotReloadPlugin, ReloadError> {
+        let path = PathBuf::from(self.assembly_path.to_os_string());
+        let context_name = path
+            .file_stem()
+            .and_then(|name| PdCString::from_os_str(name).ok())
+            .unwrap_or_else(|| self.assembly_path.clone());
+        let mut shared = Shared {
+            helper: self.helper,
+            assembly_path: self.assembly_path,
+            path,
+            context_name,
+            functions: self.functions,
+            on_reload_failure: self.on_reload_failure,
+            table: RwLock::new(Arc::new(PluginFunctionTable::empty())),
+            loaded: Mutex::new(None),
+            stopped: Mutex::new(false),
+            stop_signal: Condvar::new(),
+            poll_interval: self.poll_interval,
+        };
+        let stamp = FileStamp::read(&shared.path).ok();
+        let table = shared.load(1)?;
+        shared.table = RwLock::new(Arc::new(table));
+        shared.loaded = Mutex::new(stamp);
+
+        let shared = Arc::new(shared);
+        let watcher = thread::Builder::new()
+            .name(WATCHER_THREAD_NAME.to_owned())
+            .spawn({
+                let shared = Arc::clone(&shared);
+                move || shared.watch()
+            })?;
+        Ok(HotReloadPlugin {
+            shared,
+            watcher: Some(watcher),
+        })
+    }
+}
+
+impl fmt::Debug for HotReloadBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HotReloadBuilder")
+            .field("assembly_path", &self.assembly_path)
+            .field("poll_interval", &self.poll_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A managed plugin which is reloaded into a fresh collectible `AssemblyLoadContext` whenever its assembly changes
+/// on disk.
+///
+/// The functions registered with the [`HotReloadBuilder`] are resolved again after every reload and published as a
+/// new [`PluginFunctionTable`], which replaces the previous one atomically. Callers keep using the table they
+/// obtained from [`functions`](HotReloadPlugin::functions) until they ask for it again, and the context of a
+/// replaced table is unloaded once the last reference to it is dropped.
+///
+/// The assembly is loaded from memory, so the file is not locked and can be overwritten while the plugin is loaded.
+/// Symbols are loaded from a `.pdb` file next to the assembly if it exists.
+///
+/// # Example
+/// ```no_run
+/// # use netcorehost::{hostfxr::{AssemblyLoadContextHelper, HotReloadPlugin}, nethost, pdcstr};
+/// # let hostfxr = nethost::load_hostfxr()?;
+/// # let context = hostfxr.initialize_for_runtime_config(pdcstr!("Test.runtimeconfig.json"))?;
+/// # let helper = AssemblyLoadContextHelper::load(&context.get_delegate_loader()?, pdcstr!("NetCoreHost.Helper.dll"))?;
+/// let plugin = HotReloadPlugin::builder(&helper, pdcstr!("Plugin.dll"))
+///     .function_with_unmanaged_callers_only::<fn() -> i32>("run", pdcstr!("Plugin.Entry, Plugin"), pdcstr!("Run"))
+///     .on_reload_failure(|err| eprintln!("failed to reload plugin: {err}"))
+///     .build()?;
+///
+/// loop {
+///     let functions = plugin.functions();
+///     let run = functions.get::<fn() -> i32>("run").unwrap();
+///     run.call(());
+/// #   break;
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+pub struct HotReloadPlugin {
+    shared: Arc<Shared>,
+    watcher: Option<JoinHandle<()>>,
+}
+
+impl HotReloadPlugin {
+    /// Creates a builder for a plugin loaded from the assembly at the given path.
+    pub fn builder(
+        helper: &AssemblyLoadContextHelper,
+        assembly_path: impl AsRef<PdCStr>,
+    ) -> HotReloadBuilder {
+        HotReloadBuilder {
+            helper: helper.clone(),
+            assembly_path: assembly_path.as_ref().to_owned(),
+            functions: Vec::new(),
+            poll_interval: HotReloadBuilder::DEFAULT_POLL_INTERVAL,
+            on_reload_failure: None,
+        }
+    }
+
+    /// Returns the path of the plugin assembly.
+    #[must_use]
+    pub fn assembly_path(&self) -> &PdCStr {
+        &self.shared.assembly_path
+    }
+
+    /// Returns the functions of the currently loaded version of the plugin.
+    #[must_use]
+    pub fn functions(&self) -> Arc<PluginFunctionTable> {
+        self.shared.current()
+    }
+
+    /// Returns the generation of the currently loaded version of the plugin, which starts at 1 and is incremented
+    /// with every successful reload.
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        self.shared.current().generation()
+    }
+
+    /// Reloads the plugin immediately, regardless of whether its assembly changed.
+    ///
+    /// Unlike reloads triggered by the watcher, failures are returned instead of being passed to the
+    /// [`on_reload_failure`](HotReloadBuilder::on_reload_failure) callback.
+    pub fn reload(&self) -> Result<Arc<PluginFunctionTable>, ReloadError> {
+        self.shared.reload()
+    }
+}
+
+impl fmt::Debug for HotReloadPlugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HotReloadPlugin")
+            .field("assembly_path", &self.shared.assembly_path)
+            .field("generation", &self.generation())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for HotReloadPlugin {
+    fn drop(&mut self) {
+        *self
+            .shared
+            .stopped
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = true;
+        self.shared.stop_signal.notify_all();
+        if let Some(watcher) = self.watcher.take() {
+            let _ = watcher.join();
+        }
+    }
+}
+
+struct Shared {
+    helper: AssemblyLoadContextHelper,
+    assembly_path: PdCString,
+    path: PathBuf,
+    context_name: PdCString,
+    functions: Vec<Registration>,
+    on_reload_failure: Option<ReloadFailureCallback>,
+    table: RwLock<Arc<PluginFunctionTable>>,
+    /// The state of the assembly file when it was last loaded, also serializes reloads.
+    loaded: Mutex<Option<FileStamp>>,
+    stopped: Mutex<bool>,
+    stop_signal: Condvar,
+    poll_interval: Duration,
+}
+
+impl Shared {
+    fn current(&self) -> Arc<PluginFunctionTable> {
+        Arc::clone(&self.table.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn load(&self, generation: u64) -> Result<PluginFunctionTable, ReloadError> {
+        let assembly = fs::read(&self.path)?;
+        let symbols = match fs::read(self.path.with_extension("pdb")) {
+            Ok(symbols) => Some(symbols),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err.into()),
+        };
+
+        let context = self.helper.create_context(&self.context_name, true)?;
+        // the table unloads the context if anything below fails.
+        let mut table = PluginFunctionTable {
+            generation,
+            context: Some(context),
+            functions: HashMap::with_capacity(self.functions.len()),
+        };
+        let context = table.context.as_ref().unwrap();
+        context.load_assembly_from_memory(AssemblyBytes::with_optional_symbols(
+            &assembly,
+            symbols.as_deref(),
+        ))?;
+
+        for registration in &self.functions {
+            let type_name = &registration.type_name;
+            let method_name = &registration.method_name;
+            let function = match &registration.signature {
+                Signature::Default => context
+                    .get_function_with_default_signature(type_name, method_name)
+                    .map(|f| f.as_ptr()),
+                Signature::UnmanagedCallersOnly => context
+                    .get_function_with_unmanaged_callers_only::<fn()>(type_name, method_name)
+                    .map(|f| f.as_ptr()),
+                Signature::Delegate(delegate_type_name) => context
+                    .get_function::<fn()>(type_name, method_name, delegate_type_name)
+                    .map(|f| f.as_ptr()),
+            }
+            .map_err(|source| ReloadError::Function {
+                key: registration.key.clone(),
+                source,
+            })?;
+            table.functions.insert(
+                registration.key.clone(),
+                TableEntry {
+                    // SAFETY: the pointer is only stored as an address and converted back before it is called.
+                    function: unsafe { FnPtr::from_ptr(function) },
+                    function_type: registration.function_type,
+                },
+            );
+        }
+        Ok(table)
+    }
+
+    fn reload(&self) -> Result<Arc<PluginFunctionTable>, ReloadError> {
+        let mut loaded = self.loaded.lock().unwrap_or_else(PoisonError::into_inner);
+        // a failed reload is only retried once the file changes again.
+        *loaded = FileStamp::read(&self.path).ok();
+
+        let table = Arc::new(self.load(self.current().generation() + 1)?);
+        let previous = mem::replace(
+            &mut *self.table.write().unwrap_or_else(PoisonError::into_inner),
+            Arc::clone(&table),
+        );
+        drop(loaded);
+        // unloads the previous context unless it is still in use.
+        drop(previous);
+        Ok(table)
+    }
+
+    fn watch(&self) {
+        // the state of a changed file is only loaded once it stayed the same for a whole poll interval.
+        let mut pending = None;
+        loop {
+            let stopped = self.stopped.lock().unwrap_or_else(PoisonError::into_inner);
+            let (stopped, _) = self
+                .stop_signal
+                .wait_timeout_while(stopped, self.poll_interval, |stopped| !*stopped)
+                .unwrap_or_else(PoisonError::into_inner);
+            if *stopped {
+                return;
+            }
+            drop(stopped);
+
+            let stamp = FileStamp::read(&self.path).ok();
+            if stamp == *self.loaded.lock().unwrap_or_else(PoisonError::into_inner) {
+                pending = None;
+            } else if pending != Some(stamp) {
+                pending = Some(stamp);
+            } else if let Err(err) = self.reload() {
+                pending = None;
+                self.report_failure(&err);
+            }
+        }
+    }
+
+    fn report_failure(&self, err: &ReloadError) {
+        if let Some(on_reload_failure) = &self.on_reload_failure {
+            on_reload_failure(err);
+            return;
+        }
+
+        #[cfg(any(feature = "log", feature = "tracing"))]
+        {
+            let assembly_path = self.assembly_path.to_string_lossy();
+            #[cfg(feature = "tracing")]
+            tracing::warn!(target: "netcorehost::hostfxr", %assembly_path, %err, "failed to reload plugin");
+            #[cfg(feature = "log")]
+            log::warn!(target: "netcorehost::hostfxr", "failed to reload plugin {assembly_path}: {err}");
+        }
+        #[cfg(not(any(feature = "log", feature = "tracing")))]
+        let _ = err;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileStamp {
+    fn read(path: &std::path::Path) -> io::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        Ok(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+}
+
+/// The functions resolved from one version of a [`HotReloadPlugin`].
+///
+/// The context of the version is unloaded once the table is dropped, so the functions borrow the table and are only
+/// handed out as [`PluginFunction`]s, which cannot be copied out of that borrow.
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+pub struct PluginFunctionTable {
+    generation: u64,
+    context: Option<AssemblyLoadContextHandle>,
+    functions: HashMap<String, TableEntry>,
+}
+
+#[derive(Clone, Copy)]
+struct TableEntry {
+    /// The resolved function, stored with a placeholder signature until it is taken as `function_type`.
+    function: unsafe extern "system" fn(),
+    function_type: TypeId,
+}
+
+impl PluginFunctionTable {
+    fn empty() -> Self {
+        Self {
+            generation: 0,
+            context: None,
+            functions: HashMap::new(),
+        }
+    }
+
+    /// Returns the generation of the plugin version these functions were resolved from.
+    #[must_use]
+    pub const fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the keys of all functions in this table.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.functions.keys().map(String::as_str)
+    }
+
+    /// Returns the function registered under the given key as a function of type `F`.
+    /// The function can only be called while the table is borrowed.
+    ///
+    /// Returns [`None`] if there is no function registered under the key or if it was registered as a different type.
+    #[must_use]
+    pub fn get<F>(&self, key: &str) -> Option<PluginFunction<'_, <F as WithAbi<System>>::F>>
+    where
+        F: FnPtr + WithAbi<System>,
+        <F as WithAbi<System>>::F: 'static,
+    {
+        self.get_as(key, function_type::<F>())
+    }
+
+    /// Returns the function registered under the given key with the default signature.
+    ///
+    /// Returns [`None`] if there is no function registered under the key or if it was registered with a different
+    /// signature.
+    #[must_use]
+    pub fn get_with_default_signature(
+        &self,
+        key: &str,
+    ) -> Option<PluginFunction<'_, component_entry_point_fn>> {
+        self.get_as(key, TypeId::of::<component_entry_point_fn>())
+    }
+
+    fn get_as<F: ManagedFnPtr>(
+        &self,
+        key: &str,
+        function_type: TypeId,
+    ) -> Option<PluginFunction<'_, F>> {
+        let entry = self.functions.get(key)?;
+        if entry.function_type != function_type {
+            return None;
+        }
+        // SAFETY: the function was resolved as `F` when the table was loaded, which was just checked.
+        Some(PluginFunction::new(ManagedFunction(unsafe {
+            F::from_ptr(entry.function.as_ptr())
+        })))
+    }
+}
+
+impl fmt::Debug for PluginFunctionTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginFunctionTable")
+            .field("generation", &self.generation)
+            .field("functions", &self.functions.keys())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for PluginFunctionTable {
+    fn drop(&mut self) {
+        if let Some(context) = self.context.take() {
+            // the context is collected in the background once it is no longer referenced.
+            let _ = context.unload();
+        }
+    }
+}
+
+/// An error returned when a [`HotReloadPlugin`] could not be (re)loaded.
+#[derive(Error, Debug)]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+pub enum ReloadError {
+    /// The plugin assembly or its symbols could not be read.
+    #[error("Failed to read the plugin assembly: {0}")]
+    Io(#[from] io::Error),
+    /// The plugin assembly could not be loaded into a new context.
+    #[error(transparent)]
+    Load(#[from] AssemblyLoadContextError),
+    /// A registered function could not be resolved from the new version of the plugin.
+    #[error("Failed to resolve the function registered as '{key}': {source}")]
+    Function {
+        /// The key the function was registered under.
+        key: String,
+        /// The reason the function could not be resolved.
+        #[source]
+        source: GetManagedFunctionError,
+    },
+}
@@ -0,0 +1,314 @@
+use crate::{
+    error::HostingError,
+    hostfxr::{AppExit, AppExitClassification, AppOrHostingResult, Hostfxr},
+    pdcstring::{PdCStr, PdCString, PdUChar},
+};
+use std::{
+    env,
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Write},
+    mem,
+    path::{Path, PathBuf},
+    process::{self, Command, ExitStatus, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The environment variable containing the path of the request file passed to an isolated child process.
+const REQUEST_ENV_VAR: &str = "NETCOREHOST_ISOLATED_REQUEST";
+/// The environment variable containing the path of the response file written by an isolated child process.
+const RESPONSE_ENV_VAR: &str = "NETCOREHOST_ISOLATED_RESPONSE";
+
+const ERROR_RECORD: char = 'E';
+const RESULT_RECORD: char = 'R';
+
+/// Loads hostfxr from the given path in a child process and runs the app at `app_path` with the given arguments
+/// there. See [`IsolatedApp`] for details.
+///
+/// # Note
+/// The current executable is used as the child process, so it has to call [`run_isolated_child`] at the start of
+/// its `main` function.
+pub fn run_app_isolated(
+    hostfxr_path: impl Into<PathBuf>,
+    app_path: impl Into<PdCString>,
+    args: impl IntoIterator<Item = impl Into<PdCString>>,
+) -> io::Result<IsolatedAppOutput> {
+    IsolatedApp::new(hostfxr_path, app_path).args(args).run()
+}
+
+/// A .NET app which is run in a child process, so that calls to `Environment.Exit`, crashes and console output of
+/// the app do not affect the current process.
+///
+/// The child process (by default the current executable) loads hostfxr, initializes a host context for the dotnet
+/// command line and runs the app. It has to call [`run_isolated_child`] at the start of its `main` function to do
+/// so.
+#[derive(Debug, Clone)]
+pub struct IsolatedApp {
+    hostfxr_path: PathBuf,
+    app_path: PdCString,
+    args: Vec<PdCString>,
+    launcher: Option<PathBuf>,
+    launcher_args: Vec<OsString>,
+}
+
+impl IsolatedApp {
+    /// Creates a new isolated app which loads hostfxr from the given path and runs the app at `app_path`.
+    pub fn new(hostfxr_path: impl Into<PathBuf>, app_path: impl Into<PdCString>) -> Self {
+        Self {
+            hostfxr_path: hostfxr_path.into(),
+            app_path: app_path.into(),
+            args: Vec::new(),
+            launcher: None,
+            launcher_args: Vec::new(),
+        }
+    }
+
+    /// Adds a command line argument for the app.
+    #[must_use]
+    pub fn arg(mut self, arg: impl Into<PdCString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Adds command line arguments for the app.
+    #[must_use]
+    pub fn args(mut self, args: impl IntoIterator<Item = impl Into<PdCString>>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets the executable spawned as the child process and the arguments it is spawned with.
+    /// Defaults to the current executable without arguments.
+    ///
+    /// The launcher has to call [`run_isolated_child`] before doing anything else.
+    #[must_use]
+    pub fn launcher(
+        mut self,
+        launcher: impl Into<PathBuf>,
+        args: impl IntoIterator<Item = impl Into<OsString>>,
+    ) -> Self {
+        self.launcher = Some(launcher.into());
+        self.launcher_args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Spawns the child process, waits for it to exit and returns its output.
+    ///
+    /// An error is only returned if the child process could not be spawned or its output could not be read. Hosting
+    /// failures and failures of the app are reported through the returned [`IsolatedAppOutput`].
+    pub fn run(&self) -> io::Result<IsolatedAppOutput> {
+        let launcher = match &self.launcher {
+            Some(launcher) => launcher.clone(),
+            None => env::current_exe()?,
+        };
+
+        let files = TempFiles::new();
+
+        let hostfxr_path = PdCString::from_os_str(&self.hostfxr_path)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        let request = iter_request(&hostfxr_path, &self.app_path, &self.args);
+        fs::write(&files.request, encode_strings(request))?;
+        File::create(&files.response)?;
+
+        let output = Command::new(launcher)
+            .args(&self.launcher_args)
+            .env(REQUEST_ENV_VAR, &files.request)
+            .env(RESPONSE_ENV_VAR, &files.response)
+            .stdin(Stdio::null())
+            .output()?;
+
+        let response = fs::read_to_string(&files.response).unwrap_or_default();
+        let mut error_writer_output = Vec::new();
+        let mut reported_result = None;
+        for record in response.split('\0').filter(|record| !record.is_empty()) {
+            let (tag, content) = record.split_at(1);
+            match tag.chars().next() {
+                Some(ERROR_RECORD) => error_writer_output.push(content.to_owned()),
+                Some(RESULT_RECORD) => {
+                    reported_result = content.parse::<i32>().ok().map(AppOrHostingResult::from);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(IsolatedAppOutput {
+            status: output.status,
+            stdout: output.stdout,
+            stderr: output.stderr,
+            error_writer_output,
+            reported_result,
+        })
+    }
+}
+
+/// The output of an app run using [`IsolatedApp::run`] or [`run_app_isolated`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsolatedAppOutput {
+    /// The exit status of the child process.
+    pub status: ExitStatus,
+    /// Everything the child process wrote to stdout.
+    pub stdout: Vec<u8>,
+    /// Everything the child process wrote to stderr.
+    pub stderr: Vec<u8>,
+    /// The messages reported to the hostfxr error writer in the child process.
+    pub error_writer_output: Vec<String>,
+    reported_result: Option<AppOrHostingResult>,
+}
+
+impl IsolatedAppOutput {
+    /// Returns the result of running the app.
+    ///
+    /// If `run_app` returned, this is its untruncated result. Otherwise (e.g. if the app called `Environment.Exit`)
+    /// this is the exit code of the child process, which may be truncated by the operating system. Returns [`None`]
+    /// if the child process was terminated by a signal.
+    #[must_use]
+    pub fn result(&self) -> Option<AppOrHostingResult> {
+        self.reported_result
+            .or_else(|| self.status.code().map(AppOrHostingResult::from))
+    }
+
+    /// Returns the result of running the app classified using the given mode, see [`AppExit::classify`].
+    /// Whether errors were reported is determined using [`error_writer_output`](IsolatedAppOutput::error_writer_output).
+    ///
+    /// Returns [`None`] if the child process was terminated by a signal.
+    #[must_use]
+    pub fn exit(&self, mode: AppExitClassification) -> Option<AppExit> {
+        let result = self.result()?;
+        Some(result.classify(!self.error_writer_output.is_empty(), mode))
+    }
+
+    /// Returns whether the child process reported a result, i.e. whether hosting failed or `run_app` returned and
+    /// the app neither exited the process itself nor crashed.
+    #[must_use]
+    pub const fn has_reported_result(&self) -> bool {
+        self.reported_result.is_some()
+    }
+}
+
+/// Runs the app requested by [`IsolatedApp::run`] and exits if the current process was spawned by it. Returns
+/// without doing anything otherwise.
+///
+/// This function has to be called at the start of the `main` function of the executable used as the child process.
+pub fn run_isolated_child() {
+    let (Some(request_path), Some(response_path)) =
+        (env::var_os(REQUEST_ENV_VAR), env::var_os(RESPONSE_ENV_VAR))
+    else {
+        return;
+    };
+
+    let Ok(mut response) = File::options().append(true).open(&response_path) else {
+        process::exit(exit_code(HostingError::InvalidArgFailure));
+    };
+    let result = run_child(Path::new(&request_path), &mut response);
+    let code = match result {
+        Ok(result) => result.value(),
+        Err(error) => exit_code(error),
+    };
+    let _ = write!(response, "{RESULT_RECORD}{code}\0");
+    let _ = response.flush();
+    process::exit(code);
+}
+
+fn run_child(request_path: &Path, response: &mut File) -> Result<AppOrHostingResult, HostingError> {
+    let mut report = {
+        let mut response = response.try_clone().ok();
+        move |message: &str| {
+            if let Some(response) = response.as_mut() {
+                let _ = write!(response, "{ERROR_RECORD}{message}\0");
+            }
+        }
+    };
+
+    let request = match fs::read(request_path) {
+        Ok(request) => decode_strings(&request),
+        Err(err) => {
+            report(&format!("Failed to read the isolated app request: {err}"));
+            return Err(HostingError::InvalidArgFailure);
+        }
+    };
+    let [hostfxr_path, app_path, args @ ..] = request.as_slice() else {
+        report("The isolated app request is malformed.");
+        return Err(HostingError::InvalidArgFailure);
+    };
+
+    let hostfxr = match Hostfxr::load_from_path(hostfxr_path.to_os_string()) {
+        Ok(hostfxr) => hostfxr,
+        Err(err) => {
+            report(&format!("Failed to load hostfxr: {err}"));
+            return Err(HostingError::CoreHostLibLoadFailure);
+        }
+    };
+
+    let mut writer_response = response.try_clone().ok();
+    hostfxr.set_error_writer(Some(Box::new(move |message: &PdCStr| {
+        if let Some(response) = writer_response.as_mut() {
+            let _ = write!(response, "{ERROR_RECORD}{}\0", message.to_string_lossy());
+        }
+    })))?;
+
+    let context = hostfxr.initialize_for_dotnet_command_line_with_args(app_path, args.iter())?;
+    Ok(context.run_app().into())
+}
+
+#[allow(clippy::cast_possible_wrap)]
+const fn exit_code(error: HostingError) -> i32 {
+    error.value() as i32
+}
+
+fn iter_request<'a>(
+    hostfxr_path: &'a PdCStr,
+    app_path: &'a PdCStr,
+    args: &'a [PdCString],
+) -> impl Iterator<Item = &'a PdCStr> {
+    [hostfxr_path, app_path]
+        .into_iter()
+        .chain(args.iter().map(PdCString::as_ref))
+}
+
+/// Encodes the given strings as their platform characters in native byte order, each followed by a nul terminator.
+fn encode_strings<'a>(strings: impl Iterator<Item = &'a PdCStr>) -> Vec<u8> {
+    strings
+        .flat_map(PdCStr::as_slice_with_nul)
+        .flat_map(|c| c.to_ne_bytes())
+        .collect()
+}
+
+fn decode_strings(bytes: &[u8]) -> Vec<PdCString> {
+    let chars = bytes
+        .chunks_exact(mem::size_of::<PdUChar>())
+        .map(|c| PdUChar::from_ne_bytes(c.try_into().unwrap()))
+        .collect::<Vec<_>>();
+    chars
+        .split_inclusive(|&c| c == 0)
+        .filter_map(|s| PdCString::from_vec(&s[..s.len() - 1]).ok())
+        .collect()
+}
+
+/// The files used to communicate with an isolated child process, which are removed on drop.
+struct TempFiles {
+    request: PathBuf,
+    response: PathBuf,
+}
+
+impl TempFiles {
+    fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let name = format!(
+            "netcorehost-isolated-{}-{}",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let dir = env::temp_dir();
+        Self {
+            request: dir.join(format!("{name}.request")),
+            response: dir.join(format!("{name}.response")),
+        }
+    }
+}
+
+impl Drop for TempFiles {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.request);
+        let _ = fs::remove_file(&self.response);
+    }
+}
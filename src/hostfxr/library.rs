@@ -1,6 +1,11 @@
 use crate::{
+    arch::{
+        ArchitectureMismatchError, CheckArchitectureError, UnreadableLibraryError,
+        check_library_architecture,
+    },
     dlopen2::wrapper::Container,
     error::{HostingError, HostingResult},
+    hostfxr::SelfContainedApp,
     pdcstring::PdCString,
 };
 use derive_more::From;
@@ -10,12 +15,27 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
+use thiserror::Error;
 
 pub(crate) type HostfxrLibrary = Container<crate::bindings::hostfxr::wrapper_option::Hostfxr>;
 pub(crate) type SharedHostfxrLibrary = Arc<HostfxrLibrary>;
-#[allow(unused, clippy::cast_possible_wrap)]
-pub(crate) const UNSUPPORTED_HOST_VERSION_ERROR_CODE: i32 =
-    HostingError::HostApiUnsupportedVersion.value() as i32;
+
+/// Prepares the current thread for calling into the hosting components or managed code.
+#[cfg(feature = "netcore3_0")]
+#[inline]
+pub(crate) fn prepare_current_thread(lib: &SharedHostfxrLibrary) {
+    #[cfg(all(unix, feature = "utils"))]
+    crate::utils::altstack::install_automatically();
+    // hosting calls still work without an error writer, their errors are then written to stderr by hostfxr.
+    if let Err(err) = crate::hostfxr::error_writer::register_error_writer(lib) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target: "netcorehost::hostfxr", %err, "failed to register the error writer");
+        #[cfg(feature = "log")]
+        log::debug!(target: "netcorehost::hostfxr", "failed to register the error writer: {err}");
+        #[cfg(not(any(feature = "log", feature = "tracing")))]
+        let _ = err;
+    }
+}
 
 /// A struct representing a loaded hostfxr library.
 #[derive(Clone, From)]
@@ -23,9 +43,19 @@ pub struct Hostfxr {
     /// The underlying hostfxr library.
     pub lib: SharedHostfxrLibrary,
     pub(crate) dotnet_exe: PdCString,
+    pub(crate) self_contained: Option<Arc<SelfContainedApp>>,
+    pub(crate) version: Option<Arc<str>>,
 }
 
 fn find_dotnet_bin(hostfxr_path: impl AsRef<Path>) -> PathBuf {
+    // hostfxr libraries of an installation are located at `<dotnet_root>/host/fxr/<version>`, which also covers
+    // installations at custom locations, e.g. configured using the install location config files.
+    if let Some(dotnet_root) = dotnet_root_from_path(hostfxr_path.as_ref()) {
+        let mut p = OsString::from(dotnet_root.join("dotnet"));
+        p.push(EXE_SUFFIX);
+        return PathBuf::from(p);
+    }
+
     let mut p = hostfxr_path.as_ref().to_path_buf();
     loop {
         if let Some(dir) = p.file_name() {
@@ -44,16 +74,60 @@ fn find_dotnet_bin(hostfxr_path: impl AsRef<Path>) -> PathBuf {
     PathBuf::from(p)
 }
 
+/// Determines the dotnet root from the `<dotnet_root>/host/fxr/<version>` directory hostfxr is installed in.
+fn dotnet_root_from_path(hostfxr_path: &Path) -> Option<&Path> {
+    hostfxr_version_from_path(hostfxr_path)?;
+    let host_dir = hostfxr_path.parent()?.parent()?.parent()?;
+    if host_dir.file_name()? != "host" {
+        return None;
+    }
+    host_dir.parent()
+}
+
+/// Determines the version of hostfxr from the `host/fxr/<version>` directory it is installed in.
+fn hostfxr_version_from_path(hostfxr_path: &Path) -> Option<Arc<str>> {
+    let version_dir = hostfxr_path.parent()?;
+    let fxr_dir = version_dir.parent()?;
+    if fxr_dir.file_name()? != "fxr" {
+        return None;
+    }
+    let version = version_dir.file_name()?.to_str()?;
+    version
+        .starts_with(|c: char| c.is_ascii_digit())
+        .then(|| Arc::from(version))
+}
+
 impl Hostfxr {
     /// Loads the hostfxr library from the given path.
-    pub fn load_from_path(path: impl AsRef<Path>) -> Result<Self, crate::dlopen2::Error> {
+    ///
+    /// If the library was built for a different architecture than the current process, loading fails with
+    /// [`LoadFromPathError::ArchitectureMismatch`].
+    pub fn load_from_path(path: impl AsRef<Path>) -> Result<Self, LoadFromPathError> {
         let path = path.as_ref();
-        let lib = SharedHostfxrLibrary::new(unsafe { Container::load(path) }?);
 
         // Some APIs of hostfxr.dll require a path to the dotnet executable, so we try to locate it here based on the hostfxr path.
-        let dotnet_exe = PdCString::from_os_str(find_dotnet_bin(path)).unwrap();
+        let dotnet_exe = find_dotnet_bin(path);
+
+        Self::load_with_dotnet_exe(path, dotnet_exe)
+    }
+
+    /// Loads the hostfxr library from the given path using the given dotnet executable for APIs which require one.
+    pub(crate) fn load_with_dotnet_exe(
+        path: impl AsRef<Path>,
+        dotnet_exe: impl AsRef<Path>,
+    ) -> Result<Self, LoadFromPathError> {
+        let path = path.as_ref();
+        check_library_architecture(path)?;
+
+        let lib = SharedHostfxrLibrary::new(unsafe { Container::load(path) }?);
+        let dotnet_exe = PdCString::from_os_str(dotnet_exe.as_ref())?;
 
-        Ok(Self { lib, dotnet_exe })
+        Ok(Self {
+            lib,
+            dotnet_exe,
+            self_contained: None,
+            version: hostfxr_version_from_path(path),
+        })
     }
 
     /// Locates the hostfxr library using [`nethost`](crate::nethost) and loads it.
@@ -63,12 +137,22 @@ impl Hostfxr {
     }
 
     /// Returns the path to the dotnet root.
+    /// For self-contained apps this is the directory of the app.
     #[must_use]
     pub fn get_dotnet_root(&self) -> PathBuf {
         self.get_dotnet_exe().parent().unwrap().to_owned()
     }
 
+    /// Returns the version of hostfxr if it could be determined from the `host/fxr/<version>` directory it was
+    /// loaded from. The version is not read from the library itself, so this returns [`None`] for hostfxr libraries
+    /// loaded from custom layouts, like the directory of a self-contained app or a copy of hostfxr placed elsewhere.
+    #[must_use]
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
     /// Returns the path to the dotnet executable of the same installation as hostfxr.
+    /// For self-contained apps this is the path to the apphost of the app.
     #[must_use]
     pub fn get_dotnet_exe(&self) -> PathBuf {
         self.dotnet_exe.to_os_string().into()
@@ -91,6 +175,12 @@ impl AppOrHostingResult {
     pub fn as_hosting_exit_code(self) -> HostingResult {
         HostingResult::from(self.0)
     }
+
+    /// Classifies the result using the given mode, see [`AppExit::classify`].
+    #[must_use]
+    pub fn classify(self, errors_reported: bool, mode: AppExitClassification) -> AppExit {
+        AppExit::classify(self, errors_reported, mode)
+    }
 }
 
 impl From<AppOrHostingResult> for i32 {
@@ -104,3 +194,142 @@ impl From<i32> for AppOrHostingResult {
         Self(code)
     }
 }
+
+/// The range of status codes used by the hosting components.
+const HOSTING_STATUS_RANGE: std::ops::RangeInclusive<u32> = 0x8000_8000..=0x8000_80FF;
+
+/// The mode used to decide whether the result of running an app is an exit code of the app or a hosting failure.
+/// See [`AppExit::classify`] for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AppExitClassification {
+    /// Known hosting error status codes are treated as hosting failures, as are unknown status codes in the hosting
+    /// range if the hosting components reported an error.
+    #[default]
+    Heuristic,
+    /// Status codes in the hosting range are only treated as hosting failures if the hosting components reported an
+    /// error. Every other value is treated as an exit code of the app.
+    Strict,
+}
+
+/// The structured result of running an app, distinguishing the exit code of the app from a failure of the hosting
+/// components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AppExit {
+    /// The app ran and exited with the given exit code.
+    AppExited(i32),
+    /// The hosting components failed to run the app.
+    HostingFailed(HostingError),
+}
+
+impl AppExit {
+    /// Classifies the raw result of running an app.
+    ///
+    /// The raw result is either the exit code of the app or a hosting status code, which cannot be told apart in
+    /// general as an app can exit with any value. The classification therefore uses the following heuristic:
+    /// - Values outside of the status code range of the hosting components (`0x80008000` to `0x800080FF`) are
+    ///   always exit codes of the app.
+    /// - In [`Heuristic`](AppExitClassification::Heuristic) mode, known hosting error status codes are hosting
+    ///   failures. Unknown status codes in the range are hosting failures if `errors_reported` is `true`.
+    /// - In [`Strict`](AppExitClassification::Strict) mode, status codes in the range are only hosting failures if
+    ///   `errors_reported` is `true`.
+    ///
+    /// `errors_reported` should indicate whether the hosting components reported an error through the error writer
+    /// while running the app, as they do for every failure.
+    #[must_use]
+    pub fn classify(
+        result: AppOrHostingResult,
+        errors_reported: bool,
+        mode: AppExitClassification,
+    ) -> Self {
+        #[allow(clippy::cast_sign_loss)]
+        let code = result.value() as u32;
+        if !HOSTING_STATUS_RANGE.contains(&code) {
+            return Self::AppExited(result.value());
+        }
+
+        let is_hosting_failure = match mode {
+            AppExitClassification::Heuristic => {
+                errors_reported || HostingError::known_from_status_code(code).is_ok()
+            }
+            AppExitClassification::Strict => errors_reported,
+        };
+        if is_hosting_failure {
+            Self::HostingFailed(HostingError::from_status_code(code))
+        } else {
+            Self::AppExited(result.value())
+        }
+    }
+
+    /// Gets the raw value of the result.
+    #[must_use]
+    pub const fn value(&self) -> i32 {
+        match self {
+            #[allow(clippy::cast_possible_wrap)]
+            Self::HostingFailed(error) => error.value() as i32,
+            Self::AppExited(exit_code) => *exit_code,
+        }
+    }
+
+    /// Returns the exit code of the app if it ran.
+    #[must_use]
+    pub const fn exit_code(&self) -> Option<i32> {
+        match self {
+            Self::AppExited(exit_code) => Some(*exit_code),
+            Self::HostingFailed(_) => None,
+        }
+    }
+
+    /// Returns the error of the hosting components if they failed to run the app.
+    #[must_use]
+    pub const fn hosting_error(&self) -> Option<HostingError> {
+        match self {
+            Self::AppExited(_) => None,
+            Self::HostingFailed(error) => Some(*error),
+        }
+    }
+
+    /// Transforms the result into a [`Result`] containing the exit code of the app.
+    pub const fn into_result(self) -> Result<i32, HostingError> {
+        match self {
+            Self::AppExited(exit_code) => Ok(exit_code),
+            Self::HostingFailed(error) => Err(error),
+        }
+    }
+
+    /// Converts the raw value of the result to an hosting exit code.
+    pub fn as_hosting_exit_code(self) -> HostingResult {
+        HostingResult::from(self.value())
+    }
+}
+
+impl From<AppExit> for AppOrHostingResult {
+    fn from(exit: AppExit) -> Self {
+        Self(exit.value())
+    }
+}
+
+/// Enum for errors that can occur while loading the hostfxr library using [`Hostfxr::load_from_path`].
+#[derive(Debug, Error)]
+pub enum LoadFromPathError {
+    /// The hostfxr library was built for a different architecture than the current process.
+    #[error(transparent)]
+    ArchitectureMismatch(#[from] ArchitectureMismatchError),
+    /// The header of the hostfxr library could not be read to determine its architecture.
+    #[error(transparent)]
+    UnreadableLibrary(#[from] UnreadableLibraryError),
+    /// An error occured while loading the hostfxr library.
+    #[error(transparent)]
+    DlOpen(#[from] crate::dlopen2::Error),
+    /// The path to the dotnet executable contains a nul character.
+    #[error(transparent)]
+    InvalidPath(#[from] crate::pdcstring::ContainsNul),
+}
+
+impl From<CheckArchitectureError> for LoadFromPathError {
+    fn from(err: CheckArchitectureError) -> Self {
+        match err {
+            CheckArchitectureError::ArchitectureMismatch(err) => Self::ArchitectureMismatch(err),
+            CheckArchitectureError::UnreadableLibrary(err) => Self::UnreadableLibrary(err),
+        }
+    }
+}
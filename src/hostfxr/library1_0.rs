@@ -1,10 +1,9 @@
 use crate::{
+    error::HostingError,
     hostfxr::{AppOrHostingResult, Hostfxr},
     pdcstring::PdCStr,
 };
 
-use super::UNSUPPORTED_HOST_VERSION_ERROR_CODE;
-
 impl Hostfxr {
     /// Run an application.
     ///
@@ -19,8 +18,7 @@ impl Hostfxr {
         allow(deprecated)
     )]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore1_0")))]
-    #[must_use]
-    pub fn run_app(&self, app_path: &PdCStr) -> AppOrHostingResult {
+    pub fn run_app(&self, app_path: &PdCStr) -> Result<AppOrHostingResult, HostingError> {
         self.run_app_with_args::<&PdCStr>(app_path, &[])
     }
 
@@ -40,7 +38,7 @@ impl Hostfxr {
         &self,
         app_path: &PdCStr,
         args: &[A],
-    ) -> AppOrHostingResult {
+    ) -> Result<AppOrHostingResult, HostingError> {
         let args = [&self.dotnet_exe, app_path]
             .into_iter()
             .chain(args.iter().map(|s| s.as_ref()))
@@ -51,8 +49,10 @@ impl Hostfxr {
             self.lib
                 .hostfxr_main(args.len().try_into().unwrap(), args.as_ptr())
         }
-        .unwrap_or(UNSUPPORTED_HOST_VERSION_ERROR_CODE);
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_main",
+        })?;
 
-        AppOrHostingResult::from(result)
+        Ok(AppOrHostingResult::from(result))
     }
 }
@@ -9,9 +9,7 @@ use crate::{
 
 use coreclr_hosting_shared::char_t;
 
-use std::{cell::RefCell, io, mem::MaybeUninit, path::PathBuf, ptr, slice};
-
-use super::UNSUPPORTED_HOST_VERSION_ERROR_CODE;
+use std::{cell::RefCell, mem::MaybeUninit, path::PathBuf, ptr, slice};
 
 impl Hostfxr {
     /// Run an application.
@@ -42,7 +40,7 @@ impl Hostfxr {
         args: impl IntoIterator<Item = &'a PdCStr>,
         host_path: &PdCStr,
         dotnet_root: &PdCStr,
-    ) -> io::Result<AppOrHostingResult> {
+    ) -> Result<AppOrHostingResult, HostingError> {
         let args = [&self.dotnet_exe, app_path]
             .into_iter()
             .chain(args)
@@ -58,7 +56,9 @@ impl Hostfxr {
                 app_path.as_ptr(),
             )
         }
-        .unwrap_or(UNSUPPORTED_HOST_VERSION_ERROR_CODE);
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_main_startupinfo",
+        })?;
 
         Ok(AppOrHostingResult::from(result))
     }
@@ -94,7 +94,9 @@ impl Hostfxr {
                 resolve_sdk2_callback,
             )
         }
-        .unwrap_or(UNSUPPORTED_HOST_VERSION_ERROR_CODE);
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_resolve_sdk2",
+        })?;
         HostingResult::from(result).into_result()?;
 
         let raw_result = RESOLVE_SDK2_DATA
@@ -105,28 +107,34 @@ impl Hostfxr {
 
     /// Get the list of all available SDKs ordered by ascending version.
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore2_1")))]
-    #[must_use]
-    pub fn get_available_sdks(&self) -> Vec<PathBuf> {
+    pub fn get_available_sdks(&self) -> Result<Vec<PathBuf>, HostingError> {
         self.get_available_sdks_raw(None)
     }
 
     /// Get the list of all available SDKs ordered by ascending version, based on the provided `dotnet` executable.
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore2_1")))]
-    #[must_use]
-    pub fn get_available_sdks_with_dotnet_path(&self, dotnet_path: &PdCStr) -> Vec<PathBuf> {
+    pub fn get_available_sdks_with_dotnet_path(
+        &self,
+        dotnet_path: &PdCStr,
+    ) -> Result<Vec<PathBuf>, HostingError> {
         self.get_available_sdks_raw(Some(dotnet_path))
     }
 
-    #[must_use]
-    fn get_available_sdks_raw(&self, dotnet_path: Option<&PdCStr>) -> Vec<PathBuf> {
+    fn get_available_sdks_raw(
+        &self,
+        dotnet_path: Option<&PdCStr>,
+    ) -> Result<Vec<PathBuf>, HostingError> {
         let dotnet_path = dotnet_path.map_or_else(ptr::null, |s| s.as_ptr());
         unsafe {
             self.lib
                 .hostfxr_get_available_sdks(dotnet_path, get_available_sdks_callback)
-        };
-        GET_AVAILABLE_SDKS_DATA
+        }
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_get_available_sdks",
+        })?;
+        Ok(GET_AVAILABLE_SDKS_DATA
             .with(|sdks| sdks.borrow_mut().take())
-            .unwrap()
+            .unwrap_or_default())
     }
 
     /// Get the native search directories of the runtime based upon the specified app.
@@ -150,7 +158,10 @@ impl Hostfxr {
                 0,
                 required_buffer_size.as_mut_ptr(),
             )
-        };
+        }
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_get_native_search_directories",
+        })?;
         let mut required_buffer_size = unsafe { required_buffer_size.assume_init() };
 
         buffer.reserve(required_buffer_size.try_into().unwrap());
@@ -163,7 +174,9 @@ impl Hostfxr {
                 &raw mut required_buffer_size,
             )
         }
-        .unwrap_or(UNSUPPORTED_HOST_VERSION_ERROR_CODE);
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_get_native_search_directories",
+        })?;
         HostingResult::from(result).into_result()?;
         unsafe { buffer.set_len(required_buffer_size.try_into().unwrap()) };
 
@@ -1,15 +1,14 @@
 use crate::{
     bindings::hostfxr::{hostfxr_handle, hostfxr_initialize_parameters},
-    error::{HostingError, HostingResult, HostingSuccess},
+    error::{HostingCallError, HostingError, HostingResult, HostingSuccess},
     hostfxr::{
-        Hostfxr, HostfxrContext, HostfxrHandle, InitializedForCommandLine,
-        InitializedForRuntimeConfig,
+        ErrorCollector, ErrorWriter, Hostfxr, HostfxrContext, HostfxrHandle,
+        InitializedForCommandLine, InitializedForRuntimeConfig, diagnostics::hosting_call,
+        error_writer::set_thread_error_writer, prepare_current_thread,
     },
-    pdcstring::{PdCStr, PdChar},
+    pdcstring::PdCStr,
 };
-use std::{iter, mem::MaybeUninit, ptr};
-
-use super::UNSUPPORTED_HOST_VERSION_ERROR_CODE;
+use std::{iter, mem::MaybeUninit};
 
 impl Hostfxr {
     /// Initializes the hosting components for a dotnet command line running an application
@@ -34,7 +33,7 @@ impl Hostfxr {
     pub fn initialize_for_dotnet_command_line(
         &self,
         app_path: impl AsRef<PdCStr>,
-    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingError> {
+    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingCallError> {
         self.initialize_for_dotnet_command_line_with_args(app_path, iter::empty::<&PdCStr>())
     }
 
@@ -67,7 +66,7 @@ impl Hostfxr {
         &self,
         app_path: impl AsRef<PdCStr>,
         host_path: impl AsRef<PdCStr>,
-    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingError> {
+    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingCallError> {
         self.initialize_for_dotnet_command_line_with_args_and_host_path(
             app_path,
             iter::empty::<&PdCStr>(),
@@ -103,7 +102,7 @@ impl Hostfxr {
         &self,
         app_path: impl AsRef<PdCStr>,
         dotnet_root: impl AsRef<PdCStr>,
-    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingError> {
+    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingCallError> {
         self.initialize_for_dotnet_command_line_with_args_and_dotnet_root(
             app_path,
             iter::empty::<&PdCStr>(),
@@ -136,10 +135,10 @@ impl Hostfxr {
         &self,
         app_path: impl AsRef<PdCStr>,
         args: impl Iterator<Item = impl AsRef<PdCStr>>,
-    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingError> {
-        unsafe {
-            self.initialize_for_dotnet_command_line_with_parameters(app_path, args, ptr::null())
-        }
+    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingCallError> {
+        self.with_default_initialize_parameters(|parameters| unsafe {
+            self.initialize_for_dotnet_command_line_with_parameters(app_path, args, parameters)
+        })
     }
 
     /// Initializes the hosting components for a dotnet command line running an application
@@ -174,7 +173,7 @@ impl Hostfxr {
         app_path: impl AsRef<PdCStr>,
         args: impl Iterator<Item = impl AsRef<PdCStr>>,
         host_path: impl AsRef<PdCStr>,
-    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingError> {
+    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingCallError> {
         let parameters = hostfxr_initialize_parameters::with_host_path(host_path.as_ref().as_ptr());
         unsafe {
             self.initialize_for_dotnet_command_line_with_parameters(
@@ -216,7 +215,7 @@ impl Hostfxr {
         app_path: impl AsRef<PdCStr>,
         args: impl Iterator<Item = impl AsRef<PdCStr>>,
         dotnet_root: impl AsRef<PdCStr>,
-    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingError> {
+    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingCallError> {
         let parameters =
             hostfxr_initialize_parameters::with_dotnet_root(dotnet_root.as_ref().as_ptr());
         unsafe {
@@ -233,23 +232,43 @@ impl Hostfxr {
         app_path: impl AsRef<PdCStr>,
         args: impl Iterator<Item = impl AsRef<PdCStr>>,
         parameters: *const hostfxr_initialize_parameters,
-    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingError> {
+    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingCallError> {
+        prepare_current_thread(&self.lib);
         let mut hostfxr_handle = MaybeUninit::<hostfxr_handle>::uninit();
 
-        let app_path = app_path.as_ref().as_ptr();
+        let app_path = app_path.as_ref();
         let args = args.map(|arg| arg.as_ref().as_ptr());
-        let app_path_and_args = iter::once(app_path).chain(args).collect::<Vec<_>>();
-        let result = unsafe {
-            self.lib.hostfxr_initialize_for_dotnet_command_line(
-                app_path_and_args.len().try_into().unwrap(),
-                app_path_and_args.as_ptr(),
-                parameters,
-                hostfxr_handle.as_mut_ptr(),
-            )
-        }
-        .unwrap_or(UNSUPPORTED_HOST_VERSION_ERROR_CODE);
+        let app_path_and_args = iter::once(app_path.as_ptr())
+            .chain(args)
+            .collect::<Vec<_>>();
+        let collector = ErrorCollector::new();
+        let result = hosting_call!(
+            "hostfxr_initialize_for_dotnet_command_line",
+            app_path = app_path;
+            unsafe {
+                self.lib.hostfxr_initialize_for_dotnet_command_line(
+                    app_path_and_args.len().try_into().unwrap(),
+                    app_path_and_args.as_ptr(),
+                    parameters,
+                    hostfxr_handle.as_mut_ptr(),
+                )
+            }
+        )
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_initialize_for_dotnet_command_line",
+        });
 
-        let success_code = HostingResult::from(result).into_result()?;
+        let success_code = result
+            .and_then(|result| HostingResult::from(result).into_result())
+            .map_err(|code| unsafe {
+                self.call_error(
+                    code,
+                    "hostfxr_initialize_for_dotnet_command_line",
+                    collector,
+                )
+                .with_arg("app_path", app_path)
+                .with_parameters(parameters)
+            })?;
 
         let is_primary = matches!(success_code, HostingSuccess::Success);
 
@@ -284,10 +303,10 @@ impl Hostfxr {
     pub fn initialize_for_runtime_config(
         &self,
         runtime_config_path: impl AsRef<PdCStr>,
-    ) -> Result<HostfxrContext<InitializedForRuntimeConfig>, HostingError> {
-        unsafe {
-            self.initialize_for_runtime_config_with_parameters(runtime_config_path, ptr::null())
-        }
+    ) -> Result<HostfxrContext<InitializedForRuntimeConfig>, HostingCallError> {
+        self.with_default_initialize_parameters(|parameters| unsafe {
+            self.initialize_for_runtime_config_with_parameters(runtime_config_path, parameters)
+        })
     }
 
     /// This function loads the specified `.runtimeconfig.json`, resolve all frameworks, resolve all the assets from those frameworks and
@@ -319,7 +338,7 @@ impl Hostfxr {
         &self,
         runtime_config_path: impl AsRef<PdCStr>,
         host_path: impl AsRef<PdCStr>,
-    ) -> Result<HostfxrContext<InitializedForRuntimeConfig>, HostingError> {
+    ) -> Result<HostfxrContext<InitializedForRuntimeConfig>, HostingCallError> {
         let parameters = hostfxr_initialize_parameters::with_host_path(host_path.as_ref().as_ptr());
         unsafe {
             self.initialize_for_runtime_config_with_parameters(
@@ -356,7 +375,7 @@ impl Hostfxr {
         &self,
         runtime_config_path: impl AsRef<PdCStr>,
         dotnet_root: impl AsRef<PdCStr>,
-    ) -> Result<HostfxrContext<InitializedForRuntimeConfig>, HostingError> {
+    ) -> Result<HostfxrContext<InitializedForRuntimeConfig>, HostingCallError> {
         let parameters =
             hostfxr_initialize_parameters::with_dotnet_root(dotnet_root.as_ref().as_ptr());
         unsafe {
@@ -371,19 +390,34 @@ impl Hostfxr {
         &self,
         runtime_config_path: impl AsRef<PdCStr>,
         parameters: *const hostfxr_initialize_parameters,
-    ) -> Result<HostfxrContext<InitializedForRuntimeConfig>, HostingError> {
+    ) -> Result<HostfxrContext<InitializedForRuntimeConfig>, HostingCallError> {
+        prepare_current_thread(&self.lib);
         let mut hostfxr_handle = MaybeUninit::uninit();
 
-        let result = unsafe {
-            self.lib.hostfxr_initialize_for_runtime_config(
-                runtime_config_path.as_ref().as_ptr(),
-                parameters,
-                hostfxr_handle.as_mut_ptr(),
-            )
-        }
-        .unwrap_or(UNSUPPORTED_HOST_VERSION_ERROR_CODE);
+        let runtime_config_path = runtime_config_path.as_ref();
+        let collector = ErrorCollector::new();
+        let result = hosting_call!(
+            "hostfxr_initialize_for_runtime_config",
+            runtime_config_path = runtime_config_path;
+            unsafe {
+                self.lib.hostfxr_initialize_for_runtime_config(
+                    runtime_config_path.as_ptr(),
+                    parameters,
+                    hostfxr_handle.as_mut_ptr(),
+                )
+            }
+        )
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_initialize_for_runtime_config",
+        });
 
-        let success_code = HostingResult::from(result).into_result()?;
+        let success_code = result
+            .and_then(|result| HostingResult::from(result).into_result())
+            .map_err(|code| unsafe {
+                self.call_error(code, "hostfxr_initialize_for_runtime_config", collector)
+                    .with_arg("runtime_config_path", runtime_config_path)
+                    .with_parameters(parameters)
+            })?;
 
         let is_primary = matches!(success_code, HostingSuccess::Success);
 
@@ -396,6 +430,18 @@ impl Hostfxr {
         })
     }
 
+    /// Creates the error for a failed hosting call including the messages collected while it ran.
+    pub(crate) fn call_error(
+        &self,
+        code: HostingError,
+        operation: &'static str,
+        collector: ErrorCollector,
+    ) -> HostingCallError {
+        HostingCallError::new(code, operation)
+            .with_messages(collector.finish())
+            .with_hostfxr_version(self.version.clone())
+    }
+
     /// Sets a callback which is to be used to write errors to.
     ///
     /// # Arguments
@@ -407,7 +453,9 @@ impl Hostfxr {
     /// The error writer is registered per-thread, so the registration is thread-local. On each thread
     /// only one callback can be registered. Subsequent registrations overwrite the previous ones.
     ///
-    /// By default no callback is registered in which case the errors are written to stderr.
+    /// By default no callback is registered in which case the errors are written to the
+    /// [global error writer](crate::hostfxr::set_global_error_writer). If there is none, they are logged if the `log`
+    /// or `tracing` feature is enabled and dropped otherwise.
     ///
     /// Each call to the error writer is sort of like writing a single line (the EOL character is omitted).
     /// Multiple calls to the error writer may occure for one failure.
@@ -415,30 +463,10 @@ impl Hostfxr {
     /// If the hostfxr invokes functions in hostpolicy as part of its operation, the error writer
     /// will be propagated to hostpolicy for the duration of the call. This means that errors from
     /// both hostfxr and hostpolicy will be reporter through the same error writer.
+    ///
+    /// Returns [`HostingError::MissingExport`] if the loaded hostfxr does not export `hostfxr_set_error_writer`.
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
-    pub fn set_error_writer(&self, error_writer: Option<ErrorWriter>) {
-        let new_raw_error_writer = error_writer
-            .as_ref()
-            .map(|_| error_writer_trampoline as hostfxr_sys::hostfxr_error_writer_fn);
-        unsafe { self.lib.hostfxr_set_error_writer(new_raw_error_writer) };
-
-        CURRENT_ERROR_WRITER.with(|current_writer| {
-            *current_writer.borrow_mut() = error_writer;
-        });
+    pub fn set_error_writer(&self, error_writer: Option<ErrorWriter>) -> Result<(), HostingError> {
+        set_thread_error_writer(&self.lib, error_writer)
     }
 }
-
-type ErrorWriter = Box<dyn FnMut(&PdCStr)>;
-
-thread_local! {
-    static CURRENT_ERROR_WRITER: std::cell::RefCell<Option<ErrorWriter>> = std::cell::RefCell::new(None);
-}
-
-extern "C" fn error_writer_trampoline(raw_error: *const PdChar) {
-    CURRENT_ERROR_WRITER.with(|writer_holder| {
-        if let Some(writer) = writer_holder.borrow_mut().as_mut() {
-            let error_message = unsafe { PdCStr::from_str_ptr(raw_error) };
-            writer(error_message);
-        }
-    });
-}
@@ -7,8 +7,6 @@ use crate::{
 };
 use std::{ffi::c_void, mem::MaybeUninit, path::PathBuf, ptr, slice};
 
-use super::UNSUPPORTED_HOST_VERSION_ERROR_CODE;
-
 /// Information about the current dotnet environment loaded using [Hostfxr::get_dotnet_environment_info].
 #[derive(Debug, Clone)]
 pub struct EnvironmentInfo {
@@ -115,7 +113,9 @@ impl Hostfxr {
                 info.as_mut_ptr().cast(),
             )
         }
-        .unwrap_or(UNSUPPORTED_HOST_VERSION_ERROR_CODE);
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_get_dotnet_environment_info",
+        })?;
         HostingResult::from(result).into_result()?;
         let info = unsafe { MaybeUninit::assume_init(info) };
         Ok(info)
@@ -0,0 +1,407 @@
+use crate::{
+    bindings::{char_t, hostfxr::UNMANAGED_CALLERS_ONLY_METHOD},
+    error::HResult,
+    hostfxr::{
+        AssemblyBytes, DelegateLoader, FnPtr, GetManagedFunctionError, ManagedFunction,
+        ManagedFunctionWithDefaultSignature, RawFnPtr, SharedHostfxrLibrary,
+        resolution::FunctionRequest,
+    },
+    pdcstr,
+    pdcstring::{PdCStr, PdCString},
+};
+use fn_ptr::{WithAbi, abi::System};
+use std::{ffi::c_void, fmt, mem::MaybeUninit, ptr, sync::Arc};
+use thiserror::Error;
+
+type CreateContextFn = extern "system" fn(*const char_t, i32, *mut isize) -> i32;
+type LoadFromPathFn = extern "system" fn(isize, *const char_t) -> i32;
+type LoadFromBytesFn = extern "system" fn(isize, *const u8, isize, *const u8, isize) -> i32;
+type GetFunctionPointerFn = extern "system" fn(
+    isize,
+    *const char_t,
+    *const char_t,
+    *const char_t,
+    *mut *const c_void,
+) -> i32;
+type UnloadFn = extern "system" fn(isize, *mut isize) -> i32;
+type IsAliveFn = extern "system" fn(isize, i32) -> i32;
+type FreeHandleFn = extern "system" fn(isize);
+
+/// The entry points of the managed helper for working with `AssemblyLoadContext`s.
+///
+/// The helper is a small managed assembly shipped with this crate in `helper/NetCoreHost.Helper`, which has to be
+/// built with `dotnet build` and loaded using [`AssemblyLoadContextHelper::load`] before any
+/// [`AssemblyLoadContextHandle`] can be created.
+///
+/// # Example
+/// ```no_run
+/// # use netcorehost::{hostfxr::AssemblyLoadContextHelper, nethost, pdcstr};
+/// let hostfxr = nethost::load_hostfxr()?;
+/// let context = hostfxr.initialize_for_runtime_config(pdcstr!("Test.runtimeconfig.json"))?;
+/// let helper = AssemblyLoadContextHelper::load(
+///     &context.get_delegate_loader()?,
+///     pdcstr!("NetCoreHost.Helper.dll"),
+/// )?;
+///
+/// let plugins = helper.create_context(pdcstr!("Plugins"), false)?;
+/// plugins.load_assembly_from_path(pdcstr!("Plugin.dll"))?;
+/// let run = plugins.get_function_with_unmanaged_callers_only::<fn() -> i32>(
+///     pdcstr!("Plugin.Entry, Plugin"),
+///     pdcstr!("Run"),
+/// )?;
+/// run();
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+pub struct AssemblyLoadContextHelper(Arc<HelperFunctions>);
+
+struct HelperFunctions {
+    create_context: CreateContextFn,
+    load_from_path: LoadFromPathFn,
+    load_from_bytes: LoadFromBytesFn,
+    get_function_pointer: GetFunctionPointerFn,
+    unload: UnloadFn,
+    is_alive: IsAliveFn,
+    free_handle: FreeHandleFn,
+    #[allow(unused)]
+    hostfxr: SharedHostfxrLibrary,
+}
+
+impl AssemblyLoadContextHelper {
+    /// Loads the managed helper from the given path and resolves its entry points.
+    pub fn load(
+        loader: &DelegateLoader,
+        helper_assembly_path: impl AsRef<PdCStr>,
+    ) -> Result<Self, GetManagedFunctionError> {
+        let helper_assembly_path = helper_assembly_path.as_ref();
+        let type_name = pdcstr!("NetCoreHost.Helper.AssemblyLoadContextHelper, NetCoreHost.Helper");
+        macro_rules! entry_point {
+            ($fn:ty, $name:literal) => {
+                loader
+                    .load_assembly_and_get_function_with_unmanaged_callers_only::<$fn>(
+                        helper_assembly_path,
+                        type_name,
+                        pdcstr!($name),
+                    )?
+                    .0
+            };
+        }
+        Ok(Self(Arc::new(HelperFunctions {
+            create_context: entry_point!(CreateContextFn, "CreateContext"),
+            load_from_path: entry_point!(LoadFromPathFn, "LoadFromPath"),
+            load_from_bytes: entry_point!(LoadFromBytesFn, "LoadFromBytes"),
+            get_function_pointer: entry_point!(GetFunctionPointerFn, "GetFunctionPointer"),
+            unload: entry_point!(UnloadFn, "Unload"),
+            is_alive: entry_point!(IsAliveFn, "IsAlive"),
+            free_handle: entry_point!(FreeHandleFn, "FreeHandle"),
+            hostfxr: loader.hostfxr.clone(),
+        })))
+    }
+
+    /// Creates a new `AssemblyLoadContext` with the given name.
+    ///
+    /// Assemblies loaded into a collectible context can be unloaded together with the context.
+    pub fn create_context(
+        &self,
+        name: impl AsRef<PdCStr>,
+        is_collectible: bool,
+    ) -> Result<AssemblyLoadContextHandle, AssemblyLoadContextError> {
+        let name = name.as_ref();
+        let mut handle = MaybeUninit::uninit();
+        let result = (self.0.create_context)(
+            name.as_ptr(),
+            i32::from(is_collectible),
+            handle.as_mut_ptr(),
+        );
+        AssemblyLoadContextError::check("CreateContext", result)?;
+        Ok(AssemblyLoadContextHandle {
+            helper: self.clone(),
+            handle: unsafe { handle.assume_init() },
+            name: name.to_owned(),
+            is_collectible,
+        })
+    }
+}
+
+impl fmt::Debug for AssemblyLoadContextHelper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssemblyLoadContextHelper")
+            .finish_non_exhaustive()
+    }
+}
+
+/// A handle to an `AssemblyLoadContext` created using [`AssemblyLoadContextHelper::create_context`].
+///
+/// Assemblies can be loaded into the context and functions are resolved from the assemblies loaded into it. Types
+/// from assemblies that have not been loaded yet are loaded into the context on first use.
+/// The context is released once the handle is dropped, collectible contexts can be unloaded explicitly using
+/// [`unload`](AssemblyLoadContextHandle::unload).
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+pub struct AssemblyLoadContextHandle {
+    helper: AssemblyLoadContextHelper,
+    handle: isize,
+    name: PdCString,
+    is_collectible: bool,
+}
+
+impl AssemblyLoadContextHandle {
+    /// Returns the name of the context.
+    #[must_use]
+    pub fn name(&self) -> &PdCStr {
+        &self.name
+    }
+
+    /// Returns whether the context is collectible.
+    #[must_use]
+    pub const fn is_collectible(&self) -> bool {
+        self.is_collectible
+    }
+
+    /// Loads the assembly at the given path into this context.
+    pub fn load_assembly_from_path(
+        &self,
+        assembly_path: impl AsRef<PdCStr>,
+    ) -> Result<(), AssemblyLoadContextError> {
+        let result = (self.helper.0.load_from_path)(self.handle, assembly_path.as_ref().as_ptr());
+        AssemblyLoadContextError::check("LoadFromPath", result)
+    }
+
+    /// Loads the assembly from the given buffers into this context. Symbols are only passed if `symbols_bytes` is
+    /// not empty.
+    pub fn load_assembly_from_bytes(
+        &self,
+        assembly_bytes: impl AsRef<[u8]>,
+        symbols_bytes: impl AsRef<[u8]>,
+    ) -> Result<(), AssemblyLoadContextError> {
+        let symbols_bytes = symbols_bytes.as_ref();
+        self.load_assembly_from_memory(AssemblyBytes::with_optional_symbols(
+            assembly_bytes.as_ref(),
+            (!symbols_bytes.is_empty()).then_some(symbols_bytes),
+        ))
+    }
+
+    /// Loads the given assembly into this context from memory.
+    pub fn load_assembly_from_memory(
+        &self,
+        assembly: AssemblyBytes<'_>,
+    ) -> Result<(), AssemblyLoadContextError> {
+        let assembly_bytes = assembly.assembly();
+        let (symbols_ptr, symbols_len) = assembly.symbols().map_or((ptr::null(), 0), |symbols| {
+            (symbols.as_ptr(), symbols.len())
+        });
+        #[allow(clippy::cast_possible_wrap)]
+        let result = (self.helper.0.load_from_bytes)(
+            self.handle,
+            assembly_bytes.as_ptr(),
+            assembly_bytes.len() as isize,
+            symbols_ptr,
+            symbols_len as isize,
+        );
+        AssemblyLoadContextError::check("LoadFromBytes", result)
+    }
+
+    /// Starts unloading this context, which has to be collectible.
+    ///
+    /// The context is only collected once no managed or native code references it anymore, which can be observed
+    /// using the returned [`UnloadingContext`]. Functions resolved from this context must not be called after this.
+    pub fn unload(mut self) -> Result<UnloadingContext, AssemblyLoadContextError> {
+        let mut weak_handle = MaybeUninit::uninit();
+        let result = (self.helper.0.unload)(self.handle, weak_handle.as_mut_ptr());
+        AssemblyLoadContextError::check("Unload", result)?;
+        // the helper released the handle to the context.
+        self.handle = 0;
+        Ok(UnloadingContext {
+            helper: self.helper.clone(),
+            weak_handle: unsafe { weak_handle.assume_init() },
+        })
+    }
+
+    fn get_function_pointer_raw(
+        &self,
+        request: &FunctionRequest<'_>,
+        delegate_type_name: *const char_t,
+    ) -> Result<RawFnPtr, GetManagedFunctionError> {
+        let mut function = MaybeUninit::uninit();
+        let result = (self.helper.0.get_function_pointer)(
+            self.handle,
+            request.type_name.as_ptr(),
+            request.method_name.as_ptr(),
+            delegate_type_name,
+            function.as_mut_ptr(),
+        );
+        if result < 0 {
+            return Err(request.diagnose(HResult::from(result)).into());
+        }
+        Ok(unsafe { function.assume_init() }.cast())
+    }
+
+    /// Finds the specified type and method in this context and returns a native function pointer to that method.
+    ///
+    /// # Arguments
+    ///  * `type_name`:
+    ///    Assembly qualified type name to find
+    ///  * `method_name`:
+    ///    Name of the method on the `type_name` to find. The method must be static and must match the signature of `delegate_type_name`.
+    ///  * `delegate_type_name`:
+    ///    Assembly qualified delegate type name for the method signature.
+    pub fn get_function<F: FnPtr + WithAbi<System>>(
+        &self,
+        type_name: impl AsRef<PdCStr>,
+        method_name: &PdCStr,
+        delegate_type_name: impl AsRef<PdCStr>,
+    ) -> Result<ManagedFunction<<F as WithAbi<System>>::F>, GetManagedFunctionError> {
+        let delegate_type_name = delegate_type_name.as_ref();
+        let request = FunctionRequest {
+            assembly_path: None,
+            type_name: type_name.as_ref(),
+            method_name,
+            unmanaged_callers_only: false,
+            delegate_type_name: Some(delegate_type_name),
+        };
+        let function = self.get_function_pointer_raw(&request, delegate_type_name.as_ptr())?;
+        Ok(ManagedFunction(unsafe {
+            <<F as WithAbi<System>>::F>::from_ptr(function)
+        }))
+    }
+
+    /// Finds the specified type and method in this context and returns a native function pointer to that method.
+    ///
+    /// # Arguments
+    ///  * `type_name`:
+    ///    Assembly qualified type name to find
+    ///  * `method_name`:
+    ///    Name of the method on the `type_name` to find. The method must be static and must match the following signature:
+    ///    `public delegate int ComponentEntryPoint(IntPtr args, int sizeBytes);`
+    pub fn get_function_with_default_signature(
+        &self,
+        type_name: impl AsRef<PdCStr>,
+        method_name: &PdCStr,
+    ) -> Result<ManagedFunctionWithDefaultSignature, GetManagedFunctionError> {
+        let request = FunctionRequest {
+            assembly_path: None,
+            type_name: type_name.as_ref(),
+            method_name,
+            unmanaged_callers_only: false,
+            delegate_type_name: None,
+        };
+        let function = self.get_function_pointer_raw(&request, ptr::null())?;
+        Ok(ManagedFunction(unsafe { FnPtr::from_ptr(function) }))
+    }
+
+    /// Finds the specified type and method in this context and returns a native function pointer to that method.
+    ///
+    /// # Arguments
+    ///  * `type_name`:
+    ///    Assembly qualified type name to find
+    ///  * `method_name`:
+    ///    Name of the method on the `type_name` to find. The method must be static and must be annotated with [`UnmanagedCallersOnly`].
+    ///
+    /// [`UnmanagedCallersOnly`]: https://docs.microsoft.com/en-us/dotnet/api/system.runtime.interopservices.unmanagedcallersonlyattribute
+    pub fn get_function_with_unmanaged_callers_only<F: FnPtr + WithAbi<System>>(
+        &self,
+        type_name: impl AsRef<PdCStr>,
+        method_name: &PdCStr,
+    ) -> Result<ManagedFunction<<F as WithAbi<System>>::F>, GetManagedFunctionError> {
+        let request = FunctionRequest {
+            assembly_path: None,
+            type_name: type_name.as_ref(),
+            method_name,
+            unmanaged_callers_only: true,
+            delegate_type_name: None,
+        };
+        let function = self.get_function_pointer_raw(&request, UNMANAGED_CALLERS_ONLY_METHOD)?;
+        Ok(ManagedFunction(unsafe {
+            <<F as WithAbi<System>>::F>::from_ptr(function)
+        }))
+    }
+}
+
+impl fmt::Debug for AssemblyLoadContextHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssemblyLoadContextHandle")
+            .field("name", &self.name)
+            .field("is_collectible", &self.is_collectible)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for AssemblyLoadContextHandle {
+    fn drop(&mut self) {
+        if self.handle != 0 {
+            (self.helper.0.free_handle)(self.handle);
+        }
+    }
+}
+
+/// A weak reference to an `AssemblyLoadContext` which is being unloaded, returned by
+/// [`AssemblyLoadContextHandle::unload`].
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+pub struct UnloadingContext {
+    helper: AssemblyLoadContextHelper,
+    weak_handle: isize,
+}
+
+impl UnloadingContext {
+    /// Returns whether the context has not been collected yet, without triggering a garbage collection.
+    #[must_use]
+    pub fn is_alive(&self) -> bool {
+        (self.helper.0.is_alive)(self.weak_handle, 0) != 0
+    }
+
+    /// Triggers up to `max_attempts` garbage collections until the context has been collected and returns whether it
+    /// was collected.
+    #[must_use]
+    pub fn wait_for_collection(&self, max_attempts: u32) -> bool {
+        !self.is_alive()
+            || (0..max_attempts).any(|_| (self.helper.0.is_alive)(self.weak_handle, 1) == 0)
+    }
+}
+
+impl fmt::Debug for UnloadingContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnloadingContext")
+            .field("is_alive", &self.is_alive())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for UnloadingContext {
+    fn drop(&mut self) {
+        (self.helper.0.free_handle)(self.weak_handle);
+    }
+}
+
+/// An error returned by the managed helper while creating or loading into an `AssemblyLoadContext`.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[error("{operation} failed with {code}.")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+pub struct AssemblyLoadContextError {
+    operation: &'static str,
+    code: HResult,
+}
+
+impl AssemblyLoadContextError {
+    fn check(operation: &'static str, result: i32) -> Result<(), Self> {
+        if result < 0 {
+            Err(Self {
+                operation,
+                code: HResult::from(result),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the name of the failed helper entry point, e.g. `LoadFromPath`.
+    #[must_use]
+    pub const fn operation(&self) -> &'static str {
+        self.operation
+    }
+
+    /// Returns the status code returned by the helper, usually the `HResult` of the thrown exception.
+    #[must_use]
+    pub const fn code(&self) -> HResult {
+        self.code
+    }
+}
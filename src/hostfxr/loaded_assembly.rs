@@ -0,0 +1,152 @@
+use crate::{
+    hostfxr::{
+        DelegateLoader, FnPtr, GetManagedFunctionError, ManagedFunction,
+        ManagedFunctionWithDefaultSignature, resolution::split_type_name,
+    },
+    metadata::{AssemblyIdentity, AssemblyMetadata},
+    pdcstring::{PdCStr, PdCString},
+};
+use fn_ptr::{WithAbi, abi::System};
+use std::{borrow::Cow, ffi::OsString};
+
+/// An assembly loaded into the default load context using
+/// [`HostfxrContext::load_assembly_from_bytes`](crate::hostfxr::HostfxrContext::load_assembly_from_bytes).
+///
+/// The name of the assembly is read from its metadata once it was loaded, so functions can be resolved using type names
+/// without the `, AssemblyName` suffix. If the metadata cannot be read, the assembly stays loaded but its name is
+/// unknown and type names have to be assembly qualified.
+///
+/// # Example
+/// ```no_run
+/// # use netcorehost::{nethost, pdcstr};
+/// let hostfxr = nethost::load_hostfxr()?;
+/// let context = hostfxr.initialize_for_runtime_config(pdcstr!("Test.runtimeconfig.json"))?;
+/// let assembly = context.load_assembly_from_bytes(std::fs::read("ClassLibrary.dll")?, [])?;
+/// assert_eq!(assembly.name(), Some("ClassLibrary"));
+///
+/// let hello = assembly.get_function_with_unmanaged_callers_only::<fn() -> i32>(
+///     pdcstr!("ClassLibrary.Library"),
+///     pdcstr!("Hello"),
+/// )?;
+/// hello();
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net8_0")))]
+pub struct LoadedAssembly {
+    loader: DelegateLoader,
+    metadata: Option<AssemblyMetadata>,
+}
+
+impl LoadedAssembly {
+    /// Reads the metadata of the given image of an assembly that was already loaded by the runtime.
+    pub(crate) fn new(loader: DelegateLoader, image: &[u8]) -> Self {
+        Self {
+            loader,
+            metadata: AssemblyMetadata::parse(image).ok(),
+        }
+    }
+
+    /// Returns the simple name of the assembly, or [`None`] if it could not be read from its metadata.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.identity().map(|identity| identity.name.as_str())
+    }
+
+    /// Returns the identity of the assembly, or [`None`] if it could not be read from its metadata.
+    #[must_use]
+    pub fn identity(&self) -> Option<&AssemblyIdentity> {
+        self.metadata.as_ref()?.assembly()
+    }
+
+    /// Returns the metadata of the assembly, or [`None`] if it could not be read.
+    #[must_use]
+    pub const fn metadata(&self) -> Option<&AssemblyMetadata> {
+        self.metadata.as_ref()
+    }
+
+    /// Returns the given type name qualified with the name of this assembly, unless it already names an assembly or
+    /// the name of this assembly is unknown.
+    #[must_use]
+    pub fn qualify_type_name<'a>(&self, type_name: &'a PdCStr) -> Cow<'a, PdCStr> {
+        let Some(assembly_name) = self.name() else {
+            return Cow::Borrowed(type_name);
+        };
+        if split_type_name(&type_name.to_string_lossy()).1.is_some() {
+            return Cow::Borrowed(type_name);
+        }
+        let mut name = type_name.to_os_string();
+        name.push(OsString::from(format!(", {assembly_name}")));
+        // neither the type name nor the assembly name read from the metadata contain a nul.
+        Cow::Owned(PdCString::from_os_str(name).unwrap())
+    }
+
+    /// Finds the specified type and method in this assembly and returns a native function pointer to that method.
+    ///
+    /// # Arguments
+    ///  * `type_name`:
+    ///    Name of the type to find, e.g. `Namespace.Type`.
+    ///  * `method_name`:
+    ///    Name of the method on the `type_name` to find. The method must be static and must match the signature of `delegate_type_name`.
+    ///  * `delegate_type_name`:
+    ///    Assembly qualified delegate type name for the method signature.
+    pub fn get_function<F: FnPtr + WithAbi<System>>(
+        &self,
+        type_name: impl AsRef<PdCStr>,
+        method_name: &PdCStr,
+        delegate_type_name: impl AsRef<PdCStr>,
+    ) -> Result<ManagedFunction<<F as WithAbi<System>>::F>, GetManagedFunctionError> {
+        self.loader.get_function::<F>(
+            self.qualify_type_name(type_name.as_ref()),
+            method_name,
+            delegate_type_name,
+        )
+    }
+
+    /// Finds the specified type and method in this assembly and returns a native function pointer to that method.
+    ///
+    /// # Arguments
+    ///  * `type_name`:
+    ///    Name of the type to find, e.g. `Namespace.Type`.
+    ///  * `method_name`:
+    ///    Name of the method on the `type_name` to find. The method must be static and must match the following signature:
+    ///    `public delegate int ComponentEntryPoint(IntPtr args, int sizeBytes);`
+    pub fn get_function_with_default_signature(
+        &self,
+        type_name: impl AsRef<PdCStr>,
+        method_name: &PdCStr,
+    ) -> Result<ManagedFunctionWithDefaultSignature, GetManagedFunctionError> {
+        self.loader.get_function_with_default_signature(
+            self.qualify_type_name(type_name.as_ref()),
+            method_name,
+        )
+    }
+
+    /// Finds the specified type and method in this assembly and returns a native function pointer to that method.
+    ///
+    /// # Arguments
+    ///  * `type_name`:
+    ///    Name of the type to find, e.g. `Namespace.Type`.
+    ///  * `method_name`:
+    ///    Name of the method on the `type_name` to find. The method must be static and must be annotated with [`UnmanagedCallersOnly`].
+    ///
+    /// [`UnmanagedCallersOnly`]: https://docs.microsoft.com/en-us/dotnet/api/system.runtime.interopservices.unmanagedcallersonlyattribute
+    pub fn get_function_with_unmanaged_callers_only<F: FnPtr + WithAbi<System>>(
+        &self,
+        type_name: impl AsRef<PdCStr>,
+        method_name: &PdCStr,
+    ) -> Result<ManagedFunction<<F as WithAbi<System>>::F>, GetManagedFunctionError> {
+        self.loader.get_function_with_unmanaged_callers_only::<F>(
+            self.qualify_type_name(type_name.as_ref()),
+            method_name,
+        )
+    }
+}
+
+impl std::fmt::Debug for LoadedAssembly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoadedAssembly")
+            .field("identity", &self.identity())
+            .finish_non_exhaustive()
+    }
+}
@@ -1,6 +1,12 @@
 mod library;
 pub use library::*;
 
+mod self_contained;
+pub use self_contained::*;
+
+mod trace;
+pub use trace::*;
+
 #[cfg(feature = "netcore1_0")]
 mod library1_0;
 #[cfg(feature = "netcore1_0")]
@@ -27,6 +33,19 @@ mod library6_0;
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net6_0")))]
 pub use library6_0::*;
 
+#[cfg(feature = "netcore3_0")]
+mod error_writer;
+#[cfg(feature = "netcore3_0")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
+pub use error_writer::*;
+
+#[cfg(feature = "netcore3_0")]
+mod diagnostics;
+#[cfg(feature = "netcore3_0")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
+#[allow(unused_imports)]
+pub use diagnostics::*;
+
 #[cfg(feature = "netcore3_0")]
 mod context;
 #[cfg(feature = "netcore3_0")]
@@ -39,6 +58,42 @@ mod delegate_loader;
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
 pub use delegate_loader::*;
 
+#[cfg(feature = "netcore3_0")]
+mod resolution;
+#[cfg(feature = "netcore3_0")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
+pub use resolution::*;
+
+#[cfg(feature = "net5_0")]
+mod assembly_bytes;
+#[cfg(feature = "net5_0")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+pub use assembly_bytes::*;
+
+#[cfg(feature = "net8_0")]
+mod loaded_assembly;
+#[cfg(feature = "net8_0")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net8_0")))]
+pub use loaded_assembly::*;
+
+#[cfg(feature = "net5_0")]
+mod load_context;
+#[cfg(feature = "net5_0")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+pub use load_context::*;
+
+#[cfg(feature = "net5_0")]
+mod plugin;
+#[cfg(feature = "net5_0")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+pub use plugin::*;
+
+#[cfg(feature = "net5_0")]
+mod hot_reload;
+#[cfg(feature = "net5_0")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+pub use hot_reload::*;
+
 #[cfg(feature = "netcore3_0")]
 mod runtime_property;
 #[cfg(feature = "netcore3_0")]
@@ -51,3 +106,24 @@ mod managed_function;
 #[cfg(feature = "netcore3_0")]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
 pub use managed_function::*;
+
+#[cfg(feature = "netcore3_0")]
+mod api;
+#[cfg(feature = "netcore3_0")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
+pub use api::*;
+
+#[cfg(feature = "netcore3_0")]
+mod isolated;
+#[cfg(feature = "netcore3_0")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
+pub use isolated::*;
+
+#[cfg(all(feature = "netcore3_0", feature = "fake"))]
+mod fake;
+#[cfg(all(feature = "netcore3_0", feature = "fake"))]
+#[cfg_attr(
+    feature = "doc-cfg",
+    doc(cfg(all(feature = "netcore3_0", feature = "fake")))
+)]
+pub use fake::*;
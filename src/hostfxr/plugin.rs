@@ -0,0 +1,225 @@
+use crate::{
+    bindings::hostfxr::component_entry_point_fn,
+    hostfxr::{
+        AssemblyLoadContextError, AssemblyLoadContextHandle, AssemblyLoadContextHelper, FnPtr,
+        GetManagedFunctionError, ManagedFnPtr, ManagedFunction,
+    },
+    pdcstring::{PdCStr, PdCString},
+};
+use fn_ptr::{SafeFnPtr, UnsafeFnPtr, WithAbi, abi::System};
+use std::{fmt, marker::PhantomData, path::Path};
+
+/// A managed plugin loaded into its own collectible `AssemblyLoadContext`.
+///
+/// Functions resolved from the plugin borrow the handle and can only be called through it, so the plugin cannot be
+/// unloaded while they are in use.
+/// Dropping the handle unloads the plugin and reports through `log` and `tracing` whether its context was collected;
+/// use [`unload`](PluginHandle::unload) to get the result instead.
+///
+/// # Example
+/// ```no_run
+/// # use netcorehost::{hostfxr::{AssemblyLoadContextHelper, PluginHandle}, nethost, pdcstr};
+/// # let hostfxr = nethost::load_hostfxr()?;
+/// # let context = hostfxr.initialize_for_runtime_config(pdcstr!("Test.runtimeconfig.json"))?;
+/// # let helper = AssemblyLoadContextHelper::load(&context.get_delegate_loader()?, pdcstr!("NetCoreHost.Helper.dll"))?;
+/// let plugin = PluginHandle::load(&helper, pdcstr!("Plugin.dll"))?;
+/// let run = plugin.get_function_with_unmanaged_callers_only::<fn() -> i32>(
+///     pdcstr!("Plugin.Entry, Plugin"),
+///     pdcstr!("Run"),
+/// )?;
+/// run.call(());
+///
+/// assert!(plugin.unload()?);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "net5_0")))]
+pub struct PluginHandle {
+    context: Option<AssemblyLoadContextHandle>,
+    assembly_path: PdCString,
+}
+
+impl PluginHandle {
+    /// The number of garbage collections triggered while waiting for an unloaded plugin to be collected.
+    pub const COLLECTION_ATTEMPTS: u32 = 10;
+
+    /// Creates a collectible context named after the assembly file and loads the assembly at the given path into it.
+    pub fn load(
+        helper: &AssemblyLoadContextHelper,
+        assembly_path: impl AsRef<PdCStr>,
+    ) -> Result<Self, AssemblyLoadContextError> {
+        let assembly_path = assembly_path.as_ref();
+        let path = assembly_path.to_os_string();
+        let name = Path::new(&path)
+            .file_stem()
+            .and_then(|name| PdCString::from_os_str(name).ok())
+            .unwrap_or_else(|| assembly_path.to_owned());
+
+        let context = helper.create_context(name, true)?;
+        context.load_assembly_from_path(assembly_path)?;
+        Ok(Self {
+            context: Some(context),
+            assembly_path: assembly_path.to_owned(),
+        })
+    }
+
+    /// Returns the path of the plugin assembly.
+    #[must_use]
+    pub fn assembly_path(&self) -> &PdCStr {
+        &self.assembly_path
+    }
+
+    /// Returns the name of the context the plugin was loaded into.
+    #[must_use]
+    pub fn context_name(&self) -> &PdCStr {
+        self.context().name()
+    }
+
+    // the context is not exposed, as the functions it resolves are not bound to the lifetime of the plugin.
+    fn context(&self) -> &AssemblyLoadContextHandle {
+        self.context.as_ref().unwrap()
+    }
+
+    /// Finds the specified type and method in the plugin and returns a native function pointer to that method.
+    ///
+    /// See [`AssemblyLoadContextHandle::get_function`].
+    pub fn get_function<F: FnPtr + WithAbi<System>>(
+        &self,
+        type_name: impl AsRef<PdCStr>,
+        method_name: &PdCStr,
+        delegate_type_name: impl AsRef<PdCStr>,
+    ) -> Result<PluginFunction<'_, <F as WithAbi<System>>::F>, GetManagedFunctionError> {
+        self.context()
+            .get_function::<F>(type_name, method_name, delegate_type_name)
+            .map(PluginFunction::new)
+    }
+
+    /// Finds the specified type and method in the plugin and returns a native function pointer to that method.
+    ///
+    /// See [`AssemblyLoadContextHandle::get_function_with_default_signature`].
+    pub fn get_function_with_default_signature(
+        &self,
+        type_name: impl AsRef<PdCStr>,
+        method_name: &PdCStr,
+    ) -> Result<PluginFunction<'_, component_entry_point_fn>, GetManagedFunctionError> {
+        self.context()
+            .get_function_with_default_signature(type_name, method_name)
+            .map(PluginFunction::new)
+    }
+
+    /// Finds the specified type and method in the plugin and returns a native function pointer to that method.
+    ///
+    /// See [`AssemblyLoadContextHandle::get_function_with_unmanaged_callers_only`].
+    pub fn get_function_with_unmanaged_callers_only<F: FnPtr + WithAbi<System>>(
+        &self,
+        type_name: impl AsRef<PdCStr>,
+        method_name: &PdCStr,
+    ) -> Result<PluginFunction<'_, <F as WithAbi<System>>::F>, GetManagedFunctionError> {
+        self.context()
+            .get_function_with_unmanaged_callers_only::<F>(type_name, method_name)
+            .map(PluginFunction::new)
+    }
+
+    /// Unloads the plugin and returns whether its context was collected after [`COLLECTION_ATTEMPTS`] garbage
+    /// collections.
+    ///
+    /// A context that is not collected is usually still referenced, e.g. by a running thread, a static field of
+    /// another context or a delegate stored outside of the plugin.
+    ///
+    /// [`COLLECTION_ATTEMPTS`]: PluginHandle::COLLECTION_ATTEMPTS
+    pub fn unload(mut self) -> Result<bool, AssemblyLoadContextError> {
+        let context = self.context.take().unwrap();
+        let unloading = context.unload()?;
+        Ok(unloading.wait_for_collection(Self::COLLECTION_ATTEMPTS))
+    }
+}
+
+impl fmt::Debug for PluginHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginHandle")
+            .field("assembly_path", &self.assembly_path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for PluginHandle {
+    fn drop(&mut self) {
+        let Some(context) = self.context.take() else {
+            return;
+        };
+        let result = context
+            .unload()
+            .map(|unloading| unloading.wait_for_collection(Self::COLLECTION_ATTEMPTS));
+
+        #[cfg(any(feature = "log", feature = "tracing"))]
+        {
+            let assembly_path = self.assembly_path.to_string_lossy();
+            match result {
+                Ok(true) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(target: "netcorehost::hostfxr", %assembly_path, "unloaded plugin");
+                    #[cfg(feature = "log")]
+                    log::debug!(target: "netcorehost::hostfxr", "unloaded plugin {assembly_path}");
+                }
+                Ok(false) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(target: "netcorehost::hostfxr", %assembly_path, "plugin was unloaded but not collected");
+                    #[cfg(feature = "log")]
+                    log::warn!(target: "netcorehost::hostfxr", "plugin {assembly_path} was unloaded but not collected");
+                }
+                Err(err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(target: "netcorehost::hostfxr", %assembly_path, %err, "failed to unload plugin");
+                    #[cfg(feature = "log")]
+                    log::warn!(target: "netcorehost::hostfxr", "failed to unload plugin {assembly_path}: {err}");
+                }
+            }
+        }
+        #[cfg(not(any(feature = "log", feature = "tracing")))]
+        let _ = result;
+    }
+}
+
+/// A function resolved from a [`PluginHandle`] or taken from a [`PluginFunctionTable`], which cannot outlive the
+/// context it was resolved from.
+///
+/// The function pointer itself is not handed out, as it could be copied and called after the context was unloaded.
+/// Instead, the function is called with its arguments as a tuple using [`call`](PluginFunction::call) or
+/// [`call_unsafe`](PluginFunction::call_unsafe) for `unsafe` function pointer types.
+///
+/// [`PluginFunctionTable`]: crate::hostfxr::PluginFunctionTable
+pub struct PluginFunction<'plugin, F: ManagedFnPtr> {
+    function: ManagedFunction<F>,
+    plugin: PhantomData<&'plugin ()>,
+}
+
+impl<F: ManagedFnPtr> PluginFunction<'_, F> {
+    pub(crate) const fn new(function: ManagedFunction<F>) -> Self {
+        Self {
+            function,
+            plugin: PhantomData,
+        }
+    }
+}
+
+impl<F: ManagedFnPtr> fmt::Debug for PluginFunction<'_, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.function.fmt(f)
+    }
+}
+
+impl<F: ManagedFnPtr + SafeFnPtr> PluginFunction<'_, F> {
+    /// Calls the function with the given arguments.
+    pub fn call(&self, args: F::Args) -> F::Output {
+        self.function.0.invoke(args)
+    }
+}
+
+impl<F: ManagedFnPtr + UnsafeFnPtr> PluginFunction<'_, F> {
+    /// Calls the function with the given arguments.
+    ///
+    /// # Safety
+    /// The caller has to uphold the requirements of the called function.
+    pub unsafe fn call_unsafe(&self, args: F::Args) -> F::Output {
+        unsafe { self.function.0.invoke(args) }
+    }
+}
@@ -0,0 +1,251 @@
+use crate::{
+    error::HResult,
+    metadata::AssemblyMetadata,
+    pdcstring::{PdCStr, PdCString},
+};
+use std::{fmt, path::PathBuf};
+
+/// The reason a managed function could not be resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum ResolutionErrorKind {
+    /// The assembly to load does not exist.
+    AssemblyNotFound,
+    /// The assembly to load exists but is not a valid .NET assembly.
+    InvalidAssembly,
+    /// The assembly named in the assembly qualified type name could not be found.
+    TypeAssemblyNotFound,
+    /// The type does not exist in its assembly.
+    TypeNotFound,
+    /// The type exists but does not define a method with the given name.
+    MethodNotFound,
+    /// The method exists but does not match the requested delegate type.
+    IncompatibleSignature,
+    /// The method is overloaded and the overload to use could not be determined, e.g. because multiple overloads are
+    /// annotated with [`UnmanagedCallersOnly`](https://docs.microsoft.com/en-us/dotnet/api/system.runtime.interopservices.unmanagedcallersonlyattribute).
+    AmbiguousMethod,
+    /// The method exists but the requested delegate type could not be found.
+    DelegateTypeNotFound,
+    /// The method exists but is not annotated with
+    /// [`UnmanagedCallersOnly`](https://docs.microsoft.com/en-us/dotnet/api/system.runtime.interopservices.unmanagedcallersonlyattribute).
+    MethodNotUnmanagedCallersOnly,
+    /// The type or method could not be found or it has an incompatible signature, but the metadata of its assembly
+    /// could not be inspected to tell which.
+    TypeOrMethodNotFound,
+    /// Some other error occured, e.g. an exception was thrown by a static constructor.
+    Other,
+}
+
+impl ResolutionErrorKind {
+    const fn description(self) -> &'static str {
+        match self {
+            Self::AssemblyNotFound => "the assembly does not exist",
+            Self::InvalidAssembly => "the assembly is not a valid .NET assembly",
+            Self::TypeAssemblyNotFound => "the assembly of the type could not be found",
+            Self::TypeNotFound => "the type does not exist in its assembly",
+            Self::MethodNotFound => "the type does not define a method with this name",
+            Self::IncompatibleSignature => "the method does not match the delegate type",
+            Self::AmbiguousMethod => {
+                "the method is overloaded and the overload to use is ambiguous"
+            }
+            Self::DelegateTypeNotFound => "the delegate type could not be found",
+            Self::MethodNotUnmanagedCallersOnly => {
+                "the method is not annotated with UnmanagedCallersOnly"
+            }
+            Self::TypeOrMethodNotFound => {
+                "the type or method could not be found or it has an incompatible signature"
+            }
+            Self::Other => "the runtime returned an error",
+        }
+    }
+}
+
+impl fmt::Display for ResolutionErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+/// An error returned when a managed function could not be resolved, together with the names that were looked up.
+///
+/// The runtime uses the same status codes for different failures (e.g. `COR_E_INVALIDOPERATION` for both an invalid
+/// assembly path and a method that is not annotated with `UnmanagedCallersOnly`), so the [`kind`] is determined by
+/// inspecting the metadata of the involved assemblies after the lookup failed.
+///
+/// [`kind`]: FunctionResolutionError::kind
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
+pub struct FunctionResolutionError {
+    kind: ResolutionErrorKind,
+    code: Option<HResult>,
+    assembly_path: Option<PdCString>,
+    type_name: PdCString,
+    method_name: PdCString,
+}
+
+impl FunctionResolutionError {
+    /// Returns the reason the function could not be resolved.
+    #[must_use]
+    pub const fn kind(&self) -> ResolutionErrorKind {
+        self.kind
+    }
+
+    /// Returns the status code returned by the runtime or [`None`] if the lookup failed before calling into it.
+    #[must_use]
+    pub const fn code(&self) -> Option<HResult> {
+        self.code
+    }
+
+    /// Returns the path of the assembly that was loaded for the lookup, if any.
+    #[must_use]
+    pub fn assembly_path(&self) -> Option<&PdCStr> {
+        self.assembly_path.as_deref()
+    }
+
+    /// Returns the assembly qualified name of the type that was looked up.
+    #[must_use]
+    pub fn type_name(&self) -> &PdCStr {
+        &self.type_name
+    }
+
+    /// Returns the name of the method that was looked up.
+    #[must_use]
+    pub fn method_name(&self) -> &PdCStr {
+        &self.method_name
+    }
+}
+
+impl fmt::Display for FunctionResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to resolve method '{}' of type '{}'",
+            self.method_name.to_string_lossy(),
+            self.type_name.to_string_lossy()
+        )?;
+        if let Some(assembly_path) = &self.assembly_path {
+            write!(f, " from '{}'", assembly_path.to_string_lossy())?;
+        }
+        write!(f, ": {}", self.kind)?;
+        if let Some(code) = self.code {
+            write!(f, " ({code})")?;
+        }
+        f.write_str(".")
+    }
+}
+
+impl std::error::Error for FunctionResolutionError {}
+
+/// The names passed to a function lookup, used to build a [`FunctionResolutionError`] if it fails.
+pub(crate) struct FunctionRequest<'a> {
+    pub(crate) assembly_path: Option<&'a PdCStr>,
+    pub(crate) type_name: &'a PdCStr,
+    pub(crate) method_name: &'a PdCStr,
+    pub(crate) unmanaged_callers_only: bool,
+    pub(crate) delegate_type_name: Option<&'a PdCStr>,
+}
+
+impl FunctionRequest<'_> {
+    pub(crate) fn error(
+        &self,
+        kind: ResolutionErrorKind,
+        code: Option<HResult>,
+    ) -> FunctionResolutionError {
+        FunctionResolutionError {
+            kind,
+            code,
+            assembly_path: self.assembly_path.map(ToOwned::to_owned),
+            type_name: self.type_name.to_owned(),
+            method_name: self.method_name.to_owned(),
+        }
+    }
+
+    /// Determines why the lookup failed with the given status code.
+    pub(crate) fn diagnose(&self, code: HResult) -> FunctionResolutionError {
+        let kind = match code {
+            // the assembly to load was checked to exist, so this refers to the assembly of the type.
+            HResult::COR_E_FILENOTFOUND => ResolutionErrorKind::TypeAssemblyNotFound,
+            HResult::COR_E_BADIMAGEFORMAT => ResolutionErrorKind::InvalidAssembly,
+            HResult::COR_E_AMBIGUOUSMATCH => ResolutionErrorKind::AmbiguousMethod,
+            HResult::COR_E_INVALIDOPERATION
+            | HResult::COR_E_TYPELOAD
+            | HResult::COR_E_MISSINGMETHOD
+            | HResult::COR_E_ARGUMENT => self.inspect(code),
+            _ => ResolutionErrorKind::Other,
+        };
+        self.error(kind, Some(code))
+    }
+
+    fn inspect(&self, code: HResult) -> ResolutionErrorKind {
+        let assembly_path = self
+            .assembly_path
+            .map(|path| PathBuf::from(path.to_os_string()));
+        let assembly = match assembly_path.as_ref().map(AssemblyMetadata::read) {
+            Some(Ok(metadata)) => Some(metadata),
+            Some(Err(_)) => return ResolutionErrorKind::InvalidAssembly,
+            None => None,
+        };
+
+        let type_name = self.type_name.to_string_lossy();
+        let (type_name, assembly_name) = split_type_name(&type_name);
+        let metadata = match (assembly, assembly_name) {
+            (Some(assembly), Some(name))
+                if assembly
+                    .assembly()
+                    .is_some_and(|identity| identity.name.eq_ignore_ascii_case(name)) =>
+            {
+                Some(assembly)
+            }
+            // the type may be defined in a dependency next to the loaded assembly.
+            (_, Some(name)) => assembly_path
+                .as_ref()
+                .and_then(|path| path.parent())
+                .and_then(|dir| AssemblyMetadata::read(dir.join(format!("{name}.dll"))).ok()),
+            (_, None) => None,
+        };
+
+        let Some(metadata) = metadata else {
+            return match code {
+                HResult::COR_E_INVALIDOPERATION if self.unmanaged_callers_only => {
+                    ResolutionErrorKind::MethodNotUnmanagedCallersOnly
+                }
+                HResult::COR_E_INVALIDOPERATION => ResolutionErrorKind::InvalidAssembly,
+                HResult::COR_E_TYPELOAD => ResolutionErrorKind::TypeNotFound,
+                HResult::COR_E_MISSINGMETHOD => ResolutionErrorKind::MethodNotFound,
+                _ => ResolutionErrorKind::TypeOrMethodNotFound,
+            };
+        };
+        let Some(ty) = metadata.find_type(type_name) else {
+            return ResolutionErrorKind::TypeNotFound;
+        };
+        let method_name = self.method_name.to_string_lossy();
+        let mut methods = ty.methods_named(&method_name).peekable();
+        if methods.peek().is_none() {
+            ResolutionErrorKind::MethodNotFound
+        } else if self.unmanaged_callers_only && !methods.any(|m| m.is_unmanaged_callers_only()) {
+            ResolutionErrorKind::MethodNotUnmanagedCallersOnly
+        } else if code == HResult::COR_E_TYPELOAD && self.delegate_type_name.is_some() {
+            // the type was found, so the type that failed to load is the delegate type.
+            ResolutionErrorKind::DelegateTypeNotFound
+        } else {
+            ResolutionErrorKind::IncompatibleSignature
+        }
+    }
+}
+
+/// Splits an assembly qualified type name into the type name and the simple name of the assembly.
+pub(crate) fn split_type_name(name: &str) -> (&str, Option<&str>) {
+    let mut depth = 0usize;
+    for (i, c) in name.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                let assembly = name[i + 1..].split(',').next().unwrap_or_default().trim();
+                return (name[..i].trim(), (!assembly.is_empty()).then_some(assembly));
+            }
+            _ => {}
+        }
+    }
+    (name.trim(), None)
+}
@@ -22,7 +22,9 @@ impl<I> HostfxrContext<I> {
                 value.as_mut_ptr(),
             )
         }
-        .unwrap();
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_get_runtime_property_value",
+        })?;
         HostingResult::from(result).into_result()?;
 
         Ok(unsafe { PdCStr::from_str_ptr(value.assume_init()) })
@@ -41,7 +43,9 @@ impl<I> HostfxrContext<I> {
                 value.as_ref().as_ptr(),
             )
         }
-        .unwrap();
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_set_runtime_property_value",
+        })?;
         HostingResult::from(result).into_result().map(|_| ())
     }
 
@@ -57,7 +61,9 @@ impl<I> HostfxrContext<I> {
                 ptr::null(),
             )
         }
-        .unwrap();
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_set_runtime_property_value",
+        })?;
         HostingResult::from(result).into_result().map(|_| ())
     }
 
@@ -73,7 +79,9 @@ impl<I> HostfxrContext<I> {
                 ptr::null_mut(),
             )
         }
-        .unwrap();
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_get_runtime_properties",
+        })?;
 
         // ignore buffer too small error as the first call is only to get the required buffer size.
         match HostingResult::from(result).into_result() {
@@ -93,7 +101,9 @@ impl<I> HostfxrContext<I> {
                 values.as_mut_ptr(),
             )
         }
-        .unwrap();
+        .ok_or(HostingError::MissingExport {
+            name: "hostfxr_get_runtime_properties",
+        })?;
         HostingResult::from(result).into_result()?;
 
         unsafe { keys.set_len(count) };
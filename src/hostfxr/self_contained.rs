@@ -0,0 +1,497 @@
+use crate::{
+    arch::{
+        ArchitectureMismatchError, CheckArchitectureError, UnreadableLibraryError,
+        check_library_architecture,
+    },
+    dlopen2::wrapper::Container,
+    hostfxr::Hostfxr,
+    pdcstring::PdCString,
+};
+use std::{
+    env::consts::{DLL_PREFIX, DLL_SUFFIX, EXE_SUFFIX},
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use thiserror::Error;
+
+#[cfg(feature = "netcore3_0")]
+use crate::{
+    bindings::hostfxr::hostfxr_initialize_parameters,
+    error::{HostingCallError, HostingError},
+    hostfxr::{HostfxrContext, InitializedForCommandLine},
+    pdcstring::PdCStr,
+};
+#[cfg(feature = "netcore3_0")]
+use std::{iter, mem};
+
+use super::SharedHostfxrLibrary;
+
+const RUNTIME_CONFIG_SUFFIX: &str = ".runtimeconfig.json";
+
+/// The on-disk layout of an app-local self-contained .NET deployment.
+///
+/// A self-contained deployment ships the hosting components (`hostfxr`, `hostpolicy`) and the runtime (`coreclr`)
+/// next to the application instead of relying on a global .NET installation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfContainedApp {
+    app_dir: PathBuf,
+    app_name: String,
+    hostfxr_path: PathBuf,
+    hostpolicy_path: PathBuf,
+    coreclr_path: PathBuf,
+}
+
+impl SelfContainedApp {
+    /// Locates the self-contained application in the given directory.
+    ///
+    /// The application is identified by its `.runtimeconfig.json`, so the directory has to contain exactly one.
+    pub fn locate(app_dir: impl AsRef<Path>) -> Result<Self, SelfContainedLayoutError> {
+        let app_dir = app_dir.as_ref();
+        if !app_dir.is_dir() {
+            return Err(SelfContainedLayoutError::DirectoryNotFound(
+                app_dir.to_path_buf(),
+            ));
+        }
+
+        let mut app_names = fs::read_dir(app_dir)?
+            .filter_map(|entry| {
+                let file_name = entry.ok()?.file_name();
+                let file_name = file_name.to_str()?;
+                file_name
+                    .strip_suffix(RUNTIME_CONFIG_SUFFIX)
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_owned)
+            })
+            .collect::<Vec<_>>();
+
+        match app_names.len() {
+            0 => Err(SelfContainedLayoutError::MissingRuntimeConfig(
+                app_dir.to_path_buf(),
+            )),
+            1 => Self::locate_with_name(app_dir, app_names.pop().unwrap()),
+            _ => {
+                app_names.sort();
+                Err(SelfContainedLayoutError::AmbiguousApp(app_names))
+            }
+        }
+    }
+
+    /// Locates the self-contained application with the given name (the file name of the main assembly without
+    /// its extension) in the given directory.
+    pub fn locate_with_name(
+        app_dir: impl AsRef<Path>,
+        app_name: impl Into<String>,
+    ) -> Result<Self, SelfContainedLayoutError> {
+        let app_dir = app_dir.as_ref();
+        if !app_dir.is_dir() {
+            return Err(SelfContainedLayoutError::DirectoryNotFound(
+                app_dir.to_path_buf(),
+            ));
+        }
+
+        let app = Self {
+            app_dir: app_dir.to_path_buf(),
+            hostfxr_path: app_dir.join(library_file_name("hostfxr")),
+            hostpolicy_path: app_dir.join(library_file_name("hostpolicy")),
+            coreclr_path: app_dir.join(library_file_name("coreclr")),
+            app_name: app_name.into(),
+        };
+        app.validate()?;
+        Ok(app)
+    }
+
+    fn validate(&self) -> Result<(), SelfContainedLayoutError> {
+        let runtime_config_path = self.runtime_config_path();
+        if !runtime_config_path.is_file() {
+            return Err(SelfContainedLayoutError::MissingRuntimeConfig(
+                runtime_config_path,
+            ));
+        }
+
+        // Framework-dependent apps list their frameworks under "framework(s)", while self-contained apps
+        // list the frameworks they were published with under "includedFrameworks".
+        let runtime_config = fs::read_to_string(&runtime_config_path)?;
+        if !JsonReader::new(&runtime_config).contains(&["runtimeOptions", "includedFrameworks"]) {
+            return Err(SelfContainedLayoutError::NotSelfContained(
+                runtime_config_path,
+            ));
+        }
+
+        for (component, path) in [
+            (SelfContainedComponent::Hostfxr, &self.hostfxr_path),
+            (SelfContainedComponent::Hostpolicy, &self.hostpolicy_path),
+            (SelfContainedComponent::CoreClr, &self.coreclr_path),
+        ] {
+            if !path.is_file() {
+                return Err(SelfContainedLayoutError::MissingComponent {
+                    component,
+                    path: path.clone(),
+                });
+            }
+        }
+
+        let app_path = self.app_path();
+        if !app_path.is_file() {
+            return Err(SelfContainedLayoutError::MissingComponent {
+                component: SelfContainedComponent::App,
+                path: app_path,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the directory containing the application.
+    /// For self-contained apps this directory is also the dotnet root.
+    #[must_use]
+    pub fn app_dir(&self) -> &Path {
+        &self.app_dir
+    }
+
+    /// Returns the name of the application.
+    #[must_use]
+    pub fn app_name(&self) -> &str {
+        &self.app_name
+    }
+
+    /// Returns the path to the main assembly of the application.
+    #[must_use]
+    pub fn app_path(&self) -> PathBuf {
+        self.app_file(".dll")
+    }
+
+    /// Returns the path to the native host (apphost) of the application.
+    ///
+    /// # Note
+    /// The apphost does not have to exist, it is only passed to the runtime as the path of the executable.
+    #[must_use]
+    pub fn host_path(&self) -> PathBuf {
+        self.app_file(EXE_SUFFIX)
+    }
+
+    /// Returns the path to the `.runtimeconfig.json` of the application.
+    #[must_use]
+    pub fn runtime_config_path(&self) -> PathBuf {
+        self.app_file(RUNTIME_CONFIG_SUFFIX)
+    }
+
+    /// Returns the path to the bundled hostfxr library.
+    #[must_use]
+    pub fn hostfxr_path(&self) -> &Path {
+        &self.hostfxr_path
+    }
+
+    /// Returns the path to the bundled hostpolicy library.
+    #[must_use]
+    pub fn hostpolicy_path(&self) -> &Path {
+        &self.hostpolicy_path
+    }
+
+    /// Returns the path to the bundled coreclr library.
+    #[must_use]
+    pub fn coreclr_path(&self) -> &Path {
+        &self.coreclr_path
+    }
+
+    fn app_file(&self, suffix: &str) -> PathBuf {
+        let mut file_name = OsString::from(&self.app_name);
+        file_name.push(suffix);
+        self.app_dir.join(file_name)
+    }
+}
+
+fn library_file_name(name: &str) -> String {
+    format!("{DLL_PREFIX}{name}{DLL_SUFFIX}")
+}
+
+impl Hostfxr {
+    /// Locates the self-contained application in the given directory, validates its layout and loads its
+    /// bundled hostfxr library.
+    ///
+    /// Contexts initialized through the returned [`Hostfxr`] without explicit initialization parameters use
+    /// the apphost of the application as the host path and the application directory as the dotnet root.
+    pub fn load_self_contained(app_dir: impl AsRef<Path>) -> Result<Self, LoadSelfContainedError> {
+        let app = SelfContainedApp::locate(app_dir)?;
+        Self::load_self_contained_app(app)
+    }
+
+    /// Loads the bundled hostfxr library of the given self-contained application.
+    ///
+    /// Contexts initialized through the returned [`Hostfxr`] without explicit initialization parameters use
+    /// the apphost of the application as the host path and the application directory as the dotnet root.
+    pub fn load_self_contained_app(app: SelfContainedApp) -> Result<Self, LoadSelfContainedError> {
+        check_library_architecture(app.hostfxr_path())?;
+        let lib = SharedHostfxrLibrary::new(unsafe { Container::load(app.hostfxr_path()) }?);
+        let dotnet_exe = PdCString::from_os_str(app.host_path())?;
+
+        Ok(Self {
+            lib,
+            dotnet_exe,
+            self_contained: Some(Arc::new(app)),
+            version: None,
+        })
+    }
+
+    /// Returns the self-contained application this hostfxr was loaded from, if it was loaded using
+    /// [`Hostfxr::load_self_contained`] or [`Hostfxr::load_self_contained_app`].
+    #[must_use]
+    pub fn self_contained_app(&self) -> Option<&SelfContainedApp> {
+        self.self_contained.as_deref()
+    }
+}
+
+#[cfg(feature = "netcore3_0")]
+impl Hostfxr {
+    /// Initializes the hosting components for running the self-contained application this hostfxr was loaded from.
+    ///
+    /// Returns [`HostingError::InvalidArgFailure`] if this hostfxr was not loaded using [`Hostfxr::load_self_contained`]
+    /// or [`Hostfxr::load_self_contained_app`].
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
+    pub fn initialize_self_contained_app(
+        &self,
+    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingCallError> {
+        self.initialize_self_contained_app_with_args(iter::empty::<&PdCStr>())
+    }
+
+    /// Initializes the hosting components for running the self-contained application this hostfxr was loaded from
+    /// with the given command line arguments.
+    ///
+    /// Returns [`HostingError::InvalidArgFailure`] if this hostfxr was not loaded using [`Hostfxr::load_self_contained`]
+    /// or [`Hostfxr::load_self_contained_app`].
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "netcore3_0")))]
+    pub fn initialize_self_contained_app_with_args(
+        &self,
+        args: impl Iterator<Item = impl AsRef<PdCStr>>,
+    ) -> Result<HostfxrContext<InitializedForCommandLine>, HostingCallError> {
+        let invalid_arg = || {
+            HostingCallError::new(
+                HostingError::InvalidArgFailure,
+                "initialize_self_contained_app",
+            )
+            .with_hostfxr_version(self.version.clone())
+        };
+        let app = self.self_contained_app().ok_or_else(invalid_arg)?;
+        let app_path = PdCString::from_os_str(app.app_path()).map_err(|_| invalid_arg())?;
+        self.initialize_for_dotnet_command_line_with_args(app_path, args)
+    }
+
+    /// Runs the given closure with the initialization parameters to use if none were explicitly specified.
+    /// For hostfxr libraries loaded from a self-contained app these point to the app, otherwise no parameters are used.
+    pub(crate) fn with_default_initialize_parameters<R>(
+        &self,
+        f: impl FnOnce(*const hostfxr_initialize_parameters) -> R,
+    ) -> R {
+        let Some(app) = self.self_contained_app() else {
+            return f(std::ptr::null());
+        };
+        let Ok(dotnet_root) = PdCString::from_os_str(app.app_dir()) else {
+            return f(std::ptr::null());
+        };
+
+        let parameters = hostfxr_initialize_parameters {
+            size: mem::size_of::<hostfxr_initialize_parameters>(),
+            host_path: self.dotnet_exe.as_ptr(),
+            dotnet_root: dotnet_root.as_ptr(),
+        };
+        f(&raw const parameters)
+    }
+}
+
+/// A component of a self-contained deployment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SelfContainedComponent {
+    /// The hostfxr library.
+    Hostfxr,
+    /// The hostpolicy library.
+    Hostpolicy,
+    /// The coreclr library.
+    CoreClr,
+    /// The main assembly of the application.
+    App,
+}
+
+/// Enum for errors that can occur while validating the layout of a self-contained deployment.
+#[derive(Debug, Error)]
+pub enum SelfContainedLayoutError {
+    /// The application directory does not exist.
+    #[error("The application directory {} does not exist.", .0.display())]
+    DirectoryNotFound(PathBuf),
+    /// No `.runtimeconfig.json` could be found for the application.
+    #[error("No runtime config could be found at {}.", .0.display())]
+    MissingRuntimeConfig(PathBuf),
+    /// The application directory contains multiple applications.
+    #[error("The application directory contains multiple applications: {}.", .0.join(", "))]
+    AmbiguousApp(Vec<String>),
+    /// The `.runtimeconfig.json` of the application describes a framework-dependent application.
+    #[error("The application described by {} is not self-contained.", .0.display())]
+    NotSelfContained(PathBuf),
+    /// A required component of the deployment is missing.
+    #[error("The {component:?} component of the deployment is missing at {}.", .path.display())]
+    MissingComponent {
+        /// The missing component.
+        component: SelfContainedComponent,
+        /// The path the component was expected at.
+        path: PathBuf,
+    },
+    /// An io error occured while inspecting the deployment.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Enum for errors that can occur while loading the hostfxr library of a self-contained deployment.
+#[derive(Debug, Error)]
+pub enum LoadSelfContainedError {
+    /// The layout of the deployment is invalid.
+    #[error(transparent)]
+    Layout(#[from] SelfContainedLayoutError),
+    /// The path to the deployment contains a nul character.
+    #[error(transparent)]
+    InvalidPath(#[from] crate::pdcstring::ContainsNul),
+    /// The hostfxr library of the deployment was built for a different architecture than the current process.
+    #[error(transparent)]
+    ArchitectureMismatch(#[from] ArchitectureMismatchError),
+    /// The header of the hostfxr library of the deployment could not be read to determine its architecture.
+    #[error(transparent)]
+    UnreadableLibrary(#[from] UnreadableLibraryError),
+    /// An error occured while loading the hostfxr library.
+    #[error(transparent)]
+    DlOpen(#[from] crate::dlopen2::Error),
+}
+
+impl From<CheckArchitectureError> for LoadSelfContainedError {
+    fn from(err: CheckArchitectureError) -> Self {
+        match err {
+            CheckArchitectureError::ArchitectureMismatch(err) => Self::ArchitectureMismatch(err),
+            CheckArchitectureError::UnreadableLibrary(err) => Self::UnreadableLibrary(err),
+        }
+    }
+}
+
+/// A minimal reader for the parts of a runtime config needed to tell self-contained apps apart.
+///
+/// Like the parser used by the hosting components it accepts comments and trailing commas.
+struct JsonReader<'a> {
+    json: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> JsonReader<'a> {
+    const fn new(json: &'a str) -> Self {
+        Self {
+            json: json.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    /// Returns whether the document contains a value at the given path of object keys.
+    fn contains(mut self, path: &[&str]) -> bool {
+        self.find(path).unwrap_or(false)
+    }
+
+    fn find(&mut self, path: &[&str]) -> Option<bool> {
+        let Some((key, rest)) = path.split_first() else {
+            return Some(true);
+        };
+        if !self.eat(b'{') {
+            return Some(false);
+        }
+        loop {
+            if self.eat(b'}') {
+                return Some(false);
+            }
+            let member = self.string()?;
+            self.expect(b':')?;
+            if member == key.as_bytes() {
+                return self.find(rest);
+            }
+            self.skip_value()?;
+            if !self.eat(b',') {
+                self.expect(b'}')?;
+                return Some(false);
+            }
+        }
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        self.skip_whitespace();
+        match self.json.get(self.pos)? {
+            b'"' => self.string().map(drop),
+            open @ (b'{' | b'[') => {
+                let close = if *open == b'{' { b'}' } else { b']' };
+                self.pos += 1;
+                loop {
+                    if self.eat(close) {
+                        return Some(());
+                    }
+                    if close == b'}' {
+                        self.string()?;
+                        self.expect(b':')?;
+                    }
+                    self.skip_value()?;
+                    if !self.eat(b',') {
+                        return self.expect(close);
+                    }
+                }
+            }
+            _ => {
+                let len = self.json[self.pos..]
+                    .iter()
+                    .position(|c| matches!(c, b',' | b'}' | b']' | b'/') || c.is_ascii_whitespace())
+                    .unwrap_or(self.json.len() - self.pos);
+                self.pos += len;
+                (len > 0).then_some(())
+            }
+        }
+    }
+
+    /// Reads a string and returns its raw contents, escape sequences are not decoded.
+    fn string(&mut self) -> Option<&'a [u8]> {
+        self.expect(b'"')?;
+        let start = self.pos;
+        while let Some(c) = self.json.get(self.pos) {
+            match c {
+                b'"' => {
+                    self.pos += 1;
+                    return Some(&self.json[start..self.pos - 1]);
+                }
+                b'\\' => self.pos += 2,
+                _ => self.pos += 1,
+            }
+        }
+        None
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.json.get(self.pos) == Some(&c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, c: u8) -> Option<()> {
+        self.eat(c).then_some(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = &self.json[self.pos.min(self.json.len())..];
+            if rest.first().is_some_and(u8::is_ascii_whitespace) {
+                self.pos += 1;
+            } else if rest.starts_with(b"//") {
+                self.pos += rest.iter().position(|c| *c == b'\n').unwrap_or(rest.len());
+            } else if rest.starts_with(b"/*") {
+                self.pos += rest
+                    .windows(2)
+                    .skip(2)
+                    .position(|w| w == b"*/")
+                    .map_or(rest.len(), |i| i + 4);
+            } else {
+                break;
+            }
+        }
+    }
+}
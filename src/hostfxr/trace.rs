@@ -0,0 +1,391 @@
+use crate::{error::HostingError, hostfxr::Hostfxr};
+use std::{
+    env,
+    ffi::OsString,
+    fmt,
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+use thiserror::Error;
+
+/// The environment variable enabling host tracing.
+pub const TRACE_ENV_VAR: &str = "COREHOST_TRACE";
+/// The environment variable containing the path of the file host tracing writes to instead of stderr.
+pub const TRACE_FILE_ENV_VAR: &str = "COREHOST_TRACEFILE";
+/// The environment variable controlling the verbosity of host tracing.
+pub const TRACE_VERBOSITY_ENV_VAR: &str = "COREHOST_TRACE_VERBOSITY";
+
+/// The verbosity used by [`HostTrace`], which includes all messages.
+const VERBOSE: &str = "4";
+
+static TRACING: AtomicBool = AtomicBool::new(false);
+
+/// Host tracing enabled for the current process, written to a file which can be parsed into [`HostTraceEvent`]s.
+///
+/// The hosting components read the tracing environment variables at the start of every hosting call, so the trace
+/// covers all calls made while the `HostTrace` is alive, regardless of when hostfxr was loaded. The previous values of
+/// the environment variables are restored and a temporary trace file is removed when the `HostTrace` is dropped.
+///
+/// # Example
+/// ```no_run
+/// # use netcorehost::{hostfxr::Hostfxr, pdcstr};
+/// let (hostfxr, mut trace) = unsafe { Hostfxr::load_from_path_with_trace("/usr/share/dotnet/host/fxr/8.0.0/libhostfxr.so") }.unwrap();
+/// if let Err(err) = hostfxr.initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json")) {
+///     let err = trace.attach(err.into());
+///     for path in err.probed_paths() {
+///         println!("probed {}", path.display());
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct HostTrace {
+    path: PathBuf,
+    temporary: bool,
+    offset: u64,
+    previous: [(&'static str, Option<OsString>); 3],
+}
+
+impl HostTrace {
+    /// Enables host tracing to a new temporary file.
+    ///
+    /// # Errors
+    /// Returns an error with kind [`io::ErrorKind::ResourceBusy`] if host tracing is already enabled by another
+    /// `HostTrace`, or an error if the trace file could not be created.
+    ///
+    /// # Safety
+    /// This function modifies the environment of the current process, see [`env::set_var`].
+    pub unsafe fn enable() -> io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = env::temp_dir().join(format!(
+            "netcorehost-trace-{}-{}.log",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        unsafe { Self::enable_with(path, true) }
+    }
+
+    /// Enables host tracing to the given file. The hosting components append to the file if it already exists.
+    ///
+    /// The trace is read back from the file, so the path has to refer to a regular file.
+    ///
+    /// # Errors
+    /// Returns an error with kind [`io::ErrorKind::ResourceBusy`] if host tracing is already enabled by another
+    /// `HostTrace`.
+    ///
+    /// # Safety
+    /// This function modifies the environment of the current process, see [`env::set_var`].
+    pub unsafe fn enable_to_file(path: impl Into<PathBuf>) -> io::Result<Self> {
+        unsafe { Self::enable_with(path.into(), false) }
+    }
+
+    unsafe fn enable_with(path: PathBuf, temporary: bool) -> io::Result<Self> {
+        if TRACING.swap(true, Ordering::AcqRel) {
+            return Err(io::Error::new(
+                io::ErrorKind::ResourceBusy,
+                "host tracing is already enabled",
+            ));
+        }
+
+        let offset = if temporary {
+            if let Err(err) = File::create(&path) {
+                TRACING.store(false, Ordering::Release);
+                return Err(err);
+            }
+            0
+        } else {
+            fs::metadata(&path).map_or(0, |metadata| metadata.len())
+        };
+
+        let previous = [TRACE_ENV_VAR, TRACE_FILE_ENV_VAR, TRACE_VERBOSITY_ENV_VAR]
+            .map(|name| (name, env::var_os(name)));
+        unsafe {
+            env::set_var(TRACE_ENV_VAR, "1");
+            env::set_var(TRACE_FILE_ENV_VAR, &path);
+            env::set_var(TRACE_VERBOSITY_ENV_VAR, VERBOSE);
+        }
+
+        Ok(Self {
+            path,
+            temporary,
+            offset,
+            previous,
+        })
+    }
+
+    /// Returns the path of the trace file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the whole trace written while tracing was enabled.
+    pub fn read_to_string(&self) -> io::Result<String> {
+        let mut file = File::open(&self.path)?;
+        let mut trace = Vec::new();
+        file.read_to_end(&mut trace)?;
+        Ok(String::from_utf8_lossy(&trace).into_owned())
+    }
+
+    /// Parses the whole trace written while tracing was enabled.
+    pub fn events(&self) -> io::Result<Vec<HostTraceEvent>> {
+        Ok(parse_host_trace(&self.read_to_string()?))
+    }
+
+    /// Parses the part of the trace written since the last call to this method or [`attach`](HostTrace::attach).
+    pub fn take_events(&mut self) -> io::Result<Vec<HostTraceEvent>> {
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut trace = Vec::new();
+        file.read_to_end(&mut trace)?;
+        // only consume complete lines, the hosting components may still be writing.
+        let complete = trace.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        self.offset += complete as u64;
+        Ok(parse_host_trace(&String::from_utf8_lossy(
+            &trace[..complete],
+        )))
+    }
+
+    /// Attaches the events traced since the last call to this method or [`take_events`](HostTrace::take_events) to
+    /// the given error.
+    ///
+    /// The error is returned even if the trace could not be read, in which case it has no events and the failure is
+    /// available as [`TracedHostingError::trace_error`].
+    pub fn attach(&mut self, error: HostingError) -> TracedHostingError {
+        let (events, trace_error) = match self.take_events() {
+            Ok(events) => (events, None),
+            Err(err) => (Vec::new(), Some(Arc::new(err))),
+        };
+        TracedHostingError {
+            error,
+            events,
+            trace_error,
+        }
+    }
+
+    /// Disables host tracing and returns the path of the trace file, which is kept even if it is temporary.
+    #[must_use]
+    pub fn keep(mut self) -> PathBuf {
+        self.temporary = false;
+        self.path.clone()
+    }
+}
+
+impl Drop for HostTrace {
+    fn drop(&mut self) {
+        for (name, value) in &self.previous {
+            match value {
+                Some(value) => unsafe { env::set_var(name, value) },
+                None => unsafe { env::remove_var(name) },
+            }
+        }
+        if self.temporary {
+            let _ = fs::remove_file(&self.path);
+        }
+        TRACING.store(false, Ordering::Release);
+    }
+}
+
+impl Hostfxr {
+    /// Enables host tracing to a temporary file using [`HostTrace::enable`] and loads the hostfxr library from the
+    /// given path, see [`Hostfxr::load_from_path`].
+    ///
+    /// # Safety
+    /// This function modifies the environment of the current process, see [`env::set_var`].
+    pub unsafe fn load_from_path_with_trace(
+        path: impl AsRef<Path>,
+    ) -> Result<(Self, HostTrace), LoadWithTraceError> {
+        let trace = unsafe { HostTrace::enable() }?;
+        let hostfxr = Self::load_from_path(path)?;
+        Ok((hostfxr, trace))
+    }
+}
+
+/// An error returned by [`Hostfxr::load_from_path_with_trace`].
+#[derive(Debug, Error)]
+pub enum LoadWithTraceError {
+    /// Host tracing could not be enabled.
+    #[error("Failed to enable host tracing: {0}")]
+    Trace(#[from] io::Error),
+    /// The hostfxr library could not be loaded.
+    #[error(transparent)]
+    Load(#[from] crate::hostfxr::LoadFromPathError),
+}
+
+/// A line of a host trace, classified by what it describes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HostTraceEvent {
+    /// A hosting component was invoked, e.g. `hostfxr_initialize_for_runtime_config` or `hostpolicy`.
+    Invoked {
+        /// The name of the invoked entry point or component.
+        component: String,
+    },
+    /// The resolution of a framework referenced by the app started.
+    ResolvingFramework {
+        /// The name of the framework, e.g. `Microsoft.NETCore.App`.
+        name: String,
+        /// The referenced version of the framework.
+        version: String,
+    },
+    /// A framework was resolved to the given directory.
+    FrameworkResolved {
+        /// The directory of the chosen framework version.
+        path: PathBuf,
+    },
+    /// A `.runtimeconfig.json` file was read.
+    RuntimeConfig {
+        /// The path of the file.
+        path: PathBuf,
+    },
+    /// A `.deps.json` file was processed.
+    DepsFile {
+        /// The path of the file.
+        path: PathBuf,
+    },
+    /// A path was probed while looking for a framework, an assembly or a native library.
+    Probe {
+        /// The probed path.
+        path: PathBuf,
+        /// The full trace line.
+        line: String,
+    },
+    /// Any other trace line.
+    Message(String),
+}
+
+impl HostTraceEvent {
+    /// Classifies a single line of a host trace. Returns [`None`] for blank lines.
+    #[must_use]
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() {
+            return None;
+        }
+
+        if let Some(rest) = trimmed.strip_prefix("--- Invoked ") {
+            let component = rest.split_whitespace().next().unwrap_or_default();
+            return Some(Self::Invoked {
+                component: component.to_owned(),
+            });
+        }
+        if let Some(rest) = trimmed.strip_prefix("--- Resolving FX directory, name ") {
+            let mut parts = rest.split('\'').skip(1).step_by(2);
+            if let (Some(name), Some(version)) = (parts.next(), parts.next()) {
+                return Some(Self::ResolvingFramework {
+                    name: name.to_owned(),
+                    version: version.to_owned(),
+                });
+            }
+        }
+        if trimmed.starts_with("Chose FX version")
+            && let Some(path) = bracketed(trimmed)
+        {
+            return Some(Self::FrameworkResolved { path: path.into() });
+        }
+        if let Some(path) = path_with_suffix(trimmed, ".runtimeconfig.json") {
+            return Some(Self::RuntimeConfig { path: path.into() });
+        }
+        if let Some(path) = path_with_suffix(trimmed, ".deps.json") {
+            return Some(Self::DepsFile { path: path.into() });
+        }
+        if trimmed.to_ascii_lowercase().contains("prob")
+            && let Some(path) = bracketed(trimmed).filter(|path| !path.is_empty())
+        {
+            return Some(Self::Probe {
+                path: path.into(),
+                line: trimmed.to_owned(),
+            });
+        }
+        Some(Self::Message(line.to_owned()))
+    }
+}
+
+/// Returns the content of the first pair of square brackets in the given line.
+fn bracketed(line: &str) -> Option<&str> {
+    let start = line.find('[')? + 1;
+    let end = start + line[start..].find(']')?;
+    Some(&line[start..end])
+}
+
+/// Returns the first path in the given line ending with the given suffix, with surrounding brackets or quotes removed.
+fn path_with_suffix<'a>(line: &'a str, suffix: &str) -> Option<&'a str> {
+    line.split(|c: char| c.is_whitespace() || matches!(c, '[' | ']' | '\'' | '"' | ','))
+        .map(|token| token.strip_prefix("cfg=").unwrap_or(token))
+        .find(|token| token.len() > suffix.len() && token.ends_with(suffix))
+}
+
+/// Parses a host trace into events.
+#[must_use]
+pub fn parse_host_trace(trace: &str) -> Vec<HostTraceEvent> {
+    trace
+        .lines()
+        .filter_map(HostTraceEvent::parse_line)
+        .collect()
+}
+
+/// A [`HostingError`] together with the host trace written while the failing call ran.
+#[derive(Debug, Clone)]
+pub struct TracedHostingError {
+    /// The error returned by the failing call.
+    pub error: HostingError,
+    /// The traced events.
+    pub events: Vec<HostTraceEvent>,
+    /// The error encountered while reading the trace, if it could not be read.
+    pub trace_error: Option<Arc<io::Error>>,
+}
+
+impl TracedHostingError {
+    /// Returns the paths probed while the failing call ran.
+    pub fn probed_paths(&self) -> impl Iterator<Item = &Path> {
+        self.events.iter().filter_map(|event| match event {
+            HostTraceEvent::Probe { path, .. } => Some(path.as_path()),
+            _ => None,
+        })
+    }
+
+    /// Returns the frameworks whose resolution was started while the failing call ran, as name and version pairs.
+    pub fn frameworks(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.events.iter().filter_map(|event| match event {
+            HostTraceEvent::ResolvingFramework { name, version } => {
+                Some((name.as_str(), version.as_str()))
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns the `.deps.json` files processed while the failing call ran.
+    pub fn deps_files(&self) -> impl Iterator<Item = &Path> {
+        self.events.iter().filter_map(|event| match event {
+            HostTraceEvent::DepsFile { path } => Some(path.as_path()),
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for TracedHostingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.trace_error {
+            None => write!(f, "{} ({} trace events)", self.error, self.events.len()),
+            Some(err) => write!(f, "{} (failed to read host trace: {err})", self.error),
+        }
+    }
+}
+
+impl std::error::Error for TracedHostingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<TracedHostingError> for HostingError {
+    fn from(err: TracedHostingError) -> Self {
+        err.error
+    }
+}
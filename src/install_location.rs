@@ -0,0 +1,373 @@
+use crate::{
+    arch::{
+        Architecture, ArchitectureMismatchError, CheckArchitectureError, UnreadableLibraryError,
+        check_library_architecture,
+    },
+    hostfxr::{Hostfxr, LoadFromPathError},
+};
+use std::{
+    env,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The directory containing the install location config files written by the official installers.
+pub const SYSTEM_CONFIG_DIR: &str = "/etc/dotnet";
+
+const INSTALL_LOCATION_FILE_NAME: &str = "install_location";
+
+/// The default install location used by the `dotnet` muxer if no other location is configured.
+#[cfg(target_os = "macos")]
+pub const DEFAULT_INSTALL_LOCATION: &str = "/usr/local/share/dotnet";
+/// The default install location used by the `dotnet` muxer if no other location is configured.
+#[cfg(target_os = "freebsd")]
+pub const DEFAULT_INSTALL_LOCATION: &str = "/usr/local/share/dotnet";
+/// The default install location used by the `dotnet` muxer if no other location is configured.
+#[cfg(not(any(target_os = "macos", target_os = "freebsd")))]
+pub const DEFAULT_INSTALL_LOCATION: &str = "/usr/share/dotnet";
+
+/// Returns the name .NET uses for the architecture of the current process (e.g. `x64` or `arm64`).
+/// This name is used in the names of architecture-specific config files and environment variables.
+#[must_use]
+pub fn current_arch_name() -> &'static str {
+    Architecture::current().map_or(env::consts::ARCH, |arch| arch.name())
+}
+
+/// A directory containing `install_location` config files.
+///
+/// The official installers write the path of the installation to `install_location` and the architecture-specific
+/// `install_location_<arch>` files. The `dotnet` muxer prefers the architecture-specific file over the generic one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InstallLocationConfig {
+    dir: PathBuf,
+}
+
+impl Default for InstallLocationConfig {
+    fn default() -> Self {
+        Self::system()
+    }
+}
+
+impl InstallLocationConfig {
+    /// Creates a config for the system-wide config directory [`SYSTEM_CONFIG_DIR`].
+    #[must_use]
+    pub fn system() -> Self {
+        Self::new(SYSTEM_CONFIG_DIR)
+    }
+
+    /// Creates a config for the given config directory.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the config directory.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the path to the config file for the given architecture or to the generic config file if no architecture is given.
+    #[must_use]
+    pub fn file_path(&self, arch: Option<&str>) -> PathBuf {
+        match arch {
+            Some(arch) => self.dir.join(format!(
+                "{INSTALL_LOCATION_FILE_NAME}_{}",
+                arch.to_ascii_lowercase()
+            )),
+            None => self.dir.join(INSTALL_LOCATION_FILE_NAME),
+        }
+    }
+
+    /// Reads the install location from the config file for the given architecture or from the generic config file
+    /// if no architecture is given.
+    ///
+    /// Returns [`None`] if the file does not exist or is empty.
+    pub fn read(&self, arch: Option<&str>) -> io::Result<Option<PathBuf>> {
+        let contents = match fs::read_to_string(self.file_path(arch)) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        // Only the first line is used, the same way the muxer does it.
+        let location = contents.lines().next().unwrap_or_default().trim();
+        Ok((!location.is_empty()).then(|| PathBuf::from(location)))
+    }
+
+    /// Reads the install location for the given architecture, falling back to the generic config file if there is
+    /// no architecture-specific one.
+    pub fn read_for_arch(&self, arch: &str) -> io::Result<Option<InstallLocation>> {
+        if let Some(path) = self.read(Some(arch))? {
+            return Ok(Some(InstallLocation {
+                path,
+                source: InstallLocationSource::ArchConfigFile,
+            }));
+        }
+        Ok(self.read(None)?.map(|path| InstallLocation {
+            path,
+            source: InstallLocationSource::ConfigFile,
+        }))
+    }
+
+    /// Writes the given install location to the config file for the given architecture or to the generic config file
+    /// if no architecture is given, creating the config directory if necessary.
+    pub fn write(&self, arch: Option<&str>, location: impl AsRef<Path>) -> io::Result<()> {
+        let mut contents = OsString::from(location.as_ref());
+        contents.push("\n");
+        fs::create_dir_all(&self.dir)?;
+        fs::write(
+            self.file_path(arch),
+            std::os::unix::ffi::OsStrExt::as_bytes(contents.as_os_str()),
+        )
+    }
+
+    /// Removes the config file for the given architecture or the generic config file if no architecture is given.
+    /// Removing a file which does not exist is not an error.
+    pub fn remove(&self, arch: Option<&str>) -> io::Result<()> {
+        match fs::remove_file(self.file_path(arch)) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Resolves the dotnet root for the architecture of the current process the same way the `dotnet` muxer does.
+    ///
+    /// The first of the following locations which is configured is used:
+    ///  1. The `DOTNET_ROOT_<ARCH>` environment variable
+    ///  2. The `DOTNET_ROOT` environment variable
+    ///  3. The architecture-specific config file
+    ///  4. The generic config file
+    ///  5. The [default install location](DEFAULT_INSTALL_LOCATION)
+    ///
+    /// Like the muxer, later locations are not considered if the first configured one does not contain a hostfxr
+    /// library. [`None`] is returned in that case. If its hostfxr library was built for a different architecture,
+    /// [`LoadFromInstallLocationError::ArchitectureMismatch`] is returned instead.
+    pub fn resolve_dotnet_root(
+        &self,
+    ) -> Result<Option<InstallLocation>, LoadFromInstallLocationError> {
+        let Some(location) = self.dotnet_root_candidates()?.into_iter().next() else {
+            return Ok(None);
+        };
+        let Some(hostfxr) = find_hostfxr_in_dotnet_root(&location.path) else {
+            return Ok(None);
+        };
+        check_library_architecture(hostfxr)?;
+        Ok(Some(location))
+    }
+
+    /// Returns all configured dotnet root candidates for the architecture of the current process in order of
+    /// precedence. [`resolve_dotnet_root`](InstallLocationConfig::resolve_dotnet_root) only uses the first one.
+    pub fn dotnet_root_candidates(&self) -> io::Result<Vec<InstallLocation>> {
+        let arch = current_arch_name();
+        let mut candidates = Vec::new();
+
+        let arch_var = Architecture::current().map(|arch| arch.dotnet_root_env_var());
+        for (var, source) in [
+            (arch_var.as_deref(), InstallLocationSource::ArchEnvironment),
+            (Some("DOTNET_ROOT"), InstallLocationSource::Environment),
+        ] {
+            if let Some(path) = var.and_then(env::var_os).filter(|p| !p.is_empty()) {
+                candidates.push(InstallLocation {
+                    path: path.into(),
+                    source,
+                });
+            }
+        }
+
+        if let Some(path) = self.read(Some(arch))? {
+            candidates.push(InstallLocation {
+                path,
+                source: InstallLocationSource::ArchConfigFile,
+            });
+        }
+        if let Some(path) = self.read(None)? {
+            candidates.push(InstallLocation {
+                path,
+                source: InstallLocationSource::ConfigFile,
+            });
+        }
+
+        candidates.push(InstallLocation {
+            path: PathBuf::from(DEFAULT_INSTALL_LOCATION),
+            source: InstallLocationSource::Default,
+        });
+
+        Ok(candidates)
+    }
+}
+
+/// A .NET install location together with where it was configured.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InstallLocation {
+    /// The path to the root of the installation.
+    pub path: PathBuf,
+    /// Where the install location was configured.
+    pub source: InstallLocationSource,
+}
+
+/// Where an [`InstallLocation`] was configured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstallLocationSource {
+    /// The `DOTNET_ROOT_<ARCH>` environment variable.
+    ArchEnvironment,
+    /// The `DOTNET_ROOT` environment variable.
+    Environment,
+    /// The architecture-specific `install_location_<arch>` config file.
+    ArchConfigFile,
+    /// The generic `install_location` config file.
+    ConfigFile,
+    /// The default install location.
+    Default,
+}
+
+/// Resolves the dotnet root for the architecture of the current process using the system-wide config files.
+/// See [`InstallLocationConfig::resolve_dotnet_root`] for details.
+pub fn resolve_dotnet_root() -> Result<Option<InstallLocation>, LoadFromInstallLocationError> {
+    InstallLocationConfig::system().resolve_dotnet_root()
+}
+
+/// Returns the path to the hostfxr library of the highest version in the given dotnet root,
+/// which is located at `<dotnet_root>/host/fxr/<version>/libhostfxr.so`.
+#[must_use]
+pub fn find_hostfxr_in_dotnet_root(dotnet_root: impl AsRef<Path>) -> Option<PathBuf> {
+    let library_name = format!(
+        "{}hostfxr{}",
+        env::consts::DLL_PREFIX,
+        env::consts::DLL_SUFFIX
+    );
+    fs::read_dir(dotnet_root.as_ref().join("host").join("fxr"))
+        .ok()?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let version = FxrVersion::parse(entry.file_name().to_str()?)?;
+            let path = entry.path().join(&library_name);
+            path.is_file().then_some((version, path))
+        })
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, path)| path)
+}
+
+impl Hostfxr {
+    /// Resolves the dotnet root using the system-wide install location config the same way the `dotnet` muxer does
+    /// and loads the hostfxr library of the highest version from it.
+    /// See [`InstallLocationConfig::resolve_dotnet_root`] for details.
+    pub fn load_from_install_location() -> Result<Self, LoadFromInstallLocationError> {
+        let location = resolve_dotnet_root()?.ok_or(LoadFromInstallLocationError::NotFound)?;
+        Self::load_from_dotnet_root(location.path)
+    }
+
+    /// Loads the hostfxr library of the highest version from the given dotnet root.
+    pub fn load_from_dotnet_root(
+        dotnet_root: impl AsRef<Path>,
+    ) -> Result<Self, LoadFromInstallLocationError> {
+        let dotnet_root = dotnet_root.as_ref();
+        let hostfxr_path = find_hostfxr_in_dotnet_root(dotnet_root)
+            .ok_or(LoadFromInstallLocationError::NotFound)?;
+        // The dotnet root is known here, so there is no need to guess it from the hostfxr path.
+        Ok(Self::load_with_dotnet_exe(
+            hostfxr_path,
+            dotnet_root.join("dotnet"),
+        )?)
+    }
+}
+
+/// Enum for errors that can occur while loading the hostfxr library from an install location.
+#[derive(Debug, thiserror::Error)]
+pub enum LoadFromInstallLocationError {
+    /// No install location containing a hostfxr library could be found.
+    #[error("No .NET install location containing hostfxr could be found.")]
+    NotFound,
+    /// An io error occured while reading the install location config.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The hostfxr library was built for a different architecture than the current process.
+    #[error(transparent)]
+    ArchitectureMismatch(#[from] ArchitectureMismatchError),
+    /// The header of the hostfxr library could not be read to determine its architecture.
+    #[error(transparent)]
+    UnreadableLibrary(#[from] UnreadableLibraryError),
+    /// An error occured while loading the hostfxr library.
+    #[error(transparent)]
+    DlOpen(crate::dlopen2::Error),
+    /// The path to the dotnet executable contains a nul character.
+    #[error(transparent)]
+    InvalidPath(#[from] crate::pdcstring::ContainsNul),
+}
+
+impl From<CheckArchitectureError> for LoadFromInstallLocationError {
+    fn from(err: CheckArchitectureError) -> Self {
+        match err {
+            CheckArchitectureError::ArchitectureMismatch(err) => Self::ArchitectureMismatch(err),
+            CheckArchitectureError::UnreadableLibrary(err) => Self::UnreadableLibrary(err),
+        }
+    }
+}
+
+impl From<crate::dlopen2::Error> for LoadFromInstallLocationError {
+    fn from(err: crate::dlopen2::Error) -> Self {
+        Self::DlOpen(err)
+    }
+}
+
+impl From<LoadFromPathError> for LoadFromInstallLocationError {
+    fn from(err: LoadFromPathError) -> Self {
+        match err {
+            LoadFromPathError::ArchitectureMismatch(err) => Self::ArchitectureMismatch(err),
+            LoadFromPathError::UnreadableLibrary(err) => Self::UnreadableLibrary(err),
+            LoadFromPathError::DlOpen(err) => Self::DlOpen(err),
+            LoadFromPathError::InvalidPath(err) => Self::InvalidPath(err),
+        }
+    }
+}
+
+/// A version of the `host/fxr/<version>` directories, ordered like semver (prereleases before releases).
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct FxrVersion {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    is_release: bool,
+    pre_release: Vec<PreReleaseIdentifier>,
+}
+
+/// A dot separated identifier of a prerelease label.
+/// Numeric identifiers are compared numerically and sort before alphanumeric ones, as in semver.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum PreReleaseIdentifier {
+    Numeric(u64),
+    Alphanumeric(String),
+}
+
+impl FxrVersion {
+    fn parse(s: &str) -> Option<Self> {
+        let (version, pre_release) = s.split_once('-').unwrap_or((s, ""));
+        let mut parts = version.split('.').map(|p| p.parse::<u64>().ok());
+        let major = parts.next()??;
+        let minor = parts.next()??;
+        let patch = parts.next()??;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            major,
+            minor,
+            patch,
+            is_release: pre_release.is_empty(),
+            pre_release: if pre_release.is_empty() {
+                Vec::new()
+            } else {
+                pre_release
+                    .split('.')
+                    .map(|identifier| {
+                        identifier.parse().map_or_else(
+                            |_| PreReleaseIdentifier::Alphanumeric(identifier.to_owned()),
+                            PreReleaseIdentifier::Numeric,
+                        )
+                    })
+                    .collect()
+            },
+        })
+    }
+}
@@ -161,6 +161,10 @@
 //! # Features
 //! - `nethost` - Links against nethost and allows for automatic detection of the hostfxr library.
 //! - `download-nethost` - Automatically downloads the latest nethost binary from [NuGet](https://www.nuget.org/packages/Microsoft.NETCore.DotNetHost/).
+//! - `bundle` - Enables reading, extracting and running single-file bundles.
+//! - `fake` - Provides `FakeHostfxr`, an in-memory implementation of the hostfxr API for testing hosts without a hostfxr library.
+//! - `log` - Logs the status of hosting calls through the [`log`](https://docs.rs/log) facade and provides an error writer forwarding the messages of hostfxr to it.
+//! - `tracing` - Wraps hosting calls in [`tracing`](https://docs.rs/tracing) spans, emits their status as events and provides an error writer forwarding the messages of hostfxr to it.
 //!
 //! [`UnmanagedCallersOnly`]: <https://docs.microsoft.com/en-us/dotnet/api/system.runtime.interopservices.unmanagedcallersonlyattribute>
 //! [`AssemblyDelegateLoader`]: crate::hostfxr::AssemblyDelegateLoader
@@ -185,6 +189,25 @@ pub mod pdcstring;
 /// Module containing error enums.
 pub mod error;
 
+/// Module for detecting the architecture of native libraries.
+pub mod arch;
+
+/// Module for reading the metadata of .NET assemblies.
+pub mod metadata;
+
+/// Module for parsing and constructing assembly qualified type names.
+pub mod type_name;
+
+/// Module for reading and writing the install location config files used to locate .NET installations. (unix-only)
+#[cfg(unix)]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(unix)))]
+pub mod install_location;
+
+/// Module for reading and running single-file bundles.
+#[cfg(feature = "bundle")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "bundle")))]
+pub mod bundle;
+
 /// Module containing additional utilities. (currently unix-only)
 #[cfg(feature = "utils")]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "utils")))]
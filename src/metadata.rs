@@ -0,0 +1,675 @@
+use std::{fmt, fs, io, path::Path};
+use thiserror::Error;
+
+/// The types, methods and identity read from the ECMA-335 metadata of a .NET assembly.
+///
+/// Only the parts of the metadata needed to diagnose failed function lookups are read, the assembly is never loaded.
+///
+/// Source: [ECMA-335, Partition II](https://www.ecma-international.org/publications-and-standards/standards/ecma-335/)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssemblyMetadata {
+    assembly: Option<AssemblyIdentity>,
+    types: Vec<TypeMetadata>,
+}
+
+impl AssemblyMetadata {
+    /// Reads the metadata of the assembly at the given path.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, MetadataError> {
+        Self::parse(&fs::read(path)?)
+    }
+
+    /// Reads the metadata of the given assembly image.
+    pub fn parse(image: &[u8]) -> Result<Self, MetadataError> {
+        let root = cli_metadata(image)?;
+        Tables::parse(root)?.read()
+    }
+
+    /// Returns the identity of the assembly or [`None`] if the image is a module without an assembly manifest.
+    #[must_use]
+    pub fn assembly(&self) -> Option<&AssemblyIdentity> {
+        self.assembly.as_ref()
+    }
+
+    /// Returns the types defined in the assembly.
+    #[must_use]
+    pub fn types(&self) -> &[TypeMetadata] {
+        &self.types
+    }
+
+    /// Returns the type with the given full name (e.g. `Namespace.Outer+Nested`).
+    #[must_use]
+    pub fn find_type(&self, full_name: &str) -> Option<&TypeMetadata> {
+        self.types.iter().find(|ty| ty.full_name == full_name)
+    }
+}
+
+/// The name, version and culture of an assembly.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssemblyIdentity {
+    /// The simple name of the assembly (e.g. `System.Runtime`).
+    pub name: String,
+    /// The version of the assembly.
+    pub version: AssemblyVersion,
+    /// The culture of the assembly or [`None`] if it is culture neutral.
+    pub culture: Option<String>,
+}
+
+/// The four-part version of an assembly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssemblyVersion {
+    /// The major version.
+    pub major: u16,
+    /// The minor version.
+    pub minor: u16,
+    /// The build number.
+    pub build: u16,
+    /// The revision number.
+    pub revision: u16,
+}
+
+impl AssemblyVersion {
+    /// Creates a new [`AssemblyVersion`] from its parts.
+    #[must_use]
+    pub const fn new(major: u16, minor: u16, build: u16, revision: u16) -> Self {
+        Self {
+            major,
+            minor,
+            build,
+            revision,
+        }
+    }
+}
+
+impl fmt::Display for AssemblyVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.build, self.revision
+        )
+    }
+}
+
+/// A type defined in an assembly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeMetadata {
+    namespace: String,
+    name: String,
+    full_name: String,
+    methods: Vec<MethodMetadata>,
+}
+
+impl TypeMetadata {
+    /// Returns the namespace of the type. For nested types this is the namespace of the outermost type.
+    #[must_use]
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Returns the name of the type without its namespace and enclosing types.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the full name of the type as used in type names, with nested types separated by `+`.
+    #[must_use]
+    pub fn full_name(&self) -> &str {
+        &self.full_name
+    }
+
+    /// Returns the methods defined on the type.
+    #[must_use]
+    pub fn methods(&self) -> &[MethodMetadata] {
+        &self.methods
+    }
+
+    /// Returns an iterator over the overloads of the method with the given name.
+    pub fn methods_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a MethodMetadata> + 'a {
+        self.methods
+            .iter()
+            .filter(move |method| method.name == name)
+    }
+}
+
+/// A method defined on a type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodMetadata {
+    name: String,
+    flags: u16,
+    unmanaged_callers_only: bool,
+}
+
+impl MethodMetadata {
+    /// Returns the name of the method.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns whether the method is static.
+    #[must_use]
+    pub const fn is_static(&self) -> bool {
+        self.flags & 0x0010 != 0
+    }
+
+    /// Returns whether the method is annotated with the
+    /// [`UnmanagedCallersOnlyAttribute`](https://docs.microsoft.com/en-us/dotnet/api/system.runtime.interopservices.unmanagedcallersonlyattribute).
+    #[must_use]
+    pub const fn is_unmanaged_callers_only(&self) -> bool {
+        self.unmanaged_callers_only
+    }
+}
+
+/// Errors that can occur while reading the metadata of an assembly.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum MetadataError {
+    /// An io error occured while reading the assembly.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The file is not a portable executable.
+    #[error("The file is not a portable executable.")]
+    NotPortableExecutable,
+    /// The file is a portable executable but does not contain .NET metadata.
+    #[error("The file does not contain .NET metadata.")]
+    NotManaged,
+    /// The metadata is malformed or uses an unsupported format.
+    #[error("The metadata is malformed or uses an unsupported format.")]
+    Malformed,
+}
+
+fn slice(bytes: &[u8], offset: usize, len: usize) -> Result<&[u8], MetadataError> {
+    offset
+        .checked_add(len)
+        .and_then(|end| bytes.get(offset..end))
+        .ok_or(MetadataError::Malformed)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, MetadataError> {
+    let bytes = slice(bytes, offset, 2)?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, MetadataError> {
+    let bytes = slice(bytes, offset, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, MetadataError> {
+    Ok(u64::from(read_u32(bytes, offset)?) | (u64::from(read_u32(bytes, offset + 4)?) << 32))
+}
+
+/// Returns the metadata root of the given PE image.
+fn cli_metadata(image: &[u8]) -> Result<&[u8], MetadataError> {
+    if image.get(..2) != Some(b"MZ") {
+        return Err(MetadataError::NotPortableExecutable);
+    }
+    let pe = read_u32(image, 0x3C).map_err(|_| MetadataError::NotPortableExecutable)? as usize;
+    if image.get(pe..pe + 4) != Some(b"PE\0\0") {
+        return Err(MetadataError::NotPortableExecutable);
+    }
+
+    let coff = pe + 4;
+    let section_count = read_u16(image, coff + 2)? as usize;
+    let optional_header_size = read_u16(image, coff + 16)? as usize;
+    let optional_header = coff + 20;
+    let (rva_count, data_directories) = match read_u16(image, optional_header)? {
+        0x010B => (optional_header + 92, optional_header + 96),
+        0x020B => (optional_header + 108, optional_header + 112),
+        _ => return Err(MetadataError::Malformed),
+    };
+    if read_u32(image, rva_count)? < 15 {
+        return Err(MetadataError::NotManaged);
+    }
+    let cli_header_rva = read_u32(image, data_directories + 14 * 8)?;
+    if cli_header_rva == 0 {
+        return Err(MetadataError::NotManaged);
+    }
+
+    let sections = optional_header + optional_header_size;
+    let rva_to_offset = |rva: u32| -> Result<usize, MetadataError> {
+        for i in 0..section_count {
+            let section = sections + i * 40;
+            let virtual_size = read_u32(image, section + 8)?;
+            let virtual_address = read_u32(image, section + 12)?;
+            let raw_size = read_u32(image, section + 16)?;
+            let raw_offset = read_u32(image, section + 20)?;
+            if rva >= virtual_address && rva - virtual_address < virtual_size.max(raw_size) {
+                return Ok((rva - virtual_address + raw_offset) as usize);
+            }
+        }
+        Err(MetadataError::Malformed)
+    };
+
+    let cli_header = rva_to_offset(cli_header_rva)?;
+    let metadata = rva_to_offset(read_u32(image, cli_header + 8)?)?;
+    let metadata_size = read_u32(image, cli_header + 12)? as usize;
+    slice(image, metadata, metadata_size)
+}
+
+const MODULE: usize = 0x00;
+const TYPE_REF: usize = 0x01;
+const TYPE_DEF: usize = 0x02;
+const FIELD_PTR: usize = 0x03;
+const FIELD: usize = 0x04;
+const METHOD_PTR: usize = 0x05;
+const METHOD_DEF: usize = 0x06;
+const PARAM: usize = 0x08;
+const INTERFACE_IMPL: usize = 0x09;
+const MEMBER_REF: usize = 0x0A;
+const CUSTOM_ATTRIBUTE: usize = 0x0C;
+const DECL_SECURITY: usize = 0x0E;
+const STAND_ALONE_SIG: usize = 0x11;
+const EVENT: usize = 0x14;
+const PROPERTY: usize = 0x17;
+const MODULE_REF: usize = 0x1A;
+const TYPE_SPEC: usize = 0x1B;
+const ASSEMBLY: usize = 0x20;
+const ASSEMBLY_REF: usize = 0x23;
+const FILE: usize = 0x26;
+const EXPORTED_TYPE: usize = 0x27;
+const MANIFEST_RESOURCE: usize = 0x28;
+const NESTED_CLASS: usize = 0x29;
+const GENERIC_PARAM: usize = 0x2A;
+const METHOD_SPEC: usize = 0x2B;
+const GENERIC_PARAM_CONSTRAINT: usize = 0x2C;
+const UNUSED: usize = usize::MAX;
+
+const TYPE_DEF_OR_REF: &[usize] = &[TYPE_DEF, TYPE_REF, TYPE_SPEC];
+const HAS_CONSTANT: &[usize] = &[FIELD, PARAM, PROPERTY];
+const HAS_CUSTOM_ATTRIBUTE: &[usize] = &[
+    METHOD_DEF,
+    FIELD,
+    TYPE_REF,
+    TYPE_DEF,
+    PARAM,
+    INTERFACE_IMPL,
+    MEMBER_REF,
+    MODULE,
+    DECL_SECURITY,
+    PROPERTY,
+    EVENT,
+    STAND_ALONE_SIG,
+    MODULE_REF,
+    TYPE_SPEC,
+    ASSEMBLY,
+    ASSEMBLY_REF,
+    FILE,
+    EXPORTED_TYPE,
+    MANIFEST_RESOURCE,
+    GENERIC_PARAM,
+    GENERIC_PARAM_CONSTRAINT,
+    METHOD_SPEC,
+];
+const HAS_FIELD_MARSHAL: &[usize] = &[FIELD, PARAM];
+const HAS_DECL_SECURITY: &[usize] = &[TYPE_DEF, METHOD_DEF, ASSEMBLY];
+const MEMBER_REF_PARENT: &[usize] = &[TYPE_DEF, TYPE_REF, MODULE_REF, METHOD_DEF, TYPE_SPEC];
+const HAS_SEMANTICS: &[usize] = &[EVENT, PROPERTY];
+const METHOD_DEF_OR_REF: &[usize] = &[METHOD_DEF, MEMBER_REF];
+const MEMBER_FORWARDED: &[usize] = &[FIELD, METHOD_DEF];
+const IMPLEMENTATION: &[usize] = &[FILE, ASSEMBLY_REF, EXPORTED_TYPE];
+const CUSTOM_ATTRIBUTE_TYPE: &[usize] = &[UNUSED, UNUSED, METHOD_DEF, MEMBER_REF, UNUSED];
+const RESOLUTION_SCOPE: &[usize] = &[MODULE, MODULE_REF, ASSEMBLY_REF, TYPE_REF];
+const TYPE_OR_METHOD_DEF: &[usize] = &[TYPE_DEF, METHOD_DEF];
+
+#[derive(Clone, Copy)]
+enum Column {
+    U16,
+    U32,
+    Str,
+    Guid,
+    Blob,
+    Index(usize),
+    Coded(&'static [usize]),
+}
+
+use Column::{Blob, Coded, Guid, Index, Str, U16, U32};
+
+/// The columns of all tables defined by ECMA-335 in table order.
+#[rustfmt::skip]
+const SCHEMA: [&[Column]; 45] = [
+    /* Module */ &[U16, Str, Guid, Guid, Guid],
+    /* TypeRef */ &[Coded(RESOLUTION_SCOPE), Str, Str],
+    /* TypeDef */ &[U32, Str, Str, Coded(TYPE_DEF_OR_REF), Index(FIELD), Index(METHOD_DEF)],
+    /* FieldPtr */ &[Index(FIELD)],
+    /* Field */ &[U16, Str, Blob],
+    /* MethodPtr */ &[Index(METHOD_DEF)],
+    /* MethodDef */ &[U32, U16, U16, Str, Blob, Index(PARAM)],
+    /* ParamPtr */ &[Index(PARAM)],
+    /* Param */ &[U16, U16, Str],
+    /* InterfaceImpl */ &[Index(TYPE_DEF), Coded(TYPE_DEF_OR_REF)],
+    /* MemberRef */ &[Coded(MEMBER_REF_PARENT), Str, Blob],
+    /* Constant */ &[U16, Coded(HAS_CONSTANT), Blob],
+    /* CustomAttribute */ &[Coded(HAS_CUSTOM_ATTRIBUTE), Coded(CUSTOM_ATTRIBUTE_TYPE), Blob],
+    /* FieldMarshal */ &[Coded(HAS_FIELD_MARSHAL), Blob],
+    /* DeclSecurity */ &[U16, Coded(HAS_DECL_SECURITY), Blob],
+    /* ClassLayout */ &[U16, U32, Index(TYPE_DEF)],
+    /* FieldLayout */ &[U32, Index(FIELD)],
+    /* StandAloneSig */ &[Blob],
+    /* EventMap */ &[Index(TYPE_DEF), Index(EVENT)],
+    /* EventPtr */ &[Index(EVENT)],
+    /* Event */ &[U16, Str, Coded(TYPE_DEF_OR_REF)],
+    /* PropertyMap */ &[Index(TYPE_DEF), Index(PROPERTY)],
+    /* PropertyPtr */ &[Index(PROPERTY)],
+    /* Property */ &[U16, Str, Blob],
+    /* MethodSemantics */ &[U16, Index(METHOD_DEF), Coded(HAS_SEMANTICS)],
+    /* MethodImpl */ &[Index(TYPE_DEF), Coded(METHOD_DEF_OR_REF), Coded(METHOD_DEF_OR_REF)],
+    /* ModuleRef */ &[Str],
+    /* TypeSpec */ &[Blob],
+    /* ImplMap */ &[U16, Coded(MEMBER_FORWARDED), Str, Index(MODULE_REF)],
+    /* FieldRVA */ &[U32, Index(FIELD)],
+    /* EncLog */ &[U32, U32],
+    /* EncMap */ &[U32],
+    /* Assembly */ &[U32, U16, U16, U16, U16, U32, Blob, Str, Str],
+    /* AssemblyProcessor */ &[U32],
+    /* AssemblyOS */ &[U32, U32, U32],
+    /* AssemblyRef */ &[U16, U16, U16, U16, U32, Blob, Str, Str, Blob],
+    /* AssemblyRefProcessor */ &[U32, Index(ASSEMBLY_REF)],
+    /* AssemblyRefOS */ &[U32, U32, U32, Index(ASSEMBLY_REF)],
+    /* File */ &[U32, Str, Blob],
+    /* ExportedType */ &[U32, U32, Str, Str, Coded(IMPLEMENTATION)],
+    /* ManifestResource */ &[U32, U32, Str, Coded(IMPLEMENTATION)],
+    /* NestedClass */ &[Index(TYPE_DEF), Index(TYPE_DEF)],
+    /* GenericParam */ &[U16, U16, Coded(TYPE_OR_METHOD_DEF), Str],
+    /* MethodSpec */ &[Coded(METHOD_DEF_OR_REF), Blob],
+    /* GenericParamConstraint */ &[Index(GENERIC_PARAM), Coded(TYPE_DEF_OR_REF)],
+];
+
+const fn tag_bits(tables: &[usize]) -> u32 {
+    usize::BITS - (tables.len() - 1).leading_zeros()
+}
+
+/// The table stream of a metadata root together with the heaps needed to read it.
+struct Tables<'a> {
+    data: &'a [u8],
+    strings: &'a [u8],
+    rows: [u32; 64],
+    offsets: [usize; SCHEMA.len()],
+    row_sizes: [usize; SCHEMA.len()],
+    wide_strings: bool,
+    wide_guids: bool,
+    wide_blobs: bool,
+}
+
+impl<'a> Tables<'a> {
+    fn parse(root: &'a [u8]) -> Result<Self, MetadataError> {
+        if read_u32(root, 0)? != 0x424A_5342 {
+            return Err(MetadataError::Malformed);
+        }
+        let version_len = read_u32(root, 12)? as usize;
+        let mut offset = 16 + version_len + 2;
+        let stream_count = read_u16(root, offset)?;
+        offset += 2;
+
+        let mut tables = None;
+        let mut strings = None;
+        for _ in 0..stream_count {
+            let stream_offset = read_u32(root, offset)? as usize;
+            let stream_size = read_u32(root, offset + 4)? as usize;
+            let name_start = offset + 8;
+            let name_len = root
+                .get(name_start..)
+                .and_then(|rest| rest.iter().position(|&b| b == 0))
+                .ok_or(MetadataError::Malformed)?;
+            let stream = slice(root, stream_offset, stream_size)?;
+            match &root[name_start..name_start + name_len] {
+                b"#~" => tables = Some(stream),
+                b"#Strings" => strings = Some(stream),
+                // uncompressed tables with indirection tables are only emitted by edit and continue.
+                b"#-" => return Err(MetadataError::Malformed),
+                _ => {}
+            }
+            offset = name_start + (name_len + 4) / 4 * 4;
+        }
+        let data = tables.ok_or(MetadataError::Malformed)?;
+        let strings = strings.unwrap_or_default();
+
+        let heap_sizes = *data.get(6).ok_or(MetadataError::Malformed)?;
+        let valid = read_u64(data, 8)?;
+        let mut rows = [0u32; 64];
+        let mut offset = 24;
+        for (table, count) in rows.iter_mut().enumerate() {
+            if valid & (1 << table) != 0 {
+                *count = read_u32(data, offset)?;
+                offset += 4;
+            }
+        }
+        if heap_sizes & 0x40 != 0 {
+            offset += 4;
+        }
+        if valid >> SCHEMA.len() != 0 || rows[FIELD_PTR] != 0 || rows[METHOD_PTR] != 0 {
+            return Err(MetadataError::Malformed);
+        }
+
+        let mut tables = Self {
+            data,
+            strings,
+            rows,
+            offsets: [0; SCHEMA.len()],
+            row_sizes: [0; SCHEMA.len()],
+            wide_strings: heap_sizes & 0x01 != 0,
+            wide_guids: heap_sizes & 0x02 != 0,
+            wide_blobs: heap_sizes & 0x04 != 0,
+        };
+        for table in 0..SCHEMA.len() {
+            let row_size = SCHEMA[table]
+                .iter()
+                .map(|&column| tables.column_size(column))
+                .sum::<usize>();
+            tables.offsets[table] = offset;
+            tables.row_sizes[table] = row_size;
+            offset += row_size * rows[table] as usize;
+        }
+        if offset > data.len() {
+            return Err(MetadataError::Malformed);
+        }
+        Ok(tables)
+    }
+
+    fn column_size(&self, column: Column) -> usize {
+        let wide = match column {
+            U16 => return 2,
+            U32 => return 4,
+            Str => self.wide_strings,
+            Guid => self.wide_guids,
+            Blob => self.wide_blobs,
+            Index(table) => self.rows[table] > 0xFFFF,
+            Coded(tables) => {
+                let max_rows = tables
+                    .iter()
+                    .filter(|&&table| table != UNUSED)
+                    .map(|&table| self.rows[table])
+                    .max()
+                    .unwrap_or(0);
+                max_rows >= 1 << (16 - tag_bits(tables))
+            }
+        };
+        if wide { 4 } else { 2 }
+    }
+
+    fn row_count(&self, table: usize) -> u32 {
+        self.rows[table]
+    }
+
+    /// Reads the given column of the given 1-based row.
+    fn cell(&self, table: usize, row: u32, column: usize) -> Result<u32, MetadataError> {
+        if row == 0 || row > self.rows[table] {
+            return Err(MetadataError::Malformed);
+        }
+        let mut offset = self.offsets[table] + (row as usize - 1) * self.row_sizes[table];
+        for &previous in &SCHEMA[table][..column] {
+            offset += self.column_size(previous);
+        }
+        match self.column_size(SCHEMA[table][column]) {
+            2 => read_u16(self.data, offset).map(u32::from),
+            _ => read_u32(self.data, offset),
+        }
+    }
+
+    /// Reads the given coded index column and returns the referenced table and 1-based row.
+    fn coded_cell(
+        &self,
+        table: usize,
+        row: u32,
+        column: usize,
+    ) -> Result<(usize, u32), MetadataError> {
+        let Coded(tables) = SCHEMA[table][column] else {
+            unreachable!("column is not a coded index")
+        };
+        let value = self.cell(table, row, column)?;
+        let bits = tag_bits(tables);
+        let target = *tables
+            .get((value & ((1 << bits) - 1)) as usize)
+            .ok_or(MetadataError::Malformed)?;
+        Ok((target, value >> bits))
+    }
+
+    fn string(&self, index: u32) -> Result<String, MetadataError> {
+        let bytes = self
+            .strings
+            .get(index as usize..)
+            .ok_or(MetadataError::Malformed)?;
+        let len = bytes
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(MetadataError::Malformed)?;
+        Ok(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+
+    fn string_cell(&self, table: usize, row: u32, column: usize) -> Result<String, MetadataError> {
+        self.string(self.cell(table, row, column)?)
+    }
+
+    fn read(&self) -> Result<AssemblyMetadata, MetadataError> {
+        let assembly = if self.row_count(ASSEMBLY) > 0 {
+            let culture = self.string_cell(ASSEMBLY, 1, 8)?;
+            Some(AssemblyIdentity {
+                name: self.string_cell(ASSEMBLY, 1, 7)?,
+                version: AssemblyVersion::new(
+                    self.cell_u16(ASSEMBLY, 1, 1)?,
+                    self.cell_u16(ASSEMBLY, 1, 2)?,
+                    self.cell_u16(ASSEMBLY, 1, 3)?,
+                    self.cell_u16(ASSEMBLY, 1, 4)?,
+                ),
+                culture: (!culture.is_empty()).then_some(culture),
+            })
+        } else {
+            None
+        };
+
+        let unmanaged_callers_only = self.unmanaged_callers_only_methods()?;
+        let type_count = self.row_count(TYPE_DEF);
+        let method_count = self.row_count(METHOD_DEF);
+        let mut types = Vec::with_capacity(type_count as usize);
+        for row in 1..=type_count {
+            let first = self.cell(TYPE_DEF, row, 5)?;
+            let end = if row < type_count {
+                self.cell(TYPE_DEF, row + 1, 5)?
+            } else {
+                method_count + 1
+            };
+            let methods = (first..end.min(method_count + 1))
+                .map(|method| {
+                    Ok(MethodMetadata {
+                        name: self.string_cell(METHOD_DEF, method, 3)?,
+                        flags: self.cell_u16(METHOD_DEF, method, 2)?,
+                        unmanaged_callers_only: unmanaged_callers_only.contains(&method),
+                    })
+                })
+                .collect::<Result<_, MetadataError>>()?;
+            types.push(TypeMetadata {
+                namespace: self.string_cell(TYPE_DEF, row, 2)?,
+                name: self.string_cell(TYPE_DEF, row, 1)?,
+                full_name: String::new(),
+                methods,
+            });
+        }
+
+        let mut enclosing = vec![0; types.len()];
+        for row in 1..=self.row_count(NESTED_CLASS) {
+            let nested = self.cell(NESTED_CLASS, row, 0)? as usize;
+            let outer = self.cell(NESTED_CLASS, row, 1)?;
+            if let Some(slot) = nested.checked_sub(1).and_then(|i| enclosing.get_mut(i)) {
+                *slot = outer;
+            }
+        }
+        for i in 0..types.len() {
+            let mut full_name = types[i].name.clone();
+            let mut current = i;
+            // the nesting depth is bounded by the number of types, which also guards against cycles.
+            for _ in 0..types.len() {
+                let Some(outer) = (enclosing[current] as usize).checked_sub(1) else {
+                    break;
+                };
+                let outer = outer.min(types.len() - 1);
+                full_name = format!("{}+{full_name}", types[outer].name);
+                current = outer;
+            }
+            let namespace = types[current].namespace.clone();
+            if !namespace.is_empty() {
+                full_name = format!("{namespace}.{full_name}");
+            }
+            types[i].namespace = namespace;
+            types[i].full_name = full_name;
+        }
+
+        Ok(AssemblyMetadata { assembly, types })
+    }
+
+    fn cell_u16(&self, table: usize, row: u32, column: usize) -> Result<u16, MetadataError> {
+        u16::try_from(self.cell(table, row, column)?).map_err(|_| MetadataError::Malformed)
+    }
+
+    /// Returns the 1-based rows of all methods annotated with `UnmanagedCallersOnlyAttribute`.
+    fn unmanaged_callers_only_methods(&self) -> Result<Vec<u32>, MetadataError> {
+        let mut methods = Vec::new();
+        for row in 1..=self.row_count(CUSTOM_ATTRIBUTE) {
+            let (parent_table, parent) = self.coded_cell(CUSTOM_ATTRIBUTE, row, 0)?;
+            if parent_table != METHOD_DEF {
+                continue;
+            }
+            let attribute_type = match self.coded_cell(CUSTOM_ATTRIBUTE, row, 1)? {
+                (MEMBER_REF, constructor) => match self.coded_cell(MEMBER_REF, constructor, 0)? {
+                    (TYPE_REF, ty) => Some((
+                        self.string_cell(TYPE_REF, ty, 2)?,
+                        self.string_cell(TYPE_REF, ty, 1)?,
+                    )),
+                    (TYPE_DEF, ty) => Some(self.type_def_name(ty)?),
+                    _ => None,
+                },
+                (METHOD_DEF, constructor) => self.declaring_type(constructor)?,
+                _ => None,
+            };
+            if attribute_type.is_some_and(|(namespace, name)| {
+                namespace == "System.Runtime.InteropServices"
+                    && name == "UnmanagedCallersOnlyAttribute"
+            }) {
+                methods.push(parent);
+            }
+        }
+        Ok(methods)
+    }
+
+    fn type_def_name(&self, row: u32) -> Result<(String, String), MetadataError> {
+        Ok((
+            self.string_cell(TYPE_DEF, row, 2)?,
+            self.string_cell(TYPE_DEF, row, 1)?,
+        ))
+    }
+
+    /// Returns the namespace and name of the type declaring the given method.
+    fn declaring_type(&self, method: u32) -> Result<Option<(String, String)>, MetadataError> {
+        let mut declaring = None;
+        for row in 1..=self.row_count(TYPE_DEF) {
+            if self.cell(TYPE_DEF, row, 5)? > method {
+                break;
+            }
+            declaring = Some(row);
+        }
+        declaring.map(|row| self.type_def_name(row)).transpose()
+    }
+}
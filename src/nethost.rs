@@ -1,7 +1,8 @@
 use crate::{
+    arch::{ArchitectureMismatchError, UnreadableLibraryError},
     bindings::{MAX_PATH, nethost::get_hostfxr_parameters},
     error::{HostingError, HostingResult, HostingSuccess},
-    hostfxr::Hostfxr,
+    hostfxr::{Hostfxr, LoadFromPathError},
     pdcstring::{self, PdCStr, PdUChar},
 };
 use std::{ffi::OsString, mem::MaybeUninit, ptr};
@@ -106,9 +107,35 @@ pub enum LoadHostfxrError {
     /// An error occured inside the hosting components.
     #[error(transparent)]
     Hosting(#[from] HostingError),
+    /// The located hostfxr library was built for a different architecture than the current process.
+    #[error(transparent)]
+    ArchitectureMismatch(#[from] ArchitectureMismatchError),
+    /// The header of the located hostfxr library could not be read to determine its architecture.
+    #[error(transparent)]
+    UnreadableLibrary(#[from] UnreadableLibraryError),
     /// An error occured while loading the hostfxr library.
     #[error(transparent)]
-    DlOpen(#[from] crate::dlopen2::Error),
+    DlOpen(crate::dlopen2::Error),
+    /// The path to the dotnet executable contains a nul character.
+    #[error(transparent)]
+    InvalidPath(#[from] crate::pdcstring::ContainsNul),
+}
+
+impl From<crate::dlopen2::Error> for LoadHostfxrError {
+    fn from(err: crate::dlopen2::Error) -> Self {
+        Self::DlOpen(err)
+    }
+}
+
+impl From<LoadFromPathError> for LoadHostfxrError {
+    fn from(err: LoadFromPathError) -> Self {
+        match err {
+            LoadFromPathError::ArchitectureMismatch(err) => Self::ArchitectureMismatch(err),
+            LoadFromPathError::UnreadableLibrary(err) => Self::UnreadableLibrary(err),
+            LoadFromPathError::DlOpen(err) => Self::DlOpen(err),
+            LoadFromPathError::InvalidPath(err) => Self::InvalidPath(err),
+        }
+    }
 }
 
 const unsafe fn maybe_uninit_slice_assume_init_ref<T>(slice: &[MaybeUninit<T>]) -> &[T] {
@@ -0,0 +1,416 @@
+use crate::{
+    metadata::AssemblyVersion,
+    pdcstring::{PdCStr, PdCString},
+};
+use std::{
+    fmt::{self, Write},
+    str::FromStr,
+};
+use thiserror::Error;
+
+/// Characters that have a special meaning in type and assembly names and are not supported in their parts.
+const RESERVED: &[char] = &[',', '[', ']', '+', '&', '*', '\\', '=', '`', '\0'];
+
+/// An assembly qualified type name like `Namespace.Outer+Nested, Assembly, Version=1.0.0.0`, as used to look up
+/// managed types and delegate types.
+///
+/// The name is validated when it is parsed or constructed, so invalid names are rejected before they are passed to
+/// the runtime. It can be passed wherever a type name is expected, as it implements [`AsRef<PdCStr>`].
+///
+/// Nested types are separated by `+` and generic types carry their arity as a `` `N `` suffix, e.g.
+/// ``Namespace.Cache`1+Entry``. Generic type arguments, pointers and arrays are not supported.
+///
+/// # Example
+/// ```
+/// # use netcorehost::{metadata::AssemblyVersion, type_name::{AssemblyName, AssemblyQualifiedName}};
+/// let name: AssemblyQualifiedName = "ExampleProject.Program, ExampleProject".parse()?;
+/// assert_eq!(name.namespace(), Some("ExampleProject"));
+/// assert_eq!(name.name(), "Program");
+///
+/// let assembly = AssemblyName::new("ExampleProject")?.with_version(AssemblyVersion::new(1, 0, 0, 0));
+/// let entry = AssemblyQualifiedName::new("ExampleProject.Cache`1", assembly)?.nested("Entry")?;
+/// assert_eq!(entry.generic_arity(), 1);
+/// assert_eq!(
+///     entry.to_string(),
+///     "ExampleProject.Cache`1+Entry, ExampleProject, Version=1.0.0.0"
+/// );
+/// # Ok::<(), netcorehost::type_name::TypeNameError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssemblyQualifiedName {
+    namespace: Option<String>,
+    types: Vec<TypeSegment>,
+    assembly: AssemblyName,
+    qualified_name: PdCString,
+}
+
+/// The name of a type without its namespace and its generic arity.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TypeSegment {
+    name: String,
+    generic_arity: u16,
+}
+
+impl AssemblyQualifiedName {
+    /// Creates a new [`AssemblyQualifiedName`] for the type with the given full name, e.g. `Namespace.Outer+Nested`,
+    /// in the given assembly.
+    pub fn new(full_name: &str, assembly: AssemblyName) -> Result<Self, TypeNameError> {
+        let (namespace, types) = parse_full_name(full_name)?;
+        Ok(Self::from_parts(namespace, types, assembly))
+    }
+
+    /// Returns the name of the type with the given name nested in this type. The name may end with a generic arity.
+    pub fn nested(&self, name: &str) -> Result<Self, TypeNameError> {
+        let segment = parse_segment(name, name)?;
+        let mut types = self.types.clone();
+        types.push(segment);
+        Ok(Self::from_parts(
+            self.namespace.clone(),
+            types,
+            self.assembly.clone(),
+        ))
+    }
+
+    fn from_parts(
+        namespace: Option<String>,
+        types: Vec<TypeSegment>,
+        assembly: AssemblyName,
+    ) -> Self {
+        let mut name = Self {
+            namespace,
+            types,
+            assembly,
+            qualified_name: PdCString::default(),
+        };
+        // all parts have been checked for reserved characters, which include nul.
+        name.qualified_name = PdCString::from_str(&name.to_string()).unwrap();
+        name
+    }
+
+    /// Returns the namespace of the type, or [`None`] if it is in the global namespace.
+    #[must_use]
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    /// Returns the name of the type without its namespace, enclosing types and generic arity.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.types.last().unwrap().name
+    }
+
+    /// Returns the names of the types enclosing this type, starting with the outermost one.
+    pub fn enclosing_types(&self) -> impl Iterator<Item = &str> {
+        let enclosing = &self.types[..self.types.len() - 1];
+        enclosing.iter().map(|segment| segment.name.as_str())
+    }
+
+    /// Returns the number of generic parameters of the type, including those declared by its enclosing types.
+    #[must_use]
+    pub fn generic_arity(&self) -> u16 {
+        self.types.iter().map(|segment| segment.generic_arity).sum()
+    }
+
+    /// Returns the full name of the type as used in type names, e.g. `Namespace.Outer+Nested`.
+    #[must_use]
+    pub fn full_name(&self) -> String {
+        let mut full_name = String::new();
+        if let Some(namespace) = &self.namespace {
+            full_name.push_str(namespace);
+            full_name.push('.');
+        }
+        for (i, segment) in self.types.iter().enumerate() {
+            if i > 0 {
+                full_name.push('+');
+            }
+            full_name.push_str(&segment.name);
+            if segment.generic_arity > 0 {
+                let _ = write!(full_name, "`{}", segment.generic_arity);
+            }
+        }
+        full_name
+    }
+
+    /// Returns the name of the assembly containing the type.
+    #[must_use]
+    pub const fn assembly(&self) -> &AssemblyName {
+        &self.assembly
+    }
+
+    /// Returns the assembly qualified name as a [`PdCStr`].
+    #[must_use]
+    pub fn as_pdcstr(&self) -> &PdCStr {
+        &self.qualified_name
+    }
+}
+
+impl FromStr for AssemblyQualifiedName {
+    type Err = TypeNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((full_name, assembly)) = s.split_once(',') else {
+            return Err(TypeNameError::MissingAssemblyName(s.to_string()));
+        };
+        Self::new(full_name.trim(), assembly.parse()?)
+    }
+}
+
+impl fmt::Display for AssemblyQualifiedName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, {}", self.full_name(), self.assembly)
+    }
+}
+
+impl AsRef<PdCStr> for AssemblyQualifiedName {
+    fn as_ref(&self) -> &PdCStr {
+        self.as_pdcstr()
+    }
+}
+
+/// The name of an assembly as used in assembly qualified type names, e.g.
+/// `Assembly, Version=1.0.0.0, Culture=neutral, PublicKeyToken=b77a5c561934e089`.
+///
+/// Only the `Version`, `Culture` and `PublicKeyToken` properties are supported. `PublicKeyToken=null` is treated
+/// like a missing public key token.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssemblyName {
+    name: String,
+    version: Option<AssemblyVersion>,
+    culture: Option<String>,
+    public_key_token: Option<[u8; 8]>,
+}
+
+impl AssemblyName {
+    /// Creates a new [`AssemblyName`] with the given simple name.
+    pub fn new(name: impl Into<String>) -> Result<Self, TypeNameError> {
+        let name = name.into();
+        if !is_valid_part(&name) {
+            return Err(TypeNameError::InvalidAssemblyName(name));
+        }
+        Ok(Self {
+            name,
+            version: None,
+            culture: None,
+            public_key_token: None,
+        })
+    }
+
+    /// Sets the version of the assembly.
+    #[must_use]
+    pub fn with_version(mut self, version: AssemblyVersion) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Sets the culture of the assembly, e.g. `en-US` or `neutral`.
+    pub fn with_culture(mut self, culture: impl Into<String>) -> Result<Self, TypeNameError> {
+        let culture = culture.into();
+        if !is_valid_part(&culture) || culture.contains(char::is_whitespace) {
+            return Err(TypeNameError::InvalidProperty {
+                property: "Culture",
+                value: culture,
+            });
+        }
+        self.culture = Some(culture);
+        Ok(self)
+    }
+
+    /// Sets the public key token of the assembly.
+    #[must_use]
+    pub fn with_public_key_token(mut self, public_key_token: [u8; 8]) -> Self {
+        self.public_key_token = Some(public_key_token);
+        self
+    }
+
+    /// Returns the simple name of the assembly.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the version of the assembly, if specified.
+    #[must_use]
+    pub const fn version(&self) -> Option<AssemblyVersion> {
+        self.version
+    }
+
+    /// Returns the culture of the assembly, if specified.
+    #[must_use]
+    pub fn culture(&self) -> Option<&str> {
+        self.culture.as_deref()
+    }
+
+    /// Returns the public key token of the assembly, if specified.
+    #[must_use]
+    pub const fn public_key_token(&self) -> Option<[u8; 8]> {
+        self.public_key_token
+    }
+}
+
+impl FromStr for AssemblyName {
+    type Err = TypeNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(',');
+        let mut assembly = Self::new(parts.next().unwrap_or_default().trim())?;
+        let mut seen = Vec::new();
+        for part in parts {
+            let Some((key, value)) = part.split_once('=') else {
+                return Err(TypeNameError::InvalidAssemblyName(s.trim().to_string()));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let property = ["Version", "Culture", "PublicKeyToken"]
+                .into_iter()
+                .find(|property| property.eq_ignore_ascii_case(key))
+                .ok_or_else(|| TypeNameError::UnknownProperty(key.to_string()))?;
+            if seen.contains(&property) {
+                return Err(TypeNameError::DuplicateProperty(property));
+            }
+            seen.push(property);
+
+            let invalid = || TypeNameError::InvalidProperty {
+                property,
+                value: value.to_string(),
+            };
+            match property {
+                "Version" => {
+                    assembly.version = Some(parse_version(value).ok_or_else(invalid)?);
+                }
+                "Culture" => assembly = assembly.with_culture(value)?,
+                _ if value.eq_ignore_ascii_case("null") => assembly.public_key_token = None,
+                _ => {
+                    assembly.public_key_token =
+                        Some(parse_public_key_token(value).ok_or_else(invalid)?);
+                }
+            }
+        }
+        Ok(assembly)
+    }
+}
+
+impl fmt::Display for AssemblyName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if let Some(version) = self.version {
+            write!(f, ", Version={version}")?;
+        }
+        if let Some(culture) = &self.culture {
+            write!(f, ", Culture={culture}")?;
+        }
+        if let Some(token) = self.public_key_token {
+            f.write_str(", PublicKeyToken=")?;
+            for byte in token {
+                write!(f, "{byte:02x}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Enum for errors that can occur while parsing or constructing an [`AssemblyQualifiedName`].
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeNameError {
+    /// The type name is not followed by the name of an assembly.
+    #[error("The type name `{0}` is not qualified with an assembly name.")]
+    MissingAssemblyName(String),
+
+    /// The name of the type, its namespace or one of its enclosing types is empty or contains reserved characters.
+    #[error("`{0}` is not a valid type name.")]
+    InvalidTypeName(String),
+
+    /// The generic arity following a type name is not a positive number.
+    #[error("`{0}` does not have a valid generic arity.")]
+    InvalidGenericArity(String),
+
+    /// The simple name of the assembly is empty or contains reserved characters.
+    #[error("`{0}` is not a valid assembly name.")]
+    InvalidAssemblyName(String),
+
+    /// The assembly name contains a property other than `Version`, `Culture` and `PublicKeyToken`.
+    #[error("Unknown assembly name property `{0}`.")]
+    UnknownProperty(String),
+
+    /// The assembly name contains a property more than once.
+    #[error("The assembly name property `{0}` is specified more than once.")]
+    DuplicateProperty(&'static str),
+
+    /// The value of an assembly name property is invalid.
+    #[error("`{value}` is not a valid value for the assembly name property `{property}`.")]
+    InvalidProperty {
+        /// The name of the property.
+        property: &'static str,
+        /// The invalid value.
+        value: String,
+    },
+}
+
+fn is_valid_part(part: &str) -> bool {
+    !part.is_empty() && part.trim() == part && !part.contains(RESERVED)
+}
+
+fn parse_full_name(full_name: &str) -> Result<(Option<String>, Vec<TypeSegment>), TypeNameError> {
+    let invalid = || TypeNameError::InvalidTypeName(full_name.to_string());
+    let mut parts = full_name.split('+');
+    let outermost = parts.next().unwrap_or_default();
+    let (namespace, outermost) = match outermost.rsplit_once('.') {
+        Some((namespace, name)) => {
+            if !namespace.split('.').all(is_valid_part) {
+                return Err(invalid());
+            }
+            (Some(namespace.to_string()), name)
+        }
+        None => (None, outermost),
+    };
+
+    let mut types = vec![parse_segment(outermost, full_name)?];
+    for nested in parts {
+        types.push(parse_segment(nested, full_name)?);
+    }
+    Ok((namespace, types))
+}
+
+fn parse_segment(segment: &str, full_name: &str) -> Result<TypeSegment, TypeNameError> {
+    let (name, generic_arity) = match segment.split_once('`') {
+        Some((name, arity)) => {
+            let arity = Some(arity)
+                .filter(|arity| {
+                    arity.bytes().all(|b| b.is_ascii_digit()) && !arity.starts_with('0')
+                })
+                .and_then(|arity| arity.parse::<u16>().ok())
+                .ok_or_else(|| TypeNameError::InvalidGenericArity(full_name.to_string()))?;
+            (name, arity)
+        }
+        None => (segment, 0),
+    };
+    if !is_valid_part(name) || name.contains('.') {
+        return Err(TypeNameError::InvalidTypeName(full_name.to_string()));
+    }
+    Ok(TypeSegment {
+        name: name.to_string(),
+        generic_arity,
+    })
+}
+
+fn parse_version(version: &str) -> Option<AssemblyVersion> {
+    let parts = version
+        .split('.')
+        .map(|part| part.parse::<u16>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if !(2..=4).contains(&parts.len()) {
+        return None;
+    }
+    let part = |i: usize| parts.get(i).copied().unwrap_or_default();
+    Some(AssemblyVersion::new(part(0), part(1), part(2), part(3)))
+}
+
+fn parse_public_key_token(token: &str) -> Option<[u8; 8]> {
+    // `from_str_radix` also accepts a leading sign, so the digits are checked up front.
+    if token.len() != 16 || !token.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut bytes = [0u8; 8];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&token[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
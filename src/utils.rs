@@ -10,10 +10,16 @@
 /// See <https://github.com/OpenByteDev/netcorehost/issues/38> for more details.
 pub mod altstack {
     use libc::{
-        MAP_ANON, MAP_FAILED, MAP_PRIVATE, PROT_READ, PROT_WRITE, SS_DISABLE, mmap, sigaltstack,
-        stack_t,
+        MAP_ANON, MAP_FAILED, MAP_PRIVATE, PROT_READ, PROT_WRITE, SS_DISABLE, SS_ONSTACK, c_void,
+        mmap, munmap, sigaltstack, stack_t,
+    };
+    use std::{
+        io,
+        marker::PhantomData,
+        mem::MaybeUninit,
+        ptr,
+        sync::atomic::{AtomicUsize, Ordering},
     };
-    use std::{io, mem::MaybeUninit, ptr};
 
     /// Represents the desired configuration of the alternate signal stack.
     #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
@@ -28,6 +34,9 @@ pub mod altstack {
         },
     }
 
+    /// An altstack size which is large enough for the exception handling of the CoreCLR.
+    pub const RECOMMENDED_SIZE: usize = 16 * 1024;
+
     impl Default for State {
         fn default() -> Self {
             Self::Enabled { size: 8 * 1024 }
@@ -35,6 +44,9 @@ pub mod altstack {
     }
 
     /// Configures the alternate signal stack according to the provided status.
+    ///
+    /// The stack mapped by this function is never freed and the previous stack is not restored.
+    /// Use an [`AltStackGuard`] if the configuration should only apply for a limited time.
     pub fn set(state: State) -> io::Result<()> {
         match state {
             State::Disabled => {
@@ -102,4 +114,662 @@ pub mod altstack {
 
         Ok(state)
     }
+
+    /// A guard which configures the alternate signal stack of the current thread and restores the previous
+    /// configuration on drop, freeing the stack it mapped.
+    ///
+    /// If the alternate signal stack was reconfigured by someone else in the meantime, it is left untouched on drop
+    /// and the stack mapped by the guard is leaked, as it may still be referenced.
+    ///
+    /// The guard is bound to the thread it was created on.
+    #[derive(Debug)]
+    #[must_use = "the previous altstack is restored when the guard is dropped"]
+    pub struct AltStackGuard {
+        previous: stack_t,
+        mapping: Option<(*mut c_void, usize)>,
+        not_send: PhantomData<*const ()>,
+    }
+
+    impl AltStackGuard {
+        /// Configures the alternate signal stack of the current thread according to the provided state until the
+        /// returned guard is dropped.
+        pub fn install(state: State) -> io::Result<Self> {
+            let (new, mapping) = match state {
+                State::Disabled => (
+                    stack_t {
+                        ss_flags: SS_DISABLE,
+                        ss_sp: ptr::null_mut(),
+                        ss_size: 0,
+                    },
+                    None,
+                ),
+                State::Enabled { size } => {
+                    let ptr = unsafe {
+                        mmap(
+                            ptr::null_mut(),
+                            size,
+                            PROT_READ | PROT_WRITE,
+                            MAP_PRIVATE | MAP_ANON,
+                            -1,
+                            0,
+                        )
+                    };
+                    if ptr == MAP_FAILED {
+                        return Err(io::Error::last_os_error());
+                    }
+                    (
+                        stack_t {
+                            ss_sp: ptr,
+                            ss_size: size,
+                            ss_flags: 0,
+                        },
+                        Some((ptr, size)),
+                    )
+                }
+            };
+
+            let mut previous = MaybeUninit::uninit();
+            let result = unsafe { sigaltstack(&raw const new, previous.as_mut_ptr()) };
+            if result != 0 {
+                let err = io::Error::last_os_error();
+                if let Some((ptr, size)) = mapping {
+                    unsafe { munmap(ptr, size) };
+                }
+                return Err(err);
+            }
+
+            let mut previous = unsafe { previous.assume_init() };
+            // the flags reported for the current stack are not valid for setting it.
+            previous.ss_flags &= !SS_ONSTACK;
+            Ok(Self {
+                previous,
+                mapping,
+                not_send: PhantomData,
+            })
+        }
+    }
+
+    impl AltStackGuard {
+        /// Returns whether the configuration installed by this guard is still the current one.
+        fn is_current(&self) -> bool {
+            let mut current = MaybeUninit::uninit();
+            let result = unsafe { sigaltstack(ptr::null(), current.as_mut_ptr()) };
+            if result != 0 {
+                return false;
+            }
+
+            let current = unsafe { current.assume_init() };
+            match self.mapping {
+                Some((ptr, size)) => {
+                    current.ss_flags & SS_DISABLE == 0
+                        && current.ss_sp == ptr
+                        && current.ss_size == size
+                }
+                None => current.ss_flags & SS_DISABLE != 0,
+            }
+        }
+    }
+
+    impl Drop for AltStackGuard {
+        fn drop(&mut self) {
+            // the altstack was changed by someone else since the guard was installed (e.g. by the runtime or a
+            // guard which is still alive), which may still refer to our stack, so leave it and leak the mapping.
+            if !self.is_current() {
+                return;
+            }
+
+            let result = unsafe { sigaltstack(&raw const self.previous, ptr::null_mut()) };
+            // if the previous stack could not be restored, ours may still be in use and must not be unmapped.
+            if result == 0
+                && let Some((ptr, size)) = self.mapping
+            {
+                unsafe { munmap(ptr, size) };
+            }
+        }
+    }
+
+    /// Encodes the state installed automatically: 0 for none, `usize::MAX` for [`State::Disabled`] and the size
+    /// for [`State::Enabled`].
+    static AUTO_INSTALL: AtomicUsize = AtomicUsize::new(0);
+
+    #[cfg(feature = "netcore3_0")]
+    thread_local! {
+        static AUTO_GUARD: std::cell::RefCell<Option<AltStackGuard>> = const { std::cell::RefCell::new(None) };
+    }
+
+    /// Sets the alternate signal stack configuration which is installed automatically on every thread that
+    /// initializes a host context, runs an app or loads a managed function through this crate, or [`None`] to stop
+    /// installing it on further threads (the default).
+    ///
+    /// The configuration is installed once per thread and the previous configuration is restored when the thread
+    /// exits. Installation is best-effort, failures are ignored.
+    ///
+    /// This has to be configured before the runtime is initialized to take effect on the initializing thread.
+    /// [`State::Enabled`] with [`RECOMMENDED_SIZE`] is a suitable configuration for the CoreCLR.
+    ///
+    /// The setting is process-wide rather than a parameter of a [`Hostfxr`](crate::hostfxr::Hostfxr) or a context,
+    /// as the altstack is a per-thread property which matters for the runtime as a whole: there is only one CoreCLR
+    /// per process and every thread that enters it, no matter through which handle, needs a large enough altstack.
+    pub fn set_auto_install(state: Option<State>) {
+        let encoded = match state {
+            None => 0,
+            Some(State::Disabled) => usize::MAX,
+            Some(State::Enabled { size }) => size.clamp(1, usize::MAX - 1),
+        };
+        AUTO_INSTALL.store(encoded, Ordering::Relaxed);
+    }
+
+    /// Returns the configuration set using [`set_auto_install`].
+    #[must_use]
+    pub fn auto_install() -> Option<State> {
+        match AUTO_INSTALL.load(Ordering::Relaxed) {
+            0 => None,
+            usize::MAX => Some(State::Disabled),
+            size => Some(State::Enabled { size }),
+        }
+    }
+
+    /// Installs the configuration set using [`set_auto_install`] on the current thread if it was not installed yet.
+    #[cfg(feature = "netcore3_0")]
+    pub(crate) fn install_automatically() {
+        let Some(state) = auto_install() else {
+            return;
+        };
+        AUTO_GUARD.with(|guard| {
+            let mut guard = guard.borrow_mut();
+            if guard.is_none() {
+                *guard = AltStackGuard::install(state).ok();
+            }
+        });
+    }
+}
+
+/// Utilities for coordinating signal handlers of the host with the ones installed by the CoreCLR on Unix platforms.
+///
+/// The CoreCLR installs handlers for signals like `SIGSEGV`, `SIGINT` and `SIGTERM` when it starts, replacing handlers
+/// the host installed earlier (the CoreCLR invokes them itself for signals it does not handle). Handlers installed by
+/// the host afterwards in turn replace the ones of the CoreCLR, which breaks e.g. the translation of `SIGSEGV` into a
+/// `NullReferenceException`.
+///
+/// A [`SignalSnapshot`](signals::SignalSnapshot) taken before the runtime is started can be used to find out which
+/// handlers the runtime replaced and to decide how the handlers of the host and the runtime coexist using a
+/// [`ChainPolicy`](signals::ChainPolicy).
+pub mod signals {
+    use libc::{
+        SA_NODEFER, SA_ONSTACK, SA_RESTART, SA_SIGINFO, SIG_DFL, SIG_IGN, SIGABRT, SIGBUS, SIGCHLD,
+        SIGCONT, SIGFPE, SIGILL, SIGINT, SIGQUIT, SIGSEGV, SIGTERM, SIGTRAP, c_int, c_void,
+        sigaction, sighandler_t, siginfo_t,
+    };
+    use std::{
+        fmt::{self, Debug},
+        io, mem,
+        ptr::{self, NonNull},
+        sync::atomic::{AtomicPtr, Ordering},
+    };
+
+    /// The signals the CoreCLR installs handlers for.
+    pub const RUNTIME_SIGNALS: &[c_int] = &[
+        SIGILL, SIGTRAP, SIGFPE, SIGBUS, SIGSEGV, SIGINT, SIGQUIT, SIGTERM, SIGABRT, SIGCHLD,
+        SIGCONT,
+    ];
+
+    /// The number of signals which can be chained.
+    const MAX_SIGNALS: usize = 65;
+
+    /// The disposition of a signal, i.e. the action installed using `sigaction`.
+    #[derive(Clone, Copy)]
+    pub struct SignalHandler(sigaction);
+
+    impl SignalHandler {
+        /// Returns the handler currently installed for the given signal.
+        pub fn current(signal: c_int) -> io::Result<Self> {
+            let mut action = unsafe { mem::zeroed::<sigaction>() };
+            let result = unsafe { libc::sigaction(signal, ptr::null(), &raw mut action) };
+            if result != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self(action))
+        }
+
+        /// Creates a handler from a raw `sigaction`.
+        #[must_use]
+        pub const fn from_raw(action: sigaction) -> Self {
+            Self(action)
+        }
+
+        /// Returns the underlying `sigaction`.
+        #[must_use]
+        pub const fn as_raw(&self) -> &sigaction {
+            &self.0
+        }
+
+        /// Returns the address of the handler function, or `SIG_DFL` or `SIG_IGN`.
+        #[must_use]
+        pub const fn address(&self) -> sighandler_t {
+            self.0.sa_sigaction
+        }
+
+        /// Returns the flags the handler was installed with.
+        #[must_use]
+        pub const fn flags(&self) -> c_int {
+            self.0.sa_flags
+        }
+
+        /// Returns whether this is the default disposition of the signal.
+        #[must_use]
+        pub const fn is_default(&self) -> bool {
+            self.address() == SIG_DFL
+        }
+
+        /// Returns whether the signal is ignored.
+        #[must_use]
+        pub const fn is_ignored(&self) -> bool {
+            self.address() == SIG_IGN
+        }
+
+        /// Installs this handler for the given signal and returns the previously installed handler.
+        ///
+        /// # Safety
+        /// The handler has to be safe to invoke for the given signal.
+        pub unsafe fn install(&self, signal: c_int) -> io::Result<Self> {
+            let mut previous = unsafe { mem::zeroed::<sigaction>() };
+            let result = unsafe { libc::sigaction(signal, &raw const self.0, &raw mut previous) };
+            if result != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self(previous))
+        }
+
+        /// Invokes the handler function. Does nothing for the default and the ignore disposition.
+        unsafe fn invoke(&self, signal: c_int, info: *mut siginfo_t, context: *mut c_void) {
+            if self.is_default() || self.is_ignored() {
+                return;
+            }
+            if self.flags() & SA_SIGINFO != 0 {
+                let handler: extern "C" fn(c_int, *mut siginfo_t, *mut c_void) =
+                    unsafe { mem::transmute(self.address()) };
+                handler(signal, info, context);
+            } else {
+                let handler: extern "C" fn(c_int) = unsafe { mem::transmute(self.address()) };
+                handler(signal);
+            }
+        }
+    }
+
+    impl PartialEq for SignalHandler {
+        fn eq(&self, other: &Self) -> bool {
+            self.address() == other.address() && self.flags() == other.flags()
+        }
+    }
+
+    impl Eq for SignalHandler {}
+
+    impl Debug for SignalHandler {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("SignalHandler")
+                .field("address", &(self.address() as *const c_void))
+                .field("flags", &self.flags())
+                .finish_non_exhaustive()
+        }
+    }
+
+    /// A handler which was replaced since a [`SignalSnapshot`] was taken.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct SignalChange {
+        /// The signal.
+        pub signal: c_int,
+        /// The handler at the time the snapshot was taken.
+        pub before: SignalHandler,
+        /// The handler installed now.
+        pub after: SignalHandler,
+    }
+
+    /// Decides how the handlers of the host and the ones installed by the runtime coexist, see
+    /// [`SignalSnapshot::apply`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum ChainPolicy {
+        /// Keep the handlers installed by the runtime. The CoreCLR invokes the handlers it replaced itself for
+        /// signals it does not handle, e.g. a `SIGSEGV` outside of managed code.
+        Runtime,
+        /// Reinstall the handlers of the host, which disables the handling of these signals by the runtime.
+        Host,
+        /// Invoke the handler of the host and then the one installed by the runtime.
+        ///
+        /// Note that the CoreCLR still forwards signals it does not handle to the handler it replaced, i.e. the one
+        /// of the host, which is then invoked a second time. There is no policy invoking the runtime before the
+        /// host, as that is what [`ChainPolicy::Runtime`] already does.
+        HostThenRuntime,
+    }
+
+    /// The handlers installed for a set of signals at a point in time.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct SignalSnapshot {
+        handlers: Vec<(c_int, SignalHandler)>,
+    }
+
+    impl SignalSnapshot {
+        /// Captures the handlers currently installed for the given signals.
+        pub fn capture(signals: &[c_int]) -> io::Result<Self> {
+            let handlers = signals
+                .iter()
+                .map(|&signal| SignalHandler::current(signal).map(|handler| (signal, handler)))
+                .collect::<io::Result<_>>()?;
+            Ok(Self { handlers })
+        }
+
+        /// Captures the handlers currently installed for the [signals the CoreCLR handles](RUNTIME_SIGNALS).
+        /// This should be called before the runtime is started.
+        pub fn capture_runtime_signals() -> io::Result<Self> {
+            Self::capture(RUNTIME_SIGNALS)
+        }
+
+        /// Returns the handler captured for the given signal.
+        #[must_use]
+        pub fn get(&self, signal: c_int) -> Option<&SignalHandler> {
+            self.handlers
+                .iter()
+                .find(|(s, _)| *s == signal)
+                .map(|(_, handler)| handler)
+        }
+
+        /// Returns the signals and handlers in this snapshot.
+        pub fn iter(&self) -> impl Iterator<Item = (c_int, &SignalHandler)> {
+            self.handlers
+                .iter()
+                .map(|(signal, handler)| (*signal, handler))
+        }
+
+        /// Compares the captured handlers with the currently installed ones and returns the ones which were
+        /// replaced.
+        pub fn changes(&self) -> io::Result<Vec<SignalChange>> {
+            let mut changes = Vec::new();
+            for &(signal, before) in &self.handlers {
+                let after = SignalHandler::current(signal)?;
+                if before != after {
+                    changes.push(SignalChange {
+                        signal,
+                        before,
+                        after,
+                    });
+                }
+            }
+            Ok(changes)
+        }
+
+        /// Reinstalls all captured handlers.
+        ///
+        /// # Safety
+        /// The captured handlers have to still be safe to invoke.
+        pub unsafe fn restore(&self) -> io::Result<()> {
+            for (signal, handler) in &self.handlers {
+                unsafe { handler.install(*signal) }?;
+            }
+            Ok(())
+        }
+
+        /// Applies the given policy to all handlers which were replaced since this snapshot was taken and returns
+        /// the affected changes.
+        ///
+        /// For the chaining policies, a dispatcher is installed which invokes both handlers in the given order.
+        /// Handlers which are the default or the ignore disposition are skipped by the dispatcher. The dispatcher is
+        /// installed with the signal mask of the runtime handler and keeps the `SA_ONSTACK`, `SA_RESTART` and
+        /// `SA_NODEFER` flags if either handler was installed with them.
+        ///
+        /// # Safety
+        /// The captured handlers have to still be safe to invoke.
+        pub unsafe fn apply(&self, policy: ChainPolicy) -> io::Result<Vec<SignalChange>> {
+            let changes = self.changes()?;
+            for change in &changes {
+                match policy {
+                    ChainPolicy::Runtime => {}
+                    ChainPolicy::Host => {
+                        unsafe { change.before.install(change.signal) }?;
+                    }
+                    ChainPolicy::HostThenRuntime => {
+                        unsafe { install_chain(change.signal, change.before, change.after) }?;
+                    }
+                }
+            }
+            Ok(changes)
+        }
+    }
+
+    /// The handlers invoked by the dispatcher for a signal, in order.
+    struct Chain([SignalHandler; 2]);
+
+    static CHAINS: [AtomicPtr<Chain>; MAX_SIGNALS] =
+        [const { AtomicPtr::new(ptr::null_mut()) }; MAX_SIGNALS];
+
+    unsafe fn install_chain(
+        signal: c_int,
+        first: SignalHandler,
+        second: SignalHandler,
+    ) -> io::Result<()> {
+        let slot = usize::try_from(signal)
+            .ok()
+            .and_then(|index| CHAINS.get(index))
+            .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+
+        // chains are leaked as the dispatcher may still be running on another thread when a chain is replaced.
+        let chain = Box::into_raw(Box::new(Chain([first, second])));
+        slot.store(chain, Ordering::Release);
+
+        let mut action = second.0;
+        action.sa_sigaction = dispatch as *const () as sighandler_t;
+        action.sa_flags =
+            (first.flags() | second.flags()) & (SA_ONSTACK | SA_RESTART | SA_NODEFER) | SA_SIGINFO;
+        unsafe { SignalHandler(action).install(signal) }?;
+        Ok(())
+    }
+
+    extern "C" fn dispatch(signal: c_int, info: *mut siginfo_t, context: *mut c_void) {
+        let chain = usize::try_from(signal)
+            .ok()
+            .and_then(|index| CHAINS.get(index))
+            .and_then(|slot| NonNull::new(slot.load(Ordering::Acquire)));
+        if let Some(chain) = chain {
+            for handler in &unsafe { chain.as_ref() }.0 {
+                unsafe { handler.invoke(signal, info, context) };
+            }
+        }
+    }
+}
+
+/// Utilities for redirecting the stdout and stderr of the process, e.g. to capture the console output of managed
+/// code in a GUI application or service where these file descriptors are used for something else.
+///
+/// The redirection applies to the whole process, including Rust code writing to stdout or stderr.
+pub mod stdio {
+    use std::{
+        io::{self, PipeReader, Read, Write},
+        os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+        sync::atomic::{AtomicBool, Ordering},
+        thread,
+    };
+
+    /// A standard stream of the process.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Stream {
+        /// The standard output stream (file descriptor 1).
+        Stdout,
+        /// The standard error stream (file descriptor 2).
+        Stderr,
+    }
+
+    impl Stream {
+        /// Returns the file descriptor of the stream.
+        #[must_use]
+        pub const fn fd(self) -> RawFd {
+            match self {
+                Self::Stdout => libc::STDOUT_FILENO,
+                Self::Stderr => libc::STDERR_FILENO,
+            }
+        }
+
+        fn flush(self) {
+            let _ = match self {
+                Self::Stdout => io::stdout().flush(),
+                Self::Stderr => io::stderr().flush(),
+            };
+        }
+    }
+
+    static REDIRECTED: AtomicBool = AtomicBool::new(false);
+
+    struct RedirectedStream {
+        stream: Stream,
+        original: OwnedFd,
+        reader: Option<PipeReader>,
+    }
+
+    /// An active redirection of stdout and/or stderr into pipes.
+    ///
+    /// The original file descriptors are restored when the redirection is [finished](Redirection::finish) or
+    /// dropped, which also closes the write end of the pipes so that the readers reach the end of the stream.
+    ///
+    /// The pipes have a limited capacity, so their readers have to be drained while the redirection is active if a
+    /// lot of output is expected, as writes block once a pipe is full. [`capture`] takes care of this.
+    pub struct Redirection {
+        streams: Vec<RedirectedStream>,
+    }
+
+    impl Redirection {
+        /// Redirects the given streams into pipes until the returned redirection is finished or dropped.
+        ///
+        /// # Errors
+        /// Returns an error with kind [`io::ErrorKind::ResourceBusy`] if another redirection is active, or if a pipe
+        /// could not be created or a file descriptor could not be duplicated.
+        pub fn start(streams: &[Stream]) -> io::Result<Self> {
+            if REDIRECTED.swap(true, Ordering::AcqRel) {
+                return Err(io::Error::new(
+                    io::ErrorKind::ResourceBusy,
+                    "stdio is already redirected",
+                ));
+            }
+
+            let mut redirection = Self {
+                streams: Vec::with_capacity(streams.len()),
+            };
+            for &stream in streams {
+                // on failure, the streams redirected so far are restored when `redirection` is dropped.
+                redirection.streams.push(RedirectedStream::start(stream)?);
+            }
+            Ok(redirection)
+        }
+
+        /// Takes the reader for the given stream. Returns [`None`] if the stream is not redirected or its reader was
+        /// already taken.
+        pub fn take_reader(&mut self, stream: Stream) -> Option<PipeReader> {
+            self.streams
+                .iter_mut()
+                .find(|s| s.stream == stream)
+                .and_then(|s| s.reader.take())
+        }
+
+        /// Restores the original file descriptors.
+        pub fn finish(mut self) -> io::Result<()> {
+            self.restore()
+        }
+
+        fn restore(&mut self) -> io::Result<()> {
+            let mut result = Ok(());
+            for stream in self.streams.drain(..).rev() {
+                if let Err(err) = stream.restore() {
+                    result = result.and(Err(err));
+                }
+            }
+            REDIRECTED.store(false, Ordering::Release);
+            result
+        }
+    }
+
+    impl Drop for Redirection {
+        fn drop(&mut self) {
+            let _ = self.restore();
+        }
+    }
+
+    impl RedirectedStream {
+        fn start(stream: Stream) -> io::Result<Self> {
+            let (reader, writer) = io::pipe()?;
+
+            stream.flush();
+            let original = unsafe { libc::dup(stream.fd()) };
+            if original == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            let original = unsafe { OwnedFd::from_raw_fd(original) };
+
+            if unsafe { libc::dup2(writer.as_raw_fd(), stream.fd()) } == -1 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(Self {
+                stream,
+                original,
+                reader: Some(reader),
+            })
+        }
+
+        fn restore(self) -> io::Result<()> {
+            self.stream.flush();
+            if unsafe { libc::dup2(self.original.as_raw_fd(), self.stream.fd()) } == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+    }
+
+    /// The output captured using [`capture`].
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct CapturedOutput {
+        /// Everything written to stdout.
+        pub stdout: Vec<u8>,
+        /// Everything written to stderr.
+        pub stderr: Vec<u8>,
+    }
+
+    impl CapturedOutput {
+        /// Returns a reader over the captured stdout.
+        #[must_use]
+        pub fn stdout_reader(&self) -> impl Read + '_ {
+            self.stdout.as_slice()
+        }
+
+        /// Returns a reader over the captured stderr.
+        #[must_use]
+        pub fn stderr_reader(&self) -> impl Read + '_ {
+            self.stderr.as_slice()
+        }
+    }
+
+    /// Redirects stdout and stderr while running the given function (e.g. a call to
+    /// [`run_app`](crate::hostfxr::HostfxrContext::run_app) or to a managed function) and returns everything written
+    /// to them in the meantime.
+    ///
+    /// The pipes are drained on background threads, so the function can write any amount of output.
+    pub fn capture<R>(f: impl FnOnce() -> R) -> io::Result<(R, CapturedOutput)> {
+        let mut redirection = Redirection::start(&[Stream::Stdout, Stream::Stderr])?;
+        let stdout = drain(redirection.take_reader(Stream::Stdout));
+        let stderr = drain(redirection.take_reader(Stream::Stderr));
+
+        let result = f();
+        redirection.finish()?;
+
+        let output = CapturedOutput {
+            stdout: stdout.join().unwrap_or_else(|_| Ok(Vec::new()))?,
+            stderr: stderr.join().unwrap_or_else(|_| Ok(Vec::new()))?,
+        };
+        Ok((result, output))
+    }
+
+    fn drain(reader: Option<PipeReader>) -> thread::JoinHandle<io::Result<Vec<u8>>> {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            if let Some(mut reader) = reader {
+                reader.read_to_end(&mut buffer)?;
+            }
+            Ok(buffer)
+        })
+    }
 }
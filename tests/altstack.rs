@@ -0,0 +1,57 @@
+#![cfg(all(unix, feature = "utils"))]
+
+use netcorehost::utils::altstack::{self, AltStackGuard, State};
+use std::thread;
+
+#[test]
+fn guard_restores_previous_altstack() {
+    thread::spawn(|| {
+        let previous = altstack::get().unwrap();
+
+        let guard = AltStackGuard::install(State::Enabled { size: 64 * 1024 }).unwrap();
+        assert_eq!(altstack::get().unwrap(), State::Enabled { size: 64 * 1024 });
+        drop(guard);
+        assert_eq!(altstack::get().unwrap(), previous);
+
+        let guard = AltStackGuard::install(State::Disabled).unwrap();
+        assert_eq!(altstack::get().unwrap(), State::Disabled);
+        drop(guard);
+        assert_eq!(altstack::get().unwrap(), previous);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn nested_guards() {
+    thread::spawn(|| {
+        let previous = altstack::get().unwrap();
+        let outer = AltStackGuard::install(State::Enabled { size: 32 * 1024 }).unwrap();
+        let inner = AltStackGuard::install(State::Enabled { size: 64 * 1024 }).unwrap();
+        drop(inner);
+        assert_eq!(altstack::get().unwrap(), State::Enabled { size: 32 * 1024 });
+        drop(outer);
+        assert_eq!(altstack::get().unwrap(), previous);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn guard_leaves_altstack_changed_by_others() {
+    thread::spawn(|| {
+        let guard = AltStackGuard::install(State::Enabled { size: 32 * 1024 }).unwrap();
+        altstack::set(State::Enabled { size: 64 * 1024 }).unwrap();
+        drop(guard);
+        assert_eq!(altstack::get().unwrap(), State::Enabled { size: 64 * 1024 });
+
+        let outer = AltStackGuard::install(State::Enabled { size: 32 * 1024 }).unwrap();
+        let inner = AltStackGuard::install(State::Disabled).unwrap();
+        drop(outer);
+        assert_eq!(altstack::get().unwrap(), State::Disabled);
+        drop(inner);
+        assert_eq!(altstack::get().unwrap(), State::Enabled { size: 32 * 1024 });
+    })
+    .join()
+    .unwrap();
+}
@@ -0,0 +1,72 @@
+use netcorehost::{
+    error::HostingError,
+    hostfxr::{AppExit, AppExitClassification, AppOrHostingResult},
+};
+
+#[allow(clippy::cast_possible_wrap)]
+fn status(error: HostingError) -> AppOrHostingResult {
+    AppOrHostingResult::from(error.value() as i32)
+}
+
+#[test]
+fn exit_codes_outside_hosting_range() {
+    for mode in [
+        AppExitClassification::Heuristic,
+        AppExitClassification::Strict,
+    ] {
+        for code in [0, 1, 42, -1, i32::MIN] {
+            assert_eq!(
+                AppOrHostingResult::from(code).classify(true, mode),
+                AppExit::AppExited(code)
+            );
+        }
+    }
+}
+
+#[test]
+fn heuristic_mode() {
+    let mode = AppExitClassification::Heuristic;
+    assert_eq!(
+        status(HostingError::FrameworkMissingFailure).classify(false, mode),
+        AppExit::HostingFailed(HostingError::FrameworkMissingFailure)
+    );
+    assert_eq!(
+        AppOrHostingResult::from(0x8000_80FE_u32 as i32).classify(false, mode),
+        AppExit::AppExited(0x8000_80FE_u32 as i32)
+    );
+    assert_eq!(
+        AppOrHostingResult::from(0x8000_80FE_u32 as i32).classify(true, mode),
+        AppExit::HostingFailed(HostingError::Unknown(0x8000_80FE))
+    );
+}
+
+#[test]
+fn strict_mode() {
+    let mode = AppExitClassification::Strict;
+    let result = status(HostingError::FrameworkMissingFailure);
+    assert_eq!(
+        result.classify(false, mode),
+        AppExit::AppExited(result.value())
+    );
+    assert_eq!(
+        result.classify(true, mode),
+        AppExit::HostingFailed(HostingError::FrameworkMissingFailure)
+    );
+}
+
+#[test]
+fn accessors() {
+    let exited = AppExit::AppExited(3);
+    assert_eq!(exited.exit_code(), Some(3));
+    assert_eq!(exited.hosting_error(), None);
+    assert_eq!(exited.into_result(), Ok(3));
+    assert_eq!(AppOrHostingResult::from(exited).value(), 3);
+
+    let failed = AppExit::HostingFailed(HostingError::CoreClrInitFailure);
+    assert_eq!(failed.exit_code(), None);
+    assert_eq!(failed.into_result(), Err(HostingError::CoreClrInitFailure));
+    assert_eq!(
+        failed.as_hosting_exit_code().into_result(),
+        Err(HostingError::CoreClrInitFailure)
+    );
+}
@@ -0,0 +1,139 @@
+use netcorehost::{
+    arch::{
+        Architecture, ArchitectureMismatchError, CheckArchitectureError,
+        check_library_architecture, read_binary_architectures,
+    },
+    hostfxr::{Hostfxr, LoadFromPathError},
+};
+use std::{fs, path::PathBuf};
+
+fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("netcorehost-arch-{}-{name}", std::process::id()));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn elf(machine: u16) -> Vec<u8> {
+    let mut out = vec![0x7F, b'E', b'L', b'F', 2, 1, 1];
+    out.resize(18, 0);
+    out.extend_from_slice(&machine.to_le_bytes());
+    out.resize(64, 0);
+    out
+}
+
+fn pe(machine: u16) -> Vec<u8> {
+    let mut out = vec![b'M', b'Z'];
+    out.resize(0x3C, 0);
+    out.extend_from_slice(&0x80u32.to_le_bytes());
+    out.resize(0x80, 0);
+    out.extend_from_slice(b"PE\0\0");
+    out.extend_from_slice(&machine.to_le_bytes());
+    out.resize(0x100, 0);
+    out
+}
+
+fn fat_mach(cpu_types: &[u32]) -> Vec<u8> {
+    let mut out = vec![0xCA, 0xFE, 0xBA, 0xBE];
+    out.extend_from_slice(&u32::try_from(cpu_types.len()).unwrap().to_be_bytes());
+    for cpu_type in cpu_types {
+        out.extend_from_slice(&cpu_type.to_be_bytes());
+        out.extend_from_slice(&[0; 16]);
+    }
+    out
+}
+
+#[test]
+fn reads_binary_headers() {
+    let cases: [(&str, Vec<u8>, Vec<Architecture>); 5] = [
+        ("elf-x64", elf(62), vec![Architecture::X64]),
+        ("elf-arm64", elf(183), vec![Architecture::Arm64]),
+        ("pe-x86", pe(0x014C), vec![Architecture::X86]),
+        ("pe-arm64", pe(0xAA64), vec![Architecture::Arm64]),
+        (
+            "macho-universal",
+            fat_mach(&[0x0100_0007, 0x0100_000C]),
+            vec![Architecture::X64, Architecture::Arm64],
+        ),
+    ];
+
+    for (name, contents, expected) in cases {
+        let path = temp_file(name, &contents);
+        assert_eq!(
+            read_binary_architectures(&path).unwrap(),
+            expected,
+            "{name}"
+        );
+    }
+
+    let mut thin_mach = 0xFEED_FACFu32.to_le_bytes().to_vec();
+    thin_mach.extend_from_slice(&0x0100_000Cu32.to_le_bytes());
+    let path = temp_file("macho-thin", &thin_mach);
+    assert_eq!(
+        read_binary_architectures(&path).unwrap(),
+        [Architecture::Arm64]
+    );
+
+    let path = temp_file("text", b"not a binary at all");
+    assert!(read_binary_architectures(&path).is_err());
+}
+
+#[test]
+fn detects_mismatch() {
+    let current = Architecture::current().unwrap();
+    let other = if current == Architecture::Arm64 {
+        Architecture::X64
+    } else {
+        Architecture::Arm64
+    };
+    let machine = |arch| match arch {
+        Architecture::X64 => 62,
+        Architecture::Arm64 => 183,
+        _ => unreachable!("tests only run on x64 and arm64"),
+    };
+
+    let matching = temp_file("matching", &elf(machine(current)));
+    let mismatching = temp_file("mismatching", &elf(machine(other)));
+    check_library_architecture(&matching).unwrap();
+
+    let expected = ArchitectureMismatchError {
+        path: mismatching.clone(),
+        expected: current,
+        found: vec![other],
+    };
+    match check_library_architecture(&mismatching) {
+        Err(CheckArchitectureError::ArchitectureMismatch(err)) => assert_eq!(err, expected),
+        result => panic!("unexpected result: {result:?}"),
+    }
+
+    match Hostfxr::load_from_path(&mismatching) {
+        Err(LoadFromPathError::ArchitectureMismatch(err)) => assert_eq!(err, expected),
+        Err(err) => panic!("unexpected error: {err}"),
+        Ok(_) => panic!("library with a different architecture was loaded"),
+    }
+}
+
+#[test]
+fn parses_names() {
+    for arch in Architecture::ALL {
+        assert_eq!(arch.name().parse::<Architecture>(), Ok(arch));
+        assert_eq!(arch.to_string(), arch.name());
+    }
+    assert_eq!("ARM64".parse(), Ok(Architecture::Arm64));
+    assert!("mips".parse::<Architecture>().is_err());
+    assert_eq!(Architecture::X64.dotnet_root_env_var(), "DOTNET_ROOT_X64");
+}
+
+#[test]
+fn rejects_unreadable_headers() {
+    let empty = temp_file("empty", &[]);
+    match check_library_architecture(&empty) {
+        Err(CheckArchitectureError::UnreadableLibrary(err)) => assert_eq!(err.path, empty),
+        result => panic!("unexpected result: {result:?}"),
+    }
+
+    match Hostfxr::load_from_path(&empty) {
+        Err(LoadFromPathError::UnreadableLibrary(err)) => assert_eq!(err.path, empty),
+        Err(err) => panic!("unexpected error: {err}"),
+        Ok(_) => panic!("library with an unreadable header was loaded"),
+    }
+}
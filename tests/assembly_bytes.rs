@@ -0,0 +1,141 @@
+#![cfg(feature = "net8_0")]
+
+use netcorehost::{
+    error::HostingError,
+    hostfxr::{AssemblyBytes, Hostfxr, HostfxrContext, InitializedForRuntimeConfig},
+    include_assembly, pdcstr,
+};
+use netcorehost_mock::MockHostfxr;
+use rusty_fork::rusty_fork_test;
+
+mod common;
+
+// not an assembly, which the mock loads anyway.
+static EMBEDDED: AssemblyBytes<'static> = include_assembly!("../LICENSE", "../README.md");
+
+fn setup() -> (MockHostfxr, HostfxrContext<InitializedForRuntimeConfig>) {
+    let mock = MockHostfxr::load().unwrap();
+    mock.reset();
+    let hostfxr = Hostfxr::load_from_path(mock.path()).unwrap();
+    let context = hostfxr
+        .initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"))
+        .unwrap();
+    (mock, context)
+}
+
+fn load_calls(mock: &MockHostfxr) -> Vec<Vec<String>> {
+    mock.calls()
+        .into_iter()
+        .filter(|call| call.function == "load_assembly_bytes")
+        .map(|call| call.args)
+        .collect()
+}
+
+rusty_fork_test! {
+    #[test]
+    fn symbols_are_optional() {
+        let (mock, context) = setup();
+        let image = common::image::test_assembly();
+        let len = image.len().to_string();
+
+        context.load_assembly_from_bytes(&image, []).unwrap();
+        context.load_assembly_from_bytes(&image, [0u8; 4]).unwrap();
+        context.load_assembly_from_memory(AssemblyBytes::new(&image)).unwrap();
+        context
+            .load_assembly_from_memory(AssemblyBytes::with_symbols(&image, &[]))
+            .unwrap();
+        assert_eq!(
+            load_calls(&mock),
+            [[&len, "null"], [&len, "4"], [&len, "null"], [&len, "0"]]
+        );
+    }
+
+    #[test]
+    fn loaded_assembly_qualifies_type_names() {
+        let (mock, context) = setup();
+        mock.register_closure("Test.Program, Test", "Main", |_args, size| size + 1);
+
+        let assembly = context
+            .load_assembly_from_bytes(common::image::test_assembly(), [])
+            .unwrap();
+        assert_eq!(assembly.name(), Some("Test"));
+        assert_eq!(
+            &*assembly.qualify_type_name(pdcstr!("Test.Program")),
+            pdcstr!("Test.Program, Test")
+        );
+        assert_eq!(
+            &*assembly.qualify_type_name(pdcstr!("Other.Type, Other")),
+            pdcstr!("Other.Type, Other")
+        );
+
+        let main = assembly
+            .get_function_with_default_signature(pdcstr!("Test.Program"), pdcstr!("Main"))
+            .unwrap();
+        assert_eq!(unsafe { main(std::ptr::null(), 41) }, 42);
+    }
+
+    #[test]
+    fn unreadable_metadata_does_not_block_loading() {
+        let (mock, context) = setup();
+        mock.register_closure("Test.Program, Test", "Main", |_args, size| size + 1);
+
+        let assembly = context.load_assembly_from_bytes([0u8; 16], []).unwrap();
+        assert_eq!(load_calls(&mock), [["16", "null"]]);
+        assert_eq!(assembly.name(), None);
+        assert!(assembly.metadata().is_none());
+        assert_eq!(
+            &*assembly.qualify_type_name(pdcstr!("Test.Program")),
+            pdcstr!("Test.Program")
+        );
+        let main = assembly
+            .get_function_with_default_signature(pdcstr!("Test.Program, Test"), pdcstr!("Main"))
+            .unwrap();
+        assert_eq!(unsafe { main(std::ptr::null(), 1) }, 2);
+
+        mock.set_status("load_assembly_bytes", HostingError::HostInvalidState.value());
+        let err = context.load_assembly_from_bytes([0u8; 16], []).unwrap_err();
+        assert_eq!(err, HostingError::HostInvalidState);
+    }
+
+    #[test]
+    fn load_multiple_assemblies() {
+        let (mock, context) = setup();
+        assert_eq!(EMBEDDED.assembly(), include_bytes!("../LICENSE"));
+        assert_eq!(EMBEDDED.symbols(), Some(&include_bytes!("../README.md")[..]));
+
+        let dependency = common::image::assembly_image("Dependency", [1, 0, 0, 0], &[]);
+        let image = common::image::test_assembly();
+        let assemblies = context
+            .load_assemblies_from_memory([
+                AssemblyBytes::from(&dependency),
+                AssemblyBytes::with_symbols(&image, b"symbols"),
+            ])
+            .unwrap();
+        let names: Vec<_> = assemblies.iter().map(|assembly| assembly.name()).collect();
+        assert_eq!(names, [Some("Dependency"), Some("Test")]);
+        assert_eq!(
+            load_calls(&mock),
+            [
+                [dependency.len().to_string(), "null".to_string()],
+                [image.len().to_string(), "7".to_string()],
+            ]
+        );
+
+        assert!(context.load_assemblies_from_memory([]).unwrap().is_empty());
+        assert_eq!(load_calls(&mock).len(), 2);
+
+        let assemblies = context
+            .load_assemblies_from_memory([AssemblyBytes::from(&image), EMBEDDED])
+            .unwrap();
+        assert_eq!(assemblies[1].name(), None);
+        assert_eq!(load_calls(&mock).len(), 4);
+
+        mock.set_status("load_assembly_bytes", HostingError::HostInvalidState.value());
+        let err = context
+            .load_assemblies_from_memory([AssemblyBytes::from(&image), AssemblyBytes::from(&image)])
+            .unwrap_err();
+        assert_eq!(err.index(), 0);
+        assert_eq!(err.error(), HostingError::HostInvalidState);
+        assert_eq!(load_calls(&mock).len(), 5);
+    }
+}
@@ -0,0 +1,188 @@
+#![cfg(feature = "bundle")]
+
+use flate2::{Compression, write::DeflateEncoder};
+use netcorehost::bundle::{Bundle, BundleError, BundleFileType};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+const BUNDLE_SIGNATURE: [u8; 32] = [
+    0x8b, 0x12, 0x02, 0xb9, 0x6a, 0x61, 0x20, 0x38, 0x72, 0x7b, 0x93, 0x02, 0x14, 0xd7, 0xa0, 0x32,
+    0x13, 0xf5, 0xb9, 0xe6, 0xef, 0xae, 0x33, 0x18, 0xee, 0x3b, 0x2d, 0xce, 0x24, 0xb3, 0x6a, 0xae,
+];
+
+const RUNTIME_CONFIG: &[u8] = br#"{ "runtimeOptions": { "tfm": "net10.0" } }"#;
+const ASSEMBLY: &[u8] =
+    b"MZ this is not really an assembly, but it compresses quite well well well well";
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("netcorehost-bundle-{}-{name}", std::process::id()))
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str) {
+    let mut len = s.len();
+    while len >= 0x80 {
+        out.push((len as u8) | 0x80);
+        len >>= 7;
+    }
+    out.push(len as u8);
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// Writes a bundle in the format used since .NET 6 containing an uncompressed runtime config and a compressed assembly.
+fn write_bundle(path: &Path) {
+    let mut out = Vec::new();
+
+    // apphost with the bundle marker placeholder
+    out.extend_from_slice(&[0xCC; 100]);
+    let placeholder = out.len();
+    out.extend_from_slice(&[0; 8]);
+    out.extend_from_slice(&BUNDLE_SIGNATURE);
+    out.extend_from_slice(&[0xCC; 100]);
+
+    let config_offset = out.len() as u64;
+    out.extend_from_slice(RUNTIME_CONFIG);
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(ASSEMBLY).unwrap();
+    let compressed = encoder.finish().unwrap();
+    let assembly_offset = out.len() as u64;
+    out.extend_from_slice(&compressed);
+
+    let header_offset = out.len() as u64;
+    out[placeholder..placeholder + 8].copy_from_slice(&header_offset.to_le_bytes());
+
+    out.extend_from_slice(&6u32.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.extend_from_slice(&2i32.to_le_bytes());
+    write_string(&mut out, "bundle-id");
+    out.extend_from_slice(&0u64.to_le_bytes()); // no deps.json
+    out.extend_from_slice(&0u64.to_le_bytes());
+    out.extend_from_slice(&config_offset.to_le_bytes());
+    out.extend_from_slice(&(RUNTIME_CONFIG.len() as u64).to_le_bytes());
+    out.extend_from_slice(&0u64.to_le_bytes()); // flags
+
+    out.extend_from_slice(&config_offset.to_le_bytes());
+    out.extend_from_slice(&(RUNTIME_CONFIG.len() as u64).to_le_bytes());
+    out.extend_from_slice(&0u64.to_le_bytes());
+    out.push(4);
+    write_string(&mut out, "App.runtimeconfig.json");
+
+    out.extend_from_slice(&assembly_offset.to_le_bytes());
+    out.extend_from_slice(&(ASSEMBLY.len() as u64).to_le_bytes());
+    out.extend_from_slice(&(compressed.len() as u64).to_le_bytes());
+    out.push(1);
+    write_string(&mut out, "App.dll");
+
+    fs::write(path, out).unwrap();
+}
+
+#[test]
+fn reads_header_and_manifest() {
+    let path = temp_path("manifest");
+    write_bundle(&path);
+
+    let bundle = Bundle::open(&path).unwrap();
+    let header = bundle.header();
+    assert_eq!(header.major_version, 6);
+    assert_eq!(header.bundle_id, "bundle-id");
+    assert_eq!(header.deps_json, None);
+    assert_eq!(
+        header.runtime_config_json.unwrap().size,
+        RUNTIME_CONFIG.len() as u64
+    );
+    assert!(!header.flags.netcoreapp3_compat_mode());
+
+    assert_eq!(bundle.files().len(), 2);
+    assert_eq!(bundle.app_name(), Some("App"));
+    assert_eq!(bundle.app_path(), path.with_file_name("App.dll"));
+    let assembly = bundle.file("App.dll").unwrap();
+    assert_eq!(assembly.file_type, BundleFileType::Assembly);
+    assert!(assembly.is_compressed());
+}
+
+#[test]
+fn reads_and_extracts_files() {
+    let path = temp_path("extract");
+    write_bundle(&path);
+    let bundle = Bundle::open(&path).unwrap();
+
+    let config = bundle.file("App.runtimeconfig.json").unwrap();
+    assert_eq!(bundle.read_file(config).unwrap(), RUNTIME_CONFIG);
+    let assembly = bundle.file("App.dll").unwrap();
+    assert_eq!(bundle.read_file(assembly).unwrap(), ASSEMBLY);
+
+    let dir = temp_path("extract-dir");
+    let extracted = bundle.extract_to(&dir).unwrap();
+    assert_eq!(extracted.app_name(), "App");
+    assert_eq!(fs::read(extracted.app_path()).unwrap(), ASSEMBLY);
+    assert_eq!(
+        fs::read(extracted.runtime_config_path()).unwrap(),
+        RUNTIME_CONFIG
+    );
+}
+
+#[test]
+fn rejects_unbundled_apphost() {
+    let path = temp_path("unbundled");
+    let mut out = vec![0xCC; 100];
+    out.extend_from_slice(&[0; 8]);
+    out.extend_from_slice(&BUNDLE_SIGNATURE);
+    fs::write(&path, out).unwrap();
+
+    assert!(matches!(Bundle::open(&path), Err(BundleError::NotABundle)));
+}
+
+#[test]
+fn rejects_files_past_the_end_of_the_bundle() {
+    let path = temp_path("oversized");
+    write_bundle(&path);
+    let bundle = Bundle::open(&path).unwrap();
+
+    let mut config = bundle.file("App.runtimeconfig.json").unwrap().clone();
+    config.size = u64::MAX;
+    assert!(matches!(
+        bundle.read_file(&config),
+        Err(BundleError::Malformed(_))
+    ));
+
+    let mut assembly = bundle.file("App.dll").unwrap().clone();
+    assembly.size = u64::MAX;
+    assert!(matches!(
+        bundle.read_file(&assembly),
+        Err(BundleError::Malformed(_))
+    ));
+    assembly.compressed_size = u64::MAX;
+    assert!(matches!(
+        bundle.read_file(&assembly),
+        Err(BundleError::Malformed(_))
+    ));
+}
+
+#[test]
+fn rejects_oversized_string_lengths() {
+    for prefix in [
+        &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F][..],
+        &[0xFF, 0xFF, 0xFF, 0xFF, 0x7F],
+        &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01],
+    ] {
+        let path = temp_path("long-string");
+        let mut out = vec![0xCC; 100];
+        out.extend_from_slice(&[0; 8]);
+        out.extend_from_slice(&BUNDLE_SIGNATURE);
+        let header_offset = out.len() as u64;
+        out[100..108].copy_from_slice(&header_offset.to_le_bytes());
+        out.extend_from_slice(&6u32.to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(&0i32.to_le_bytes());
+        out.extend_from_slice(prefix);
+        fs::write(&path, out).unwrap();
+
+        assert!(matches!(
+            Bundle::open(&path),
+            Err(BundleError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,150 @@
+//! A fake implementation of the managed `AssemblyLoadContextHelper` registered with the mock hostfxr library.
+
+use netcorehost::{
+    error::HResult,
+    hostfxr::{AssemblyLoadContextHelper, Hostfxr},
+    pdcstr,
+    pdcstring::{PdCStr, PdCString, PdChar},
+};
+use netcorehost_mock::MockHostfxr;
+use std::{
+    ffi::c_void,
+    fs,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+
+const HELPER_TYPE: &str = "NetCoreHost.Helper.AssemblyLoadContextHelper, NetCoreHost.Helper";
+
+pub static CONTEXTS: Mutex<Vec<(String, bool)>> = Mutex::new(Vec::new());
+pub static LOADED: Mutex<Vec<(isize, String)>> = Mutex::new(Vec::new());
+pub static UNLOADED: Mutex<Vec<isize>> = Mutex::new(Vec::new());
+pub static FREED: AtomicUsize = AtomicUsize::new(0);
+pub static COLLECTIONS: AtomicUsize = AtomicUsize::new(0);
+/// Prevents unloaded contexts from being collected, as if they were still referenced.
+pub static KEEP_ALIVE: AtomicBool = AtomicBool::new(false);
+
+fn string(ptr: *const PdChar) -> String {
+    unsafe { PdCStr::from_str_ptr(ptr) }.to_string_lossy()
+}
+
+extern "system" fn create_context(
+    name: *const PdChar,
+    is_collectible: i32,
+    handle: *mut isize,
+) -> i32 {
+    let mut contexts = CONTEXTS.lock().unwrap();
+    contexts.push((string(name), is_collectible != 0));
+    unsafe { *handle = contexts.len() as isize };
+    0
+}
+
+extern "system" fn load_from_path(handle: isize, path: *const PdChar) -> i32 {
+    if string(path).ends_with("Missing.dll") {
+        return HResult::COR_E_FILENOTFOUND.into();
+    }
+    LOADED.lock().unwrap().push((handle, string(path)));
+    0
+}
+
+extern "system" fn load_from_bytes(
+    handle: isize,
+    assembly: *const u8,
+    assembly_len: isize,
+    symbols: *const u8,
+    symbols_len: isize,
+) -> i32 {
+    let assembly = unsafe { std::slice::from_raw_parts(assembly, assembly_len as usize) };
+    if assembly.starts_with(b"invalid") {
+        return HResult::COR_E_BADIMAGEFORMAT.into();
+    }
+    let symbols = if symbols.is_null() {
+        "no symbols".to_string()
+    } else {
+        format!("{symbols_len} bytes of symbols")
+    };
+    LOADED
+        .lock()
+        .unwrap()
+        .push((handle, format!("{assembly_len} bytes with {symbols}")));
+    0
+}
+
+extern "system" fn run() -> i32 {
+    42
+}
+
+extern "system" fn get_function_pointer(
+    _handle: isize,
+    type_name: *const PdChar,
+    method_name: *const PdChar,
+    _delegate_type_name: *const PdChar,
+    function: *mut *const c_void,
+) -> i32 {
+    if string(type_name) != "Plugin.Entry, Plugin" {
+        HResult::COR_E_MISSINGMETHOD.into()
+    } else if string(method_name) == "Run" {
+        unsafe { *function = run as *const c_void };
+        0
+    } else if string(method_name) == "Overloaded" {
+        HResult::COR_E_AMBIGUOUSMATCH.into()
+    } else {
+        HResult::COR_E_MISSINGMETHOD.into()
+    }
+}
+
+extern "system" fn unload(handle: isize, weak_handle: *mut isize) -> i32 {
+    let (_, is_collectible) = CONTEXTS.lock().unwrap()[handle as usize - 1];
+    if !is_collectible {
+        return HResult::COR_E_INVALIDOPERATION.into();
+    }
+    UNLOADED.lock().unwrap().push(handle);
+    unsafe { *weak_handle = -handle };
+    0
+}
+
+extern "system" fn is_alive(_weak_handle: isize, collect: i32) -> i32 {
+    if collect != 0 {
+        COLLECTIONS.fetch_add(1, Ordering::SeqCst);
+    }
+    i32::from(KEEP_ALIVE.load(Ordering::SeqCst) || COLLECTIONS.load(Ordering::SeqCst) == 0)
+}
+
+extern "system" fn free_handle(_handle: isize) {
+    FREED.fetch_add(1, Ordering::SeqCst);
+}
+
+pub fn load_fake_helper() -> AssemblyLoadContextHelper {
+    let mock = MockHostfxr::load().unwrap();
+    mock.reset();
+    unsafe {
+        mock.register_function(HELPER_TYPE, "CreateContext", create_context as *const ());
+        mock.register_function(HELPER_TYPE, "LoadFromPath", load_from_path as *const ());
+        mock.register_function(HELPER_TYPE, "LoadFromBytes", load_from_bytes as *const ());
+        mock.register_function(
+            HELPER_TYPE,
+            "GetFunctionPointer",
+            get_function_pointer as *const (),
+        );
+        mock.register_function(HELPER_TYPE, "Unload", unload as *const ());
+        mock.register_function(HELPER_TYPE, "IsAlive", is_alive as *const ());
+        mock.register_function(HELPER_TYPE, "FreeHandle", free_handle as *const ());
+    }
+
+    // the delegate loader checks that the assembly exists before calling into the runtime.
+    let helper_path =
+        std::env::temp_dir().join(format!("netcorehost-helper-{}.dll", std::process::id()));
+    fs::write(&helper_path, []).unwrap();
+
+    let hostfxr = Hostfxr::load_from_path(mock.path()).unwrap();
+    let context = hostfxr
+        .initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"))
+        .unwrap();
+    AssemblyLoadContextHelper::load(
+        &context.get_delegate_loader().unwrap(),
+        PdCString::from_os_str(helper_path).unwrap(),
+    )
+    .unwrap()
+}
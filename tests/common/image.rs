@@ -0,0 +1,257 @@
+//! Builds minimal .NET assembly images containing only the metadata read by `netcorehost::metadata`.
+
+pub struct ImageMethod {
+    pub name: &'static str,
+    pub is_static: bool,
+    pub unmanaged_callers_only: bool,
+}
+
+pub struct ImageType {
+    pub namespace: &'static str,
+    pub name: &'static str,
+    /// Index of the enclosing type in the list of types.
+    pub enclosing: Option<usize>,
+    pub methods: Vec<ImageMethod>,
+}
+
+pub fn method(name: &'static str, is_static: bool, unmanaged_callers_only: bool) -> ImageMethod {
+    ImageMethod {
+        name,
+        is_static,
+        unmanaged_callers_only,
+    }
+}
+
+/// Returns an image of an assembly named `Test` with the types `Test.Program` (`Main`, `Hello` annotated with
+/// `UnmanagedCallersOnly` and the instance method `Instance`) and `Test.Program+Nested` (`Run`).
+pub fn test_assembly() -> Vec<u8> {
+    assembly_image(
+        "Test",
+        [1, 2, 3, 4],
+        &[
+            ImageType {
+                namespace: "Test",
+                name: "Program",
+                enclosing: None,
+                methods: vec![
+                    method("Main", true, false),
+                    method("Hello", true, true),
+                    method("Instance", false, false),
+                ],
+            },
+            ImageType {
+                namespace: "",
+                name: "Nested",
+                enclosing: Some(0),
+                methods: vec![method("Run", true, false)],
+            },
+        ],
+    )
+}
+
+struct Strings(Vec<u8>);
+
+impl Strings {
+    fn add(&mut self, s: &str) -> u16 {
+        if s.is_empty() {
+            return 0;
+        }
+        let offset = self.0.len() as u16;
+        self.0.extend_from_slice(s.as_bytes());
+        self.0.push(0);
+        offset
+    }
+}
+
+fn u16s(buf: &mut Vec<u8>, values: &[u16]) {
+    for value in values {
+        buf.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn u32s(buf: &mut Vec<u8>, values: &[u32]) {
+    for value in values {
+        buf.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn pad(buf: &mut Vec<u8>, alignment: usize) {
+    while !buf.len().is_multiple_of(alignment) {
+        buf.push(0);
+    }
+}
+
+pub fn assembly_image(name: &str, version: [u16; 4], types: &[ImageType]) -> Vec<u8> {
+    let mut strings = Strings(vec![0]);
+    let mut tables: Vec<(usize, u32, Vec<u8>)> = Vec::new();
+
+    // Module
+    let mut module = Vec::new();
+    u16s(
+        &mut module,
+        &[0, strings.add(&format!("{name}.dll")), 0, 0, 0],
+    );
+    tables.push((0x00, 1, module));
+
+    // TypeRef
+    let mut type_ref = Vec::new();
+    u16s(
+        &mut type_ref,
+        &[
+            0,
+            strings.add("UnmanagedCallersOnlyAttribute"),
+            strings.add("System.Runtime.InteropServices"),
+        ],
+    );
+    tables.push((0x01, 1, type_ref));
+
+    // TypeDef, starting with <Module>
+    let mut type_def = Vec::new();
+    u32s(&mut type_def, &[0]);
+    u16s(&mut type_def, &[strings.add("<Module>"), 0, 0, 1, 1]);
+    let mut method_def = Vec::new();
+    let mut custom_attribute = Vec::new();
+    let mut method_row = 1u16;
+    for ty in types {
+        u32s(&mut type_def, &[0x0010_0001]);
+        u16s(
+            &mut type_def,
+            &[
+                strings.add(ty.name),
+                strings.add(ty.namespace),
+                0,
+                1,
+                method_row,
+            ],
+        );
+        for method in &ty.methods {
+            let flags = if method.is_static { 0x0016 } else { 0x0006 };
+            u32s(&mut method_def, &[0]);
+            u16s(&mut method_def, &[0, flags, strings.add(method.name), 0, 1]);
+            if method.unmanaged_callers_only {
+                // parent: HasCustomAttribute MethodDef, type: CustomAttributeType MemberRef
+                u16s(&mut custom_attribute, &[method_row << 5, (1 << 3) | 3, 0]);
+            }
+            method_row += 1;
+        }
+    }
+    tables.push((0x02, types.len() as u32 + 1, type_def));
+    tables.push((0x06, u32::from(method_row - 1), method_def));
+
+    // MemberRef for the attribute constructor, parent: MemberRefParent TypeRef
+    let mut member_ref = Vec::new();
+    u16s(&mut member_ref, &[(1 << 3) | 1, strings.add(".ctor"), 0]);
+    tables.push((0x0A, 1, member_ref));
+
+    let attribute_count = custom_attribute.len() as u32 / 6;
+    tables.push((0x0C, attribute_count, custom_attribute));
+
+    // Assembly
+    let mut assembly = Vec::new();
+    u32s(&mut assembly, &[0x8004]);
+    u16s(&mut assembly, &version);
+    u32s(&mut assembly, &[0]);
+    u16s(&mut assembly, &[0, strings.add(name), 0]);
+    tables.push((0x20, 1, assembly));
+
+    // NestedClass
+    let mut nested_class = Vec::new();
+    let mut nested_count = 0;
+    for (i, ty) in types.iter().enumerate() {
+        if let Some(enclosing) = ty.enclosing {
+            u16s(&mut nested_class, &[i as u16 + 2, enclosing as u16 + 2]);
+            nested_count += 1;
+        }
+    }
+    tables.push((0x29, nested_count, nested_class));
+
+    tables.retain(|(_, rows, _)| *rows > 0);
+    let mut table_stream = Vec::new();
+    u32s(&mut table_stream, &[0]);
+    table_stream.extend_from_slice(&[2, 0, 0, 1]);
+    let valid = tables
+        .iter()
+        .fold(0u64, |valid, (table, _, _)| valid | (1 << table));
+    table_stream.extend_from_slice(&valid.to_le_bytes());
+    table_stream.extend_from_slice(&0u64.to_le_bytes());
+    for (_, rows, _) in &tables {
+        u32s(&mut table_stream, &[*rows]);
+    }
+    for (_, _, data) in &tables {
+        table_stream.extend_from_slice(data);
+    }
+    pad(&mut table_stream, 4);
+    let mut strings = strings.0;
+    pad(&mut strings, 4);
+
+    // metadata root with the #~ and #Strings streams
+    let header_size = 16 + 12 + 4 + (8 + 4) + (8 + 12);
+    let mut metadata = Vec::new();
+    u32s(&mut metadata, &[0x424A_5342]);
+    u16s(&mut metadata, &[1, 1]);
+    u32s(&mut metadata, &[0, 12]);
+    metadata.extend_from_slice(b"v4.0.30319\0\0");
+    u16s(&mut metadata, &[0, 2]);
+    u32s(
+        &mut metadata,
+        &[header_size as u32, table_stream.len() as u32],
+    );
+    metadata.extend_from_slice(b"#~\0\0");
+    u32s(
+        &mut metadata,
+        &[
+            (header_size + table_stream.len()) as u32,
+            strings.len() as u32,
+        ],
+    );
+    metadata.extend_from_slice(b"#Strings\0\0\0\0");
+    assert_eq!(metadata.len(), header_size);
+    metadata.extend_from_slice(&table_stream);
+    metadata.extend_from_slice(&strings);
+
+    // PE32 image with a single section containing the CLI header and the metadata
+    const SECTION_RVA: u32 = 0x2000;
+    const SECTION_OFFSET: usize = 0x200;
+    const CLI_HEADER_SIZE: u32 = 72;
+    let section_size = CLI_HEADER_SIZE + metadata.len() as u32;
+
+    let mut image = vec![0u8; 0x80];
+    image[..2].copy_from_slice(b"MZ");
+    image[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+    image.extend_from_slice(b"PE\0\0");
+    u16s(&mut image, &[0x014C, 1]);
+    u32s(&mut image, &[0, 0, 0]);
+    u16s(&mut image, &[224, 0x2102]);
+
+    let mut optional_header = vec![0u8; 224];
+    optional_header[..2].copy_from_slice(&0x010Bu16.to_le_bytes());
+    optional_header[92..96].copy_from_slice(&16u32.to_le_bytes());
+    optional_header[208..212].copy_from_slice(&SECTION_RVA.to_le_bytes());
+    optional_header[212..216].copy_from_slice(&CLI_HEADER_SIZE.to_le_bytes());
+    image.extend_from_slice(&optional_header);
+
+    image.extend_from_slice(b".text\0\0\0");
+    u32s(
+        &mut image,
+        &[
+            section_size,
+            SECTION_RVA,
+            section_size,
+            SECTION_OFFSET as u32,
+        ],
+    );
+    u32s(&mut image, &[0, 0, 0, 0x6000_0020]);
+    image.resize(SECTION_OFFSET, 0);
+
+    let mut cli_header = Vec::new();
+    u32s(&mut cli_header, &[CLI_HEADER_SIZE]);
+    u16s(&mut cli_header, &[2, 5]);
+    u32s(
+        &mut cli_header,
+        &[SECTION_RVA + CLI_HEADER_SIZE, metadata.len() as u32, 1],
+    );
+    cli_header.resize(CLI_HEADER_SIZE as usize, 0);
+    image.extend_from_slice(&cli_header);
+    image.extend_from_slice(&metadata);
+    image
+}
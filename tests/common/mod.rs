@@ -9,6 +9,10 @@ use std::{
     str::FromStr,
 };
 
+#[cfg(feature = "net5_0")]
+pub mod helper;
+pub mod image;
+
 pub fn test_netcore_version() -> String {
     env::var("NETCOREHOST_TEST_NETCORE_VERSION").unwrap_or_else(|_| "net10.0".to_string())
 }
@@ -161,3 +165,40 @@ pub fn build_library_project() {
         .wait()
         .expect("dotnet build failed");
 }
+
+pub fn helper_project_file_path() -> PathBuf {
+    PathBuf::from_str("helper/NetCoreHost.Helper/NetCoreHost.Helper.csproj")
+        .unwrap()
+        .absolutize()
+        .unwrap()
+        .to_path_buf()
+}
+
+pub fn helper_dll_path() -> PdCString {
+    PdCString::from_os_str(
+        PathBuf::from_str("helper/NetCoreHost.Helper/bin/Debug/net8.0/NetCoreHost.Helper.dll")
+            .unwrap()
+            .absolutize()
+            .unwrap()
+            .as_os_str(),
+    )
+    .unwrap()
+}
+
+pub fn build_helper_project() {
+    if Path::new(&helper_dll_path().to_os_string()).exists() {
+        return;
+    }
+
+    let project_file_path = helper_project_file_path();
+    let project_dir = project_file_path.parent().unwrap();
+
+    Command::new("dotnet")
+        .arg("build")
+        .arg(&project_file_path)
+        .current_dir(project_dir)
+        .spawn()
+        .expect("dotnet build failed")
+        .wait()
+        .expect("dotnet build failed");
+}
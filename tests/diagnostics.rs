@@ -0,0 +1,152 @@
+#![cfg(all(feature = "netcore3_0", any(feature = "log", feature = "tracing")))]
+
+use netcorehost::{error::HostingError, hostfxr::Hostfxr, pdcstr};
+use netcorehost_mock::MockHostfxr;
+use rusty_fork::rusty_fork_test;
+use std::sync::Mutex;
+
+fn setup() -> (MockHostfxr, Hostfxr) {
+    let mock = MockHostfxr::load().unwrap();
+    mock.reset();
+    let hostfxr = Hostfxr::load_from_path(mock.path()).unwrap();
+    (mock, hostfxr)
+}
+
+fn run_failing_initialization(mock: &MockHostfxr, hostfxr: &Hostfxr) {
+    hostfxr
+        .initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"))
+        .unwrap()
+        .close()
+        .unwrap();
+
+    mock.set_status(
+        "hostfxr_initialize_for_runtime_config",
+        HostingError::FrameworkMissingFailure.value(),
+    );
+    let result = hostfxr.initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"));
+    assert_eq!(result.unwrap_err(), HostingError::FrameworkMissingFailure);
+}
+
+#[cfg(feature = "log")]
+static RECORDS: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+
+#[cfg(feature = "log")]
+struct RecordingLogger;
+
+#[cfg(feature = "log")]
+impl log::Log for RecordingLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target() == "netcorehost::hostfxr"
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            RECORDS
+                .lock()
+                .unwrap()
+                .push((record.level(), record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(feature = "tracing")]
+static EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[cfg(feature = "tracing")]
+struct RecordingSubscriber;
+
+#[cfg(feature = "tracing")]
+struct FieldRecorder(String);
+
+#[cfg(feature = "tracing")]
+impl tracing::field::Visit for FieldRecorder {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.0.push_str(&format!(" {}={value:?}", field.name()));
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl tracing::Subscriber for RecordingSubscriber {
+    fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+        metadata.target() == "netcorehost::hostfxr"
+    }
+
+    fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        let mut recorder = FieldRecorder(format!("span {}", span.metadata().name()));
+        span.record(&mut recorder);
+        EVENTS.lock().unwrap().push(recorder.0);
+        tracing::span::Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        let mut recorder = FieldRecorder(format!("event {}", event.metadata().level()));
+        event.record(&mut recorder);
+        EVENTS.lock().unwrap().push(recorder.0);
+    }
+
+    fn enter(&self, _span: &tracing::span::Id) {}
+
+    fn exit(&self, _span: &tracing::span::Id) {}
+}
+
+rusty_fork_test! {
+    #[test]
+    #[cfg(feature = "log")]
+    fn forwards_to_log() {
+        log::set_logger(&RecordingLogger).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+        netcorehost::hostfxr::install_log_error_writer();
+
+        let (mock, hostfxr) = setup();
+        run_failing_initialization(&mock, &hostfxr);
+
+        let records = RECORDS.lock().unwrap();
+        assert_eq!(
+            records[0],
+            (
+                log::Level::Debug,
+                "hostfxr_initialize_for_runtime_config returned 0x00000000, runtime_config_path=\"App.runtimeconfig.json\"".to_owned()
+            )
+        );
+        assert_eq!(
+            records[1],
+            (
+                log::Level::Debug,
+                "hostfxr_close returned 0x00000000".to_owned()
+            )
+        );
+        assert_eq!(records[2].0, log::Level::Error);
+        assert!(records[2].1.starts_with("hostfxr_initialize_for_runtime_config failed"));
+        assert_eq!(records[3].0, log::Level::Warn);
+        assert!(records[3].1.starts_with("hostfxr_initialize_for_runtime_config returned 0x80008096"));
+    }
+
+    #[test]
+    #[cfg(feature = "tracing")]
+    fn forwards_to_tracing() {
+        tracing::subscriber::set_global_default(RecordingSubscriber).unwrap();
+        netcorehost::hostfxr::install_tracing_error_writer();
+
+        let (mock, hostfxr) = setup();
+        run_failing_initialization(&mock, &hostfxr);
+
+        let events = EVENTS.lock().unwrap();
+        assert_eq!(
+            events[..4],
+            [
+                "span hostfxr_initialize_for_runtime_config runtime_config_path=\"App.runtimeconfig.json\"",
+                "event DEBUG status=0x00000000",
+                "span hostfxr_close",
+                "event DEBUG status=0x00000000",
+            ]
+        );
+        assert!(events[5].starts_with("event ERROR message=hostfxr_initialize_for_runtime_config failed"));
+        assert_eq!(events[6], "event WARN status=0x80008096");
+    }
+}
@@ -1,55 +1,128 @@
-#![allow(deprecated)]
+#![cfg(feature = "netcore3_0")]
 
-use netcorehost::{hostfxr::Hostfxr, nethost, pdcstr};
+use netcorehost::{
+    error::HostingError,
+    hostfxr::{
+        ErrorCollector, GlobalErrorWriter, GlobalErrorWriterGuard, Hostfxr, collect_errors,
+        global_error_writer, set_global_error_writer,
+    },
+    pdcstr,
+};
+use netcorehost_mock::MockHostfxr;
 use rusty_fork::rusty_fork_test;
-use std::cell::Cell;
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+};
 
-mod common;
+fn setup_failing() -> (MockHostfxr, Hostfxr) {
+    let mock = MockHostfxr::load().unwrap();
+    mock.reset();
+    mock.set_status(
+        "hostfxr_initialize_for_runtime_config",
+        HostingError::FrameworkMissingFailure.value(),
+    );
+    let hostfxr = Hostfxr::load_from_path(mock.path()).unwrap();
+    (mock, hostfxr)
+}
 
-fn cause_error(hostfxr: &Hostfxr) {
-    let bad_path = pdcstr!("bad.runtimeconfig.json");
-    let _ = hostfxr.initialize_for_runtime_config(bad_path);
+fn recording_writer() -> (Arc<Mutex<Vec<String>>>, GlobalErrorWriter) {
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let writer_messages = Arc::clone(&messages);
+    let writer: GlobalErrorWriter = Arc::new(move |message| {
+        writer_messages
+            .lock()
+            .unwrap()
+            .push(message.to_string_lossy());
+    });
+    (messages, writer)
 }
 
 rusty_fork_test! {
     #[test]
-    #[cfg(feature = "netcore3_0")]
-    fn gets_called() {
-        common::setup();
-
-        let hostfxr = nethost::load_hostfxr().unwrap();
-        let was_called = Box::leak(Box::new(Cell::new(false)));
-        hostfxr.set_error_writer(Some(Box::new(
-            |_| { was_called.set(true); }
-        )));
-        cause_error(&hostfxr);
-
-        assert!(was_called.get());
+    fn global_writer_receives_errors_from_other_threads() {
+        let (_mock, hostfxr) = setup_failing();
+        let (messages, writer) = recording_writer();
+        let _guard = GlobalErrorWriterGuard::install(move |message| writer(message));
+
+        thread::spawn(move || {
+            let result = hostfxr.initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"));
+            assert_eq!(result.unwrap_err(), HostingError::FrameworkMissingFailure);
+        })
+        .join()
+        .unwrap();
+
+        let messages = messages.lock().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].starts_with("hostfxr_initialize_for_runtime_config failed"));
+    }
+
+    #[test]
+    fn thread_writer_takes_precedence() {
+        let (_mock, hostfxr) = setup_failing();
+        let (global_messages, writer) = recording_writer();
+        set_global_error_writer(Some(writer));
+
+        let (thread_messages, thread_writer) = recording_writer();
+        hostfxr
+            .set_error_writer(Some(Box::new(move |message| thread_writer(message))))
+            .unwrap();
+        let _ = hostfxr.initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"));
+        assert_eq!(thread_messages.lock().unwrap().len(), 1);
+        assert!(global_messages.lock().unwrap().is_empty());
+
+        hostfxr.set_error_writer(None).unwrap();
+        let _ = hostfxr.initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"));
+        assert_eq!(global_messages.lock().unwrap().len(), 1);
     }
 
     #[test]
-    #[cfg(feature = "netcore3_0")]
-    fn can_be_replaced() {
-        common::setup();
-
-        let hostfxr = nethost::load_hostfxr().unwrap();
-
-        let counter = Box::leak(Box::new(Cell::new(0)));
-        hostfxr.set_error_writer(Some(Box::new(
-            |_| { counter.set(counter.get() + 1); }
-        )));
-        cause_error(&hostfxr);
-        hostfxr.set_error_writer(Some(Box::new(
-            |_| { }
-        )));
-        cause_error(&hostfxr);
-        hostfxr.set_error_writer(Some(Box::new(
-            |_| { counter.set(counter.get() + 1); }
-        )));
-        cause_error(&hostfxr);
-        hostfxr.set_error_writer(None);
-        cause_error(&hostfxr);
-
-        assert_eq!(counter.get(), 2);
+    fn writer_is_registered_with_reloaded_library() {
+        let (messages, writer) = recording_writer();
+        let _guard = GlobalErrorWriterGuard::install(move |message| writer(message));
+
+        for _ in 0..2 {
+            // resetting the mock forgets the registered error writer like unloading hostfxr does.
+            let (_mock, hostfxr) = setup_failing();
+            let _ = hostfxr.initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"));
+        }
+        assert_eq!(messages.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn guard_restores_previous_writer() {
+        let (_, writer) = recording_writer();
+        assert!(set_global_error_writer(Some(Arc::clone(&writer))).is_none());
+
+        let guard = GlobalErrorWriterGuard::install(|_| {});
+        assert!(!Arc::ptr_eq(&global_error_writer().unwrap(), &writer));
+        drop(guard);
+        assert!(Arc::ptr_eq(&global_error_writer().unwrap(), &writer));
+
+        set_global_error_writer(None);
+        assert!(global_error_writer().is_none());
+    }
+
+    #[test]
+    fn collector_attaches_messages() {
+        let (_mock, hostfxr) = setup_failing();
+        let _guard = GlobalErrorWriterGuard::install(|_| {});
+
+        let outer = ErrorCollector::new();
+        let err = collect_errors(|| {
+            hostfxr.initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"))
+        })
+        .unwrap_err();
+        assert_eq!(err.code(), HostingError::FrameworkMissingFailure);
+        assert_eq!(err.operation(), "hostfxr_initialize_for_runtime_config");
+        assert_eq!(
+            err.arg("runtime_config_path"),
+            Some(pdcstr!("App.runtimeconfig.json"))
+        );
+        assert_eq!(err.messages().len(), 1);
+        assert!(err.to_string().ends_with(&err.messages()[0]));
+
+        // enclosing collectors receive the messages as well.
+        assert_eq!(outer.finish(), err.messages());
     }
 }
@@ -1,10 +1,21 @@
 #![cfg(feature = "netcore3_0")]
 
-use netcorehost::{hostfxr::GetManagedFunctionError, nethost, pdcstr};
+use netcorehost::{
+    hostfxr::{GetManagedFunctionError, ResolutionErrorKind},
+    nethost, pdcstr,
+};
 use rusty_fork::rusty_fork_test;
 
 mod common;
 
+fn resolution_error_kind<T>(result: Result<T, GetManagedFunctionError>) -> ResolutionErrorKind {
+    match result {
+        Err(GetManagedFunctionError::Resolution(err)) => err.kind(),
+        Err(err) => panic!("unexpected error: {err}"),
+        Ok(_) => panic!("function was resolved"),
+    }
+}
+
 rusty_fork_test! {
     #[test]
     fn get_function_pointer() {
@@ -23,58 +34,52 @@ rusty_fork_test! {
             pdcstr!("Test.Program, Test"),
             pdcstr!("SomeMethodThatDoesNotExist"),
         );
-        assert!(invalid_method_name.is_err());
         assert_eq!(
-            invalid_method_name.unwrap_err(),
-            GetManagedFunctionError::TypeOrMethodNotFound
+            resolution_error_kind(invalid_method_name),
+            ResolutionErrorKind::MethodNotFound
         );
 
         let invalid_method_signature = fn_loader
             .get_function_with_default_signature(pdcstr!("Test.Program, Test"), pdcstr!("Main"));
-        assert!(invalid_method_signature.is_err());
         assert_eq!(
-            invalid_method_signature.unwrap_err(),
-            GetManagedFunctionError::TypeOrMethodNotFound
+            resolution_error_kind(invalid_method_signature),
+            ResolutionErrorKind::IncompatibleSignature
         );
 
         let invalid_type_name = fn_loader.get_function_with_default_signature(
             pdcstr!("Test.SomeTypeThatDoesNotExist, Test"),
             pdcstr!("Hello"),
         );
-        assert!(invalid_type_name.is_err());
         assert_eq!(
-            invalid_type_name.unwrap_err(),
-            GetManagedFunctionError::TypeOrMethodNotFound
+            resolution_error_kind(invalid_type_name),
+            ResolutionErrorKind::TypeNotFound
         );
 
         let invalid_namespace_name = fn_loader.get_function_with_default_signature(
             pdcstr!("SomeNamespaceThatDoesNotExist.Program, Test"),
             pdcstr!("Hello"),
         );
-        assert!(invalid_namespace_name.is_err());
         assert_eq!(
-            invalid_namespace_name.unwrap_err(),
-            GetManagedFunctionError::TypeOrMethodNotFound
+            resolution_error_kind(invalid_namespace_name),
+            ResolutionErrorKind::TypeNotFound
         );
 
         let invalid_assembly_name = fn_loader.get_function_with_default_signature(
             pdcstr!("Test.Program, SomeAssemblyThatDoesNotExist"),
             pdcstr!("Hello"),
         );
-        assert!(invalid_assembly_name.is_err());
         assert_eq!(
-            invalid_assembly_name.unwrap_err(),
-            GetManagedFunctionError::AssemblyNotFound
+            resolution_error_kind(invalid_assembly_name),
+            ResolutionErrorKind::TypeAssemblyNotFound
         );
 
         let method_not_marked = fn_loader.get_function_with_unmanaged_callers_only::<fn()>(
             pdcstr!("Test.Program, Test"),
             pdcstr!("Hello"),
         );
-        assert!(method_not_marked.is_err());
         assert_eq!(
-            method_not_marked.unwrap_err(),
-            GetManagedFunctionError::MethodNotUnmanagedCallersOnly
+            resolution_error_kind(method_not_marked),
+            ResolutionErrorKind::MethodNotUnmanagedCallersOnly
         );
 
         let invalid_delegate_type_name = fn_loader.get_function::<fn()>(
@@ -82,10 +87,9 @@ rusty_fork_test! {
             pdcstr!("Hello"),
             pdcstr!("Test.Program+SomeDelegateThatDoesNotExist, Test"),
         );
-        assert!(invalid_delegate_type_name.is_err());
         assert_eq!(
-            invalid_delegate_type_name.unwrap_err(),
-            GetManagedFunctionError::TypeOrMethodNotFound
+            resolution_error_kind(invalid_delegate_type_name),
+            ResolutionErrorKind::DelegateTypeNotFound
         );
 
         context.close().unwrap();
@@ -106,10 +110,9 @@ rusty_fork_test! {
             .unwrap();
         let invalid_assembly_path = fn_loader
             .get_function_with_default_signature(pdcstr!("Test.Program, Test"), pdcstr!("Hello"));
-        assert!(invalid_assembly_path.is_err());
         assert_eq!(
-            invalid_assembly_path.unwrap_err(),
-            GetManagedFunctionError::AssemblyNotFound
+            resolution_error_kind(invalid_assembly_path),
+            ResolutionErrorKind::InvalidAssembly
         );
 
         let fn_loader = context
@@ -117,10 +120,9 @@ rusty_fork_test! {
             .unwrap();
         let non_existant_assembly_path = fn_loader
             .get_function_with_default_signature(pdcstr!("Test.Program, Test"), pdcstr!("Hello"));
-        assert!(non_existant_assembly_path.is_err());
         assert_eq!(
-            non_existant_assembly_path.unwrap_err(),
-            GetManagedFunctionError::AssemblyNotFound
+            resolution_error_kind(non_existant_assembly_path),
+            ResolutionErrorKind::AssemblyNotFound
         );
 
         context.close().unwrap();
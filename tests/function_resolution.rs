@@ -0,0 +1,182 @@
+#![cfg(feature = "net5_0")]
+
+use netcorehost::{
+    error::HResult,
+    hostfxr::{
+        AssemblyDelegateLoader, FunctionResolutionError, GetManagedFunctionError, Hostfxr,
+        HostfxrContext, InitializedForRuntimeConfig, ResolutionErrorKind,
+    },
+    pdcstr,
+    pdcstring::{PdCStr, PdCString},
+};
+use netcorehost_mock::MockHostfxr;
+use rusty_fork::rusty_fork_test;
+use std::{fs, path::PathBuf};
+
+mod common;
+
+const LOAD_FUNCTION: &str = "load_assembly_and_get_function_pointer";
+
+fn setup() -> (MockHostfxr, HostfxrContext<InitializedForRuntimeConfig>) {
+    let mock = MockHostfxr::load().unwrap();
+    mock.reset();
+    let hostfxr = Hostfxr::load_from_path(mock.path()).unwrap();
+    let context = hostfxr
+        .initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"))
+        .unwrap();
+    (mock, context)
+}
+
+fn write_assembly(name: &str, contents: &[u8]) -> PdCString {
+    let dir = std::env::temp_dir().join(format!("netcorehost-resolution-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, contents).unwrap();
+    PdCString::from_os_str(path).unwrap()
+}
+
+fn loader(
+    context: &HostfxrContext<InitializedForRuntimeConfig>,
+    path: PdCString,
+) -> AssemblyDelegateLoader {
+    context.get_delegate_loader_for_assembly(path).unwrap()
+}
+
+fn resolution_error(
+    result: Result<impl Sized, GetManagedFunctionError>,
+) -> FunctionResolutionError {
+    match result {
+        Err(GetManagedFunctionError::Resolution(err)) => err,
+        Err(err) => panic!("unexpected error: {err}"),
+        Ok(_) => panic!("function was resolved"),
+    }
+}
+
+fn default_signature_error(
+    loader: &AssemblyDelegateLoader,
+    type_name: &PdCStr,
+    method_name: &PdCStr,
+) -> FunctionResolutionError {
+    resolution_error(loader.get_function_with_default_signature(type_name, method_name))
+}
+
+rusty_fork_test! {
+    #[test]
+    fn missing_assembly() {
+        let (_mock, context) = setup();
+        let path = PathBuf::from("/path/that/does/not/exist/Test.dll");
+        let loader = loader(&context, PdCString::from_os_str(&path).unwrap());
+
+        let err = default_signature_error(&loader, pdcstr!("Test.Program, Test"), pdcstr!("Main"));
+        assert_eq!(err.kind(), ResolutionErrorKind::AssemblyNotFound);
+        assert_eq!(err.code(), None);
+        assert_eq!(err.assembly_path().unwrap().to_os_string(), path.as_os_str());
+    }
+
+    #[test]
+    fn invalid_assembly_is_not_reported_as_unmanaged_callers_only() {
+        let (mock, context) = setup();
+        mock.set_status(LOAD_FUNCTION, HResult::COR_E_INVALIDOPERATION.value());
+        let loader = loader(&context, write_assembly("Invalid.dll", b"not an assembly"));
+
+        let err = resolution_error(
+            loader.get_function_with_unmanaged_callers_only::<fn()>(
+                pdcstr!("Test.Program, Test"),
+                pdcstr!("Hello"),
+            ),
+        );
+        assert_eq!(err.kind(), ResolutionErrorKind::InvalidAssembly);
+        assert_eq!(err.code(), Some(HResult::COR_E_INVALIDOPERATION));
+    }
+
+    #[test]
+    fn method_not_unmanaged_callers_only() {
+        let (mock, context) = setup();
+        mock.set_status(LOAD_FUNCTION, HResult::COR_E_INVALIDOPERATION.value());
+        let loader = loader(&context, write_assembly("Test.dll", &common::image::test_assembly()));
+
+        let err = resolution_error(
+            loader.get_function_with_unmanaged_callers_only::<fn()>(
+                pdcstr!("Test.Program, Test"),
+                pdcstr!("Main"),
+            ),
+        );
+        assert_eq!(err.kind(), ResolutionErrorKind::MethodNotUnmanagedCallersOnly);
+        assert_eq!(err.type_name(), pdcstr!("Test.Program, Test"));
+        assert_eq!(err.method_name(), pdcstr!("Main"));
+    }
+
+    #[test]
+    fn missing_type_and_method() {
+        let (_mock, context) = setup();
+        let loader = loader(&context, write_assembly("Test.dll", &common::image::test_assembly()));
+
+        // the mock returns COR_E_MISSINGMETHOD for all unregistered functions.
+        let err = default_signature_error(&loader, pdcstr!("Test.Missing, Test"), pdcstr!("Main"));
+        assert_eq!(err.kind(), ResolutionErrorKind::TypeNotFound);
+
+        let err = default_signature_error(&loader, pdcstr!("Test.Program, Test"), pdcstr!("Missing"));
+        assert_eq!(err.kind(), ResolutionErrorKind::MethodNotFound);
+        assert_eq!(err.code(), Some(HResult::COR_E_MISSINGMETHOD));
+        assert!(err.to_string().starts_with(
+            "Failed to resolve method 'Missing' of type 'Test.Program, Test' from '"
+        ));
+        assert!(err.to_string().ends_with(
+            ": the type does not define a method with this name \
+             (0x80131513 (COR_E_MISSINGMETHOD, System.MissingMethodException))."
+        ));
+
+        let err = default_signature_error(
+            &loader,
+            pdcstr!("Test.Program+Nested, Test, Version=1.2.3.4"),
+            pdcstr!("Run"),
+        );
+        assert_eq!(err.kind(), ResolutionErrorKind::IncompatibleSignature);
+    }
+
+    #[test]
+    fn missing_delegate_type() {
+        let (mock, context) = setup();
+        mock.set_status(LOAD_FUNCTION, HResult::COR_E_TYPELOAD.value());
+        let loader = loader(&context, write_assembly("Test.dll", &common::image::test_assembly()));
+
+        let err = resolution_error(loader.get_function::<fn()>(
+            pdcstr!("Test.Program, Test"),
+            pdcstr!("Main"),
+            pdcstr!("Test.Program+MissingDelegate, Test"),
+        ));
+        assert_eq!(err.kind(), ResolutionErrorKind::DelegateTypeNotFound);
+
+        // the default signature uses a delegate type of the runtime, which always exists.
+        let err = default_signature_error(&loader, pdcstr!("Test.Program, Test"), pdcstr!("Main"));
+        assert_eq!(err.kind(), ResolutionErrorKind::IncompatibleSignature);
+    }
+
+    #[test]
+    fn type_in_dependency() {
+        let (_mock, context) = setup();
+        write_assembly("Test.dll", &common::image::test_assembly());
+        let loader = loader(&context, write_assembly("Plugin.dll", &common::image::assembly_image("Plugin", [1, 0, 0, 0], &[])));
+
+        let err = default_signature_error(&loader, pdcstr!("Test.Program, Test"), pdcstr!("Missing"));
+        assert_eq!(err.kind(), ResolutionErrorKind::MethodNotFound);
+
+        let err = default_signature_error(&loader, pdcstr!("Test.Missing, Test"), pdcstr!("Main"));
+        assert_eq!(err.kind(), ResolutionErrorKind::TypeNotFound);
+    }
+
+    #[test]
+    fn other_failures() {
+        let (mock, context) = setup();
+        let loader = loader(&context, write_assembly("Test.dll", &common::image::test_assembly()));
+
+        mock.set_status(LOAD_FUNCTION, HResult::COR_E_FILENOTFOUND.value());
+        let err = default_signature_error(&loader, pdcstr!("Test.Program, Other"), pdcstr!("Main"));
+        assert_eq!(err.kind(), ResolutionErrorKind::TypeAssemblyNotFound);
+
+        mock.set_status(LOAD_FUNCTION, HResult::COR_E_TYPEINITIALIZATION.value());
+        let err = default_signature_error(&loader, pdcstr!("Test.Program, Test"), pdcstr!("Main"));
+        assert_eq!(err.kind(), ResolutionErrorKind::Other);
+        assert_eq!(err.code(), Some(HResult::COR_E_TYPEINITIALIZATION));
+    }
+}
@@ -0,0 +1,114 @@
+use netcorehost::{
+    error::HostingError,
+    hostfxr::{HostTrace, HostTraceEvent, TRACE_ENV_VAR, TRACE_FILE_ENV_VAR, parse_host_trace},
+};
+use rusty_fork::rusty_fork_test;
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+const TRACE: &str = "\
+Tracing enabled @ Sun 10/18/2026 12:00:00 GMT
+--- Invoked hostfxr_initialize_for_runtime_config [commit hash: abc]
+Runtime config is cfg=/app/App.runtimeconfig.json dev=/app/App.runtimeconfig.dev.json
+--- Resolving FX directory, name 'Microsoft.NETCore.App' version '8.0.0'
+Searching FX directory in [/usr/share/dotnet]
+Attempting FX roll forward starting from version='[8.0.0]', apply_patches=1
+
+Chose FX version [/usr/share/dotnet/shared/Microsoft.NETCore.App/8.0.10]
+--- Invoked hostpolicy [commit hash: abc] [runtime=8.0.10]
+Using the provided deps file [/app/App.deps.json]
+    Probed deps json and matched [/app/App.dll]
+";
+
+#[test]
+fn parses_trace() {
+    let events = parse_host_trace(TRACE);
+    assert_eq!(events.len(), 10);
+    assert_eq!(
+        events[1],
+        HostTraceEvent::Invoked {
+            component: "hostfxr_initialize_for_runtime_config".to_owned()
+        }
+    );
+    assert_eq!(
+        events[2],
+        HostTraceEvent::RuntimeConfig {
+            path: PathBuf::from("/app/App.runtimeconfig.json")
+        }
+    );
+    assert_eq!(
+        events[3],
+        HostTraceEvent::ResolvingFramework {
+            name: "Microsoft.NETCore.App".to_owned(),
+            version: "8.0.0".to_owned()
+        }
+    );
+    assert!(matches!(&events[5], HostTraceEvent::Message(_)));
+    assert_eq!(
+        events[6],
+        HostTraceEvent::FrameworkResolved {
+            path: PathBuf::from("/usr/share/dotnet/shared/Microsoft.NETCore.App/8.0.10")
+        }
+    );
+    assert_eq!(
+        events[8],
+        HostTraceEvent::DepsFile {
+            path: PathBuf::from("/app/App.deps.json")
+        }
+    );
+    assert!(matches!(
+        &events[9],
+        HostTraceEvent::Probe { path, .. } if path == &PathBuf::from("/app/App.dll")
+    ));
+}
+
+rusty_fork_test! {
+    #[test]
+    fn enables_tracing_and_attaches_events() {
+        unsafe { env::set_var(TRACE_ENV_VAR, "0") };
+
+        let mut trace = unsafe { HostTrace::enable() }.unwrap();
+        assert!(unsafe { HostTrace::enable() }.is_err());
+        assert_eq!(env::var(TRACE_ENV_VAR).unwrap(), "1");
+        assert_eq!(env::var_os(TRACE_FILE_ENV_VAR).unwrap(), trace.path());
+
+        // simulate the hosting components appending to the trace file.
+        let mut file = OpenOptions::new().append(true).open(trace.path()).unwrap();
+        file.write_all(TRACE.as_bytes()).unwrap();
+        assert_eq!(trace.take_events().unwrap().len(), 10);
+        file.write_all(b"--- Invoked hostfxr_close [commit hash: abc]\nincomplete")
+            .unwrap();
+
+        let err = trace.attach(HostingError::FrameworkMissingFailure);
+        assert_eq!(err.error, HostingError::FrameworkMissingFailure);
+        assert!(err.trace_error.is_none());
+        assert_eq!(
+            err.events,
+            [HostTraceEvent::Invoked {
+                component: "hostfxr_close".to_owned()
+            }]
+        );
+        assert_eq!(trace.events().unwrap().len(), 12);
+
+        // the error is kept if the trace cannot be read.
+        drop(file);
+        fs::remove_file(trace.path()).unwrap();
+        let err = trace.attach(HostingError::FrameworkMissingFailure);
+        assert_eq!(err.error, HostingError::FrameworkMissingFailure);
+        assert!(err.events.is_empty());
+        assert_eq!(
+            err.trace_error.unwrap().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        let path = trace.path().to_owned();
+        drop(trace);
+        assert!(!path.exists());
+        assert_eq!(env::var(TRACE_ENV_VAR).unwrap(), "0");
+        assert!(env::var_os(TRACE_FILE_ENV_VAR).is_none());
+    }
+}
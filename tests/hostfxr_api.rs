@@ -0,0 +1,122 @@
+#![cfg(feature = "netcore3_0")]
+
+use netcorehost::{
+    bindings::hostfxr::hostfxr_delegate_type,
+    error::HostingError,
+    hostfxr::{
+        FakeCall, FakeHostfxr, FakeOperation, HostfxrApi, HostfxrCommandLineContextApi,
+        HostfxrContextApi,
+    },
+    pdcstr,
+    pdcstring::PdCString,
+};
+
+fn start_host<H: HostfxrApi>(hostfxr: &H) -> Result<i32, HostingError> {
+    let mut context = hostfxr.initialize_for_dotnet_command_line_with_args(
+        pdcstr!("App.dll"),
+        &[pdcstr!("--verbose")],
+    )?;
+    context.set_runtime_property_value(pdcstr!("HOST_NAME"), pdcstr!("test"))?;
+    Ok(context.run_app().value())
+}
+
+#[test]
+fn records_initialization_sequence() {
+    let hostfxr = FakeHostfxr::new();
+    hostfxr.set_exit_code(7);
+
+    assert_eq!(start_host(&hostfxr), Ok(7));
+    assert_eq!(
+        hostfxr.calls(),
+        [
+            FakeCall::InitializeForDotnetCommandLine {
+                app_path: PdCString::from(pdcstr!("App.dll")),
+                args: vec![PdCString::from(pdcstr!("--verbose"))],
+            },
+            FakeCall::SetRuntimePropertyValue {
+                context: 0,
+                name: PdCString::from(pdcstr!("HOST_NAME")),
+                value: PdCString::from(pdcstr!("test")),
+            },
+            FakeCall::RunApp { context: 0 },
+            FakeCall::Close { context: 0 },
+        ]
+    );
+    assert_eq!(hostfxr.active_contexts(), 0);
+}
+
+#[test]
+fn configured_failures() {
+    let hostfxr = FakeHostfxr::new();
+    hostfxr.fail(
+        FakeOperation::InitializeForDotnetCommandLine,
+        HostingError::FrameworkMissingFailure,
+    );
+    assert_eq!(
+        start_host(&hostfxr),
+        Err(HostingError::FrameworkMissingFailure)
+    );
+    assert_eq!(
+        hostfxr.operations(),
+        [FakeOperation::InitializeForDotnetCommandLine]
+    );
+
+    hostfxr.clear_failure(FakeOperation::InitializeForDotnetCommandLine);
+    hostfxr.fail(FakeOperation::RunApp, HostingError::CoreClrInitFailure);
+    assert_eq!(
+        start_host(&hostfxr),
+        Ok(HostingError::CoreClrInitFailure.value().cast_signed())
+    );
+}
+
+#[test]
+fn properties_and_delegates() {
+    let hostfxr = FakeHostfxr::new();
+    hostfxr.set_runtime_property(pdcstr!("APP_CONTEXT_BASE_DIRECTORY"), pdcstr!("/app"));
+    let delegate = 0x1234usize as netcorehost::hostfxr::RawFnPtr;
+    hostfxr.set_runtime_delegate(
+        hostfxr_delegate_type::hdt_load_assembly_and_get_function_pointer,
+        delegate,
+    );
+
+    let mut context = hostfxr
+        .initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"))
+        .unwrap();
+    assert!(context.is_primary());
+    let secondary = hostfxr
+        .initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"))
+        .unwrap();
+    assert!(!secondary.is_primary());
+    assert_eq!(secondary.id(), 1);
+    drop(secondary);
+
+    assert_eq!(
+        context
+            .get_runtime_property_value(pdcstr!("APP_CONTEXT_BASE_DIRECTORY"))
+            .unwrap(),
+        PdCString::from(pdcstr!("/app"))
+    );
+    context
+        .remove_runtime_property_value(pdcstr!("APP_CONTEXT_BASE_DIRECTORY"))
+        .unwrap();
+    assert_eq!(
+        context.get_runtime_property_value(pdcstr!("APP_CONTEXT_BASE_DIRECTORY")),
+        Err(HostingError::HostPropertyNotFound)
+    );
+    assert!(context.runtime_properties().unwrap().is_empty());
+
+    assert_eq!(
+        context.get_runtime_delegate(
+            hostfxr_delegate_type::hdt_load_assembly_and_get_function_pointer
+        ),
+        Ok(delegate)
+    );
+    assert_eq!(
+        context.get_runtime_delegate(hostfxr_delegate_type::hdt_com_activation),
+        Err(HostingError::HostApiUnsupportedScenario)
+    );
+
+    context.close().unwrap();
+    assert_eq!(hostfxr.active_contexts(), 0);
+    assert_eq!(hostfxr.operations().last(), Some(&FakeOperation::Close));
+}
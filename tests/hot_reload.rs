@@ -0,0 +1,124 @@
+#![cfg(feature = "net5_0")]
+
+use common::helper::{CONTEXTS, LOADED, UNLOADED, load_fake_helper};
+use netcorehost::{
+    error::HResult,
+    hostfxr::{HotReloadPlugin, ReloadError},
+    pdcstr,
+    pdcstring::PdCString,
+};
+use rusty_fork::rusty_fork_test;
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+mod common;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+fn plugin_path() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("netcorehost-hot-reload-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join("Plugin.dll")
+}
+
+fn wait_until(condition: impl Fn() -> bool) {
+    let start = Instant::now();
+    while !condition() {
+        assert!(start.elapsed() < Duration::from_secs(10), "timed out");
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+rusty_fork_test! {
+    #[test]
+    fn reload_on_change() {
+        let helper = load_fake_helper();
+        let path = plugin_path();
+        fs::write(&path, [0u8; 16]).unwrap();
+        fs::write(path.with_extension("pdb"), [0u8; 4]).unwrap();
+
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        let plugin = HotReloadPlugin::builder(&helper, PdCString::from_os_str(&path).unwrap())
+            .function_with_unmanaged_callers_only::<fn() -> i32>("run", pdcstr!("Plugin.Entry, Plugin"), pdcstr!("Run"))
+            .poll_interval(POLL_INTERVAL)
+            .on_reload_failure({
+                let failures = Arc::clone(&failures);
+                move |err| failures.lock().unwrap().push(err.to_string())
+            })
+            .build()
+            .unwrap();
+        assert_eq!(plugin.generation(), 1);
+        assert_eq!(*CONTEXTS.lock().unwrap(), [("Plugin".to_string(), true)]);
+        assert_eq!(
+            *LOADED.lock().unwrap(),
+            [(1, "16 bytes with 4 bytes of symbols".to_string())]
+        );
+
+        let first = plugin.functions();
+        let run = first.get::<fn() -> i32>("run").unwrap();
+        assert_eq!(run.call(()), 42);
+        assert!(first.get::<fn() -> i32>("missing").is_none());
+        // functions can only be taken as the type they were registered as.
+        assert!(first.get::<fn() -> u8>("run").is_none());
+        assert!(first.get::<fn(i32) -> i32>("run").is_none());
+        assert!(first.get_with_default_signature("run").is_none());
+        assert_eq!(first.keys().collect::<Vec<_>>(), ["run"]);
+
+        fs::remove_file(path.with_extension("pdb")).unwrap();
+        fs::write(&path, [0u8; 32]).unwrap();
+        wait_until(|| plugin.generation() == 2);
+        assert_eq!(LOADED.lock().unwrap()[1], (2, "32 bytes with no symbols".to_string()));
+
+        // the previous version stays loaded while it is in use.
+        assert!(UNLOADED.lock().unwrap().is_empty());
+        assert_eq!(run.call(()), 42);
+        drop(first);
+        assert_eq!(*UNLOADED.lock().unwrap(), [1]);
+
+        fs::write(&path, b"invalid assembly").unwrap();
+        wait_until(|| !failures.lock().unwrap().is_empty());
+        assert_eq!(plugin.generation(), 2);
+        assert_eq!(
+            failures.lock().unwrap()[0],
+            format!("LoadFromBytes failed with {}.", HResult::COR_E_BADIMAGEFORMAT)
+        );
+        // the context of the failed version is unloaded immediately.
+        assert_eq!(*UNLOADED.lock().unwrap(), [1, 3]);
+
+        let err = plugin.reload().unwrap_err();
+        assert!(matches!(err, ReloadError::Load(_)));
+
+        fs::write(&path, [0u8; 64]).unwrap();
+        wait_until(|| plugin.generation() == 3);
+        assert!(plugin.functions().get::<fn() -> i32>("run").is_some());
+        assert_eq!(failures.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn missing_function_fails_to_load() {
+        let helper = load_fake_helper();
+        let path = plugin_path();
+        fs::write(&path, [0u8; 16]).unwrap();
+
+        let err = HotReloadPlugin::builder(&helper, PdCString::from_os_str(&path).unwrap())
+            .function_with_unmanaged_callers_only::<fn() -> i32>("run", pdcstr!("Plugin.Entry, Plugin"), pdcstr!("Run"))
+            .function_with_default_signature("missing", pdcstr!("Plugin.Entry, Plugin"), pdcstr!("Missing"))
+            .build()
+            .unwrap_err();
+        let ReloadError::Function { key, .. } = &err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(key, "missing");
+        assert_eq!(*UNLOADED.lock().unwrap(), [1]);
+
+        let err = HotReloadPlugin::builder(&helper, pdcstr!("/path/that/does/not/exist/Plugin.dll"))
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, ReloadError::Io(_)));
+    }
+}
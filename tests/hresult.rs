@@ -0,0 +1,49 @@
+use netcorehost::error::{Facility, HResult};
+
+#[test]
+fn decodes_parts() {
+    let hr = HResult::from(0x8013_1522_u32);
+    assert!(hr.is_failure());
+    assert!(!hr.is_customer());
+    assert_eq!(hr.facility(), Facility::Urt);
+    assert_eq!(hr.code(), 0x1522);
+    assert_eq!(hr.win32_code(), None);
+
+    let hr = HResult::from_win32(2);
+    assert_eq!(hr, HResult::COR_E_FILENOTFOUND);
+    assert_eq!(hr.facility(), Facility::Win32);
+    assert_eq!(hr.win32_code(), Some(2));
+
+    assert!(HResult::S_OK.is_success());
+    assert_eq!(HResult::from(-2_146_233_054_i32), HResult::COR_E_TYPELOAD);
+    assert_eq!(i32::from(HResult::COR_E_TYPELOAD), -2_146_233_054);
+}
+
+#[test]
+fn maps_known_codes() {
+    assert_eq!(HResult::E_INVALIDARG, HResult::COR_E_ARGUMENT);
+    assert_eq!(HResult::E_INVALIDARG.name(), Some("COR_E_ARGUMENT"));
+    assert_eq!(
+        HResult::COR_E_MISSINGMETHOD.exception_type(),
+        Some("System.MissingMethodException")
+    );
+    assert_eq!(
+        HResult::E_FAIL.exception_type(),
+        Some("System.Runtime.InteropServices.COMException")
+    );
+    assert!(!HResult(0x8013_FFFF).is_known());
+    assert_eq!(HResult(0x8013_FFFF).exception_type(), None);
+}
+
+#[test]
+fn displays_value_and_meaning() {
+    assert_eq!(
+        HResult::COR_E_INVALIDOPERATION.to_string(),
+        "0x80131509 (COR_E_INVALIDOPERATION, System.InvalidOperationException)"
+    );
+    assert_eq!(HResult::S_OK.to_string(), "0x00000000 (S_OK)");
+    assert_eq!(
+        HResult(0xA004_0042).to_string(),
+        "0xA0040042 (facility 0x4, code 0x0042)"
+    );
+}
@@ -0,0 +1,208 @@
+#![cfg(unix)]
+
+use netcorehost::{
+    arch::Architecture,
+    install_location::{
+        InstallLocation, InstallLocationConfig, InstallLocationSource,
+        LoadFromInstallLocationError, current_arch_name, find_hostfxr_in_dotnet_root,
+    },
+};
+use rusty_fork::rusty_fork_test;
+use std::{
+    env::consts::{DLL_PREFIX, DLL_SUFFIX},
+    fs,
+    path::{Path, PathBuf},
+};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "netcorehost-install-location-{}-{name}",
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn elf_machine(arch: Architecture) -> u16 {
+    match arch {
+        Architecture::X64 => 62,
+        Architecture::Arm64 => 183,
+        _ => unreachable!("tests only run on x64 and arm64"),
+    }
+}
+
+/// Creates a dotnet root whose hostfxr libraries only consist of an ELF header for the given architecture.
+fn create_fake_dotnet_root_for(root: &Path, versions: &[&str], arch: Architecture) {
+    let mut header = vec![0x7F, b'E', b'L', b'F', 2, 1, 1];
+    header.resize(18, 0);
+    header.extend_from_slice(&elf_machine(arch).to_le_bytes());
+    header.resize(64, 0);
+
+    for version in versions {
+        let dir = root.join("host").join("fxr").join(version);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(format!("{DLL_PREFIX}hostfxr{DLL_SUFFIX}")),
+            &header,
+        )
+        .unwrap();
+    }
+}
+
+fn create_fake_dotnet_root(root: &Path, versions: &[&str]) {
+    create_fake_dotnet_root_for(root, versions, Architecture::current().unwrap());
+}
+
+#[test]
+fn read_and_write() {
+    let config = InstallLocationConfig::new(temp_dir("read-write").join("etc"));
+    assert_eq!(config.read(None).unwrap(), None);
+
+    config.write(None, "/opt/dotnet").unwrap();
+    config.write(Some("ARM64"), "/opt/dotnet-arm64").unwrap();
+    assert_eq!(
+        config.file_path(Some("ARM64")),
+        config.dir().join("install_location_arm64")
+    );
+    assert_eq!(
+        config.read(None).unwrap(),
+        Some(PathBuf::from("/opt/dotnet"))
+    );
+    assert_eq!(
+        config.read(Some("arm64")).unwrap(),
+        Some(PathBuf::from("/opt/dotnet-arm64"))
+    );
+
+    assert_eq!(
+        config.read_for_arch("arm64").unwrap(),
+        Some(InstallLocation {
+            path: PathBuf::from("/opt/dotnet-arm64"),
+            source: InstallLocationSource::ArchConfigFile,
+        })
+    );
+    assert_eq!(
+        config.read_for_arch("x64").unwrap(),
+        Some(InstallLocation {
+            path: PathBuf::from("/opt/dotnet"),
+            source: InstallLocationSource::ConfigFile,
+        })
+    );
+
+    config.remove(Some("arm64")).unwrap();
+    config.remove(Some("arm64")).unwrap();
+    assert_eq!(config.read(Some("arm64")).unwrap(), None);
+}
+
+#[test]
+fn only_first_line_is_used() {
+    let config = InstallLocationConfig::new(temp_dir("first-line"));
+    fs::write(
+        config.file_path(None),
+        "  /opt/dotnet  \nx64=/somewhere/else\n",
+    )
+    .unwrap();
+    assert_eq!(
+        config.read(None).unwrap(),
+        Some(PathBuf::from("/opt/dotnet"))
+    );
+}
+
+#[test]
+fn finds_highest_hostfxr_version() {
+    let root = temp_dir("highest-version");
+    create_fake_dotnet_root(&root, &["8.0.1", "10.0.0-rc.2", "9.0.10", "10.0.0"]);
+    fs::create_dir_all(root.join("host").join("fxr").join("not-a-version")).unwrap();
+
+    assert_eq!(
+        find_hostfxr_in_dotnet_root(&root),
+        Some(
+            root.join("host")
+                .join("fxr")
+                .join("10.0.0")
+                .join(format!("{DLL_PREFIX}hostfxr{DLL_SUFFIX}"))
+        )
+    );
+    assert_eq!(find_hostfxr_in_dotnet_root(root.join("missing")), None);
+}
+
+#[test]
+fn compares_prerelease_identifiers_numerically() {
+    let root = temp_dir("prerelease-version");
+    create_fake_dotnet_root(&root, &["10.0.0-rc.9", "10.0.0-rc.10", "10.0.0-preview.7"]);
+
+    assert_eq!(
+        find_hostfxr_in_dotnet_root(&root),
+        Some(
+            root.join("host")
+                .join("fxr")
+                .join("10.0.0-rc.10")
+                .join(format!("{DLL_PREFIX}hostfxr{DLL_SUFFIX}"))
+        )
+    );
+}
+
+rusty_fork_test! {
+    #[test]
+    fn resolve_uses_first_configured_location() {
+        unsafe {
+            std::env::remove_var("DOTNET_ROOT");
+            std::env::remove_var(Architecture::current().unwrap().dotnet_root_env_var());
+        }
+
+        let dir = temp_dir("resolve");
+        let arch_root = dir.join("arch-root");
+        let generic_root = dir.join("generic-root");
+        create_fake_dotnet_root(&arch_root, &["10.0.0"]);
+        create_fake_dotnet_root(&generic_root, &["10.0.0"]);
+
+        let config = InstallLocationConfig::new(dir.join("etc"));
+        config.write(None, &generic_root).unwrap();
+        config.write(Some(current_arch_name()), dir.join("missing-root")).unwrap();
+
+        // like the muxer, the generic location is not used if the arch-specific one does not contain hostfxr.
+        assert_eq!(config.resolve_dotnet_root().unwrap(), None);
+        config.remove(Some(current_arch_name())).unwrap();
+        let resolved = config.resolve_dotnet_root().unwrap().unwrap();
+        assert_eq!(resolved.path, generic_root);
+        assert_eq!(resolved.source, InstallLocationSource::ConfigFile);
+
+        config.write(Some(current_arch_name()), &arch_root).unwrap();
+        let resolved = config.resolve_dotnet_root().unwrap().unwrap();
+        assert_eq!(resolved.path, arch_root);
+        assert_eq!(resolved.source, InstallLocationSource::ArchConfigFile);
+
+        unsafe { std::env::set_var("DOTNET_ROOT", &generic_root) };
+        let resolved = config.resolve_dotnet_root().unwrap().unwrap();
+        assert_eq!(resolved.path, generic_root);
+        assert_eq!(resolved.source, InstallLocationSource::Environment);
+    }
+
+    #[test]
+    fn resolve_reports_architecture_mismatch() {
+        let current = Architecture::current().unwrap();
+        let other = if current == Architecture::Arm64 {
+            Architecture::X64
+        } else {
+            Architecture::Arm64
+        };
+        unsafe {
+            std::env::remove_var("DOTNET_ROOT");
+            std::env::remove_var(current.dotnet_root_env_var());
+        }
+
+        let dir = temp_dir("resolve-mismatch");
+        let root = dir.join("root");
+        create_fake_dotnet_root_for(&root, &["10.0.0"], other);
+        let config = InstallLocationConfig::new(dir.join("etc"));
+        config.write(Some(current_arch_name()), &root).unwrap();
+
+        match config.resolve_dotnet_root() {
+            Err(LoadFromInstallLocationError::ArchitectureMismatch(err)) => {
+                assert_eq!(err.expected, current);
+                assert_eq!(err.found, vec![other]);
+            }
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+}
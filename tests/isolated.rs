@@ -0,0 +1,75 @@
+#![cfg(feature = "netcore3_0")]
+
+use netcorehost::{
+    error::HostingError,
+    hostfxr::{IsolatedApp, run_isolated_child},
+    pdcstr,
+    pdcstring::PdCString,
+};
+use netcorehost_mock::MockHostfxr;
+use std::{env, path::PathBuf};
+
+mod common;
+
+// Entry point of the child processes spawned by the tests below. Does nothing when run as a regular test.
+#[test]
+fn isolated_child() {
+    run_isolated_child();
+}
+
+fn isolated_app(hostfxr_path: impl Into<PathBuf>, app_path: impl Into<PdCString>) -> IsolatedApp {
+    IsolatedApp::new(hostfxr_path, app_path).launcher(
+        env::current_exe().unwrap(),
+        [
+            "isolated_child",
+            "--exact",
+            "--nocapture",
+            "--test-threads=1",
+        ],
+    )
+}
+
+#[test]
+fn runs_app_in_child_process() {
+    let output = isolated_app(MockHostfxr::library_path().unwrap(), pdcstr!("App.dll"))
+        .arg(pdcstr!("--verbose"))
+        .run()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(output.has_reported_result());
+    assert_eq!(output.result().unwrap().value(), 0);
+    assert!(output.error_writer_output.is_empty());
+}
+
+#[test]
+fn reports_hostfxr_load_failure() {
+    let output = isolated_app(env::temp_dir().join("missing-hostfxr"), pdcstr!("App.dll"))
+        .run()
+        .unwrap();
+
+    assert_eq!(
+        output
+            .result()
+            .unwrap()
+            .as_hosting_exit_code()
+            .into_result(),
+        Err(HostingError::CoreHostLibLoadFailure)
+    );
+    assert_eq!(output.error_writer_output.len(), 1);
+    assert!(output.error_writer_output[0].starts_with("Failed to load hostfxr"));
+}
+
+#[test]
+#[cfg(feature = "nethost")]
+fn captures_app_output() {
+    common::setup();
+
+    let hostfxr_path = netcorehost::nethost::get_hostfxr_path().unwrap();
+    let output = isolated_app(hostfxr_path, common::test_dll_path())
+        .run()
+        .unwrap();
+
+    assert_eq!(output.result().unwrap().value(), 42);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Hello from C#!"));
+}
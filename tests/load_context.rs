@@ -0,0 +1,132 @@
+#![cfg(feature = "net5_0")]
+
+use common::helper::{CONTEXTS, FREED, LOADED, load_fake_helper};
+use netcorehost::{
+    error::HResult,
+    hostfxr::{AssemblyLoadContextHelper, GetManagedFunctionError, ResolutionErrorKind},
+    pdcstr,
+};
+use rusty_fork::rusty_fork_test;
+use std::{fs, sync::atomic::Ordering};
+
+mod common;
+
+rusty_fork_test! {
+    #[test]
+    fn load_into_context() {
+        let helper = load_fake_helper();
+
+        let plugins = helper.create_context(pdcstr!("Plugins"), true).unwrap();
+        assert_eq!(plugins.name(), pdcstr!("Plugins"));
+        assert!(plugins.is_collectible());
+        assert_eq!(*CONTEXTS.lock().unwrap(), [("Plugins".to_string(), true)]);
+
+        plugins.load_assembly_from_path(pdcstr!("Plugin.dll")).unwrap();
+        plugins.load_assembly_from_bytes([0u8; 16], []).unwrap();
+        plugins.load_assembly_from_bytes([0u8; 16], [0u8; 4]).unwrap();
+        assert_eq!(
+            *LOADED.lock().unwrap(),
+            [
+                (1, "Plugin.dll".to_string()),
+                (1, "16 bytes with no symbols".to_string()),
+                (1, "16 bytes with 4 bytes of symbols".to_string()),
+            ]
+        );
+
+        let err = plugins.load_assembly_from_path(pdcstr!("Missing.dll")).unwrap_err();
+        assert_eq!(err.operation(), "LoadFromPath");
+        assert_eq!(err.code(), HResult::COR_E_FILENOTFOUND);
+
+        let run = plugins
+            .get_function_with_unmanaged_callers_only::<fn() -> i32>(
+                pdcstr!("Plugin.Entry, Plugin"),
+                pdcstr!("Run"),
+            )
+            .unwrap();
+        assert_eq!(run(), 42);
+
+        let err = plugins
+            .get_function_with_default_signature(pdcstr!("Plugin.Entry, Plugin"), pdcstr!("Missing"))
+            .unwrap_err();
+        let GetManagedFunctionError::Resolution(err) = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(err.kind(), ResolutionErrorKind::MethodNotFound);
+        assert_eq!(err.assembly_path(), None);
+
+        let err = plugins
+            .get_function_with_unmanaged_callers_only::<fn() -> i32>(
+                pdcstr!("Plugin.Entry, Plugin"),
+                pdcstr!("Overloaded"),
+            )
+            .unwrap_err();
+        let GetManagedFunctionError::Resolution(err) = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(err.kind(), ResolutionErrorKind::AmbiguousMethod);
+
+        assert_eq!(FREED.load(Ordering::SeqCst), 0);
+        drop(plugins);
+        assert_eq!(FREED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn unload_requires_collectible_context() {
+        let helper = load_fake_helper();
+
+        let context = helper.create_context(pdcstr!("Default"), false).unwrap();
+        let err = context.unload().unwrap_err();
+        assert_eq!(err.operation(), "Unload");
+        assert_eq!(err.code(), HResult::COR_E_INVALIDOPERATION);
+        // the handle is still released if unloading failed.
+        assert_eq!(FREED.load(Ordering::SeqCst), 1);
+
+        let context = helper.create_context(pdcstr!("Collectible"), true).unwrap();
+        let unloading = context.unload().unwrap();
+        assert!(unloading.is_alive());
+        assert!(unloading.wait_for_collection(1));
+        assert!(!unloading.is_alive());
+        drop(unloading);
+        assert_eq!(FREED.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "nethost")]
+    fn load_library_into_context() {
+        common::setup();
+        common::build_helper_project();
+
+        let hostfxr = netcorehost::nethost::load_hostfxr().unwrap();
+        let context = hostfxr
+            .initialize_for_runtime_config(common::test_runtime_config_path())
+            .unwrap();
+        let helper = AssemblyLoadContextHelper::load(
+            &context.get_delegate_loader().unwrap(),
+            common::helper_dll_path(),
+        )
+        .unwrap();
+
+        let plugins = helper.create_context(pdcstr!("Plugins"), false).unwrap();
+        plugins
+            .load_assembly_from_path(common::library_dll_path())
+            .unwrap();
+        let hello = plugins
+            .get_function_with_unmanaged_callers_only::<fn() -> i32>(
+                pdcstr!("ClassLibrary.Library, ClassLibrary"),
+                pdcstr!("Hello"),
+            )
+            .unwrap();
+        assert_eq!(hello(), 42);
+
+        let bytes = fs::read(common::library_dll_path().to_os_string()).unwrap();
+        let other = helper.create_context(pdcstr!("Other"), false).unwrap();
+        other.load_assembly_from_bytes(bytes, []).unwrap();
+        let hello = other
+            .get_function_with_unmanaged_callers_only::<fn() -> i32>(
+                pdcstr!("ClassLibrary.Library, ClassLibrary"),
+                pdcstr!("Hello"),
+            )
+            .unwrap();
+        assert_eq!(hello(), 42);
+    }
+}
@@ -0,0 +1,68 @@
+use netcorehost::metadata::{AssemblyMetadata, AssemblyVersion, MetadataError};
+
+mod common;
+
+#[test]
+fn reads_assembly_identity() {
+    let metadata = AssemblyMetadata::parse(&common::image::test_assembly()).unwrap();
+    let assembly = metadata.assembly().unwrap();
+    assert_eq!(assembly.name, "Test");
+    assert_eq!(assembly.version, AssemblyVersion::new(1, 2, 3, 4));
+    assert_eq!(assembly.version.to_string(), "1.2.3.4");
+    assert_eq!(assembly.culture, None);
+}
+
+#[test]
+fn reads_types_and_methods() {
+    let metadata = AssemblyMetadata::parse(&common::image::test_assembly()).unwrap();
+    let names = metadata
+        .types()
+        .iter()
+        .map(|ty| ty.full_name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["<Module>", "Test.Program", "Test.Program+Nested"]);
+
+    let program = metadata.find_type("Test.Program").unwrap();
+    let methods = program
+        .methods()
+        .iter()
+        .map(|m| (m.name(), m.is_static(), m.is_unmanaged_callers_only()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        methods,
+        [
+            ("Main", true, false),
+            ("Hello", true, true),
+            ("Instance", false, false)
+        ]
+    );
+
+    let nested = metadata.find_type("Test.Program+Nested").unwrap();
+    assert_eq!(nested.namespace(), "Test");
+    assert_eq!(nested.name(), "Nested");
+    assert_eq!(nested.methods_named("Run").count(), 1);
+    assert!(metadata.find_type("Test.Nested").is_none());
+}
+
+#[test]
+fn rejects_non_assemblies() {
+    assert!(matches!(
+        AssemblyMetadata::parse(b"not an assembly"),
+        Err(MetadataError::NotPortableExecutable)
+    ));
+
+    // a native image without a CLI header
+    let mut image = common::image::test_assembly();
+    image[0x80 + 24 + 208..0x80 + 24 + 216].fill(0);
+    assert!(matches!(
+        AssemblyMetadata::parse(&image),
+        Err(MetadataError::NotManaged)
+    ));
+
+    let mut image = common::image::test_assembly();
+    image.truncate(0x200 + 100);
+    assert!(matches!(
+        AssemblyMetadata::parse(&image),
+        Err(MetadataError::Malformed)
+    ));
+}
@@ -0,0 +1,291 @@
+#![cfg(feature = "netcore3_0")]
+
+use netcorehost::{
+    error::HostingError,
+    hostfxr::{
+        AppExit, AppExitClassification, Hostfxr, HostfxrApi, HostfxrCommandLineContextApi,
+        HostfxrContextApi,
+    },
+    pdcstr,
+    pdcstring::PdCString,
+};
+use netcorehost_mock::MockHostfxr;
+use rusty_fork::rusty_fork_test;
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+fn setup() -> (MockHostfxr, Hostfxr) {
+    let mock = MockHostfxr::load().unwrap();
+    mock.reset();
+    let hostfxr = Hostfxr::load_from_path(mock.path()).unwrap();
+    (mock, hostfxr)
+}
+
+rusty_fork_test! {
+    #[test]
+    fn runtime_properties() {
+        let (mock, hostfxr) = setup();
+        mock.set_runtime_property("APP_CONTEXT_BASE_DIRECTORY", "/app");
+
+        let mut context = hostfxr
+            .initialize_for_runtime_config(pdcstr!("/app/App.runtimeconfig.json"))
+            .unwrap();
+        assert!(context.is_primary());
+        assert_eq!(
+            context
+                .get_runtime_property_value(pdcstr!("APP_CONTEXT_BASE_DIRECTORY"))
+                .unwrap(),
+            pdcstr!("/app")
+        );
+
+        context
+            .set_runtime_property_value(pdcstr!("TEST_PROPERTY"), pdcstr!("value"))
+            .unwrap();
+        assert_eq!(context.runtime_properties().unwrap().len(), 2);
+        context
+            .remove_runtime_property_value(pdcstr!("TEST_PROPERTY"))
+            .unwrap();
+        assert_eq!(
+            context.get_runtime_property_value(pdcstr!("TEST_PROPERTY")),
+            Err(HostingError::HostPropertyNotFound)
+        );
+
+        assert_eq!(mock.active_contexts(), 1);
+        context.close().unwrap();
+        assert_eq!(mock.active_contexts(), 0);
+
+        // the first call registers the error writer for the current thread.
+        let calls = mock.calls();
+        assert_eq!(calls[0].function, "hostfxr_set_error_writer");
+        assert_eq!(calls[1].function, "hostfxr_initialize_for_runtime_config");
+        assert_eq!(calls[1].args, ["/app/App.runtimeconfig.json"]);
+        assert_eq!(calls.last().unwrap().function, "hostfxr_close");
+    }
+
+    #[test]
+    fn delegates_call_closures() {
+        let (mock, hostfxr) = setup();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let closure_calls = Arc::clone(&calls);
+        mock.register_closure("App.Program, App", "Hello", move |_args, size| {
+            closure_calls.fetch_add(1, Ordering::SeqCst);
+            size * 2
+        });
+
+        let context = hostfxr
+            .initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"))
+            .unwrap();
+        // the delegate loader checks that the assembly exists before calling into the runtime.
+        let assembly_path =
+            std::env::temp_dir().join(format!("netcorehost-mock-{}.dll", std::process::id()));
+        std::fs::write(&assembly_path, []).unwrap();
+        let loader = context
+            .get_delegate_loader_for_assembly(PdCString::from_os_str(&assembly_path).unwrap())
+            .unwrap();
+        let hello = loader
+            .get_function_with_default_signature(pdcstr!("App.Program, App"), pdcstr!("Hello"))
+            .unwrap();
+        assert_eq!(unsafe { hello(std::ptr::null(), 21) }, 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        assert!(
+            loader
+                .get_function_with_default_signature(pdcstr!("App.Program, App"), pdcstr!("Missing"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn run_app_returns_exit_code() {
+        let (mock, hostfxr) = setup();
+        mock.set_exit_code(3);
+
+        let context = hostfxr
+            .initialize_for_dotnet_command_line(pdcstr!("App.dll"))
+            .unwrap();
+        assert_eq!(context.run_app().value(), 3);
+        assert_eq!(
+            mock.called_functions(),
+            [
+                // the error writer is registered before the first hosting call on a thread.
+                "hostfxr_set_error_writer",
+                "hostfxr_initialize_for_dotnet_command_line",
+                "hostfxr_run_app",
+                "hostfxr_close"
+            ]
+        );
+    }
+
+    #[test]
+    fn run_app_on_thread() {
+        let (mock, hostfxr) = setup();
+        mock.set_exit_code(4);
+
+        let context = hostfxr
+            .initialize_for_dotnet_command_line(pdcstr!("App.dll"))
+            .unwrap();
+        let handle = context.run_app_on_thread(16 * 1024 * 1024).unwrap();
+        assert_eq!(handle.thread().name(), Some("dotnet-app"));
+        assert_eq!(handle.join().unwrap(), AppExit::AppExited(4));
+        assert_eq!(mock.active_contexts(), 0);
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "utils"))]
+    fn altstack_is_installed_automatically() {
+        use netcorehost::utils::altstack::{self, State};
+
+        let (_mock, hostfxr) = setup();
+        altstack::set_auto_install(Some(State::Enabled { size: 48 * 1024 }));
+
+        std::thread::spawn(move || {
+            assert_ne!(altstack::get().unwrap(), State::Enabled { size: 48 * 1024 });
+            let _context = hostfxr
+                .initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"))
+                .unwrap();
+            assert_eq!(altstack::get().unwrap(), State::Enabled { size: 48 * 1024 });
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn run_app_classifies_result() {
+        let (mock, hostfxr) = setup();
+        mock.set_status(
+            "hostfxr_run_app",
+            HostingError::FrameworkMissingFailure.value(),
+        );
+        let context = hostfxr
+            .initialize_for_dotnet_command_line(pdcstr!("App.dll"))
+            .unwrap();
+        assert_eq!(
+            context.run_app(),
+            AppExit::HostingFailed(HostingError::FrameworkMissingFailure)
+        );
+
+        // an app exiting with a hosting status code is only detected in strict mode.
+        mock.clear_status("hostfxr_run_app");
+        let exit_code = HostingError::FrameworkMissingFailure.value().cast_signed();
+        mock.set_exit_code(exit_code);
+        let context = hostfxr
+            .initialize_for_dotnet_command_line(pdcstr!("App.dll"))
+            .unwrap();
+        assert_eq!(
+            context.run_app_with_classification(AppExitClassification::Strict),
+            AppExit::AppExited(exit_code)
+        );
+    }
+
+    #[test]
+    fn forced_status_is_reported() {
+        let (mock, hostfxr) = setup();
+        mock.set_status(
+            "hostfxr_initialize_for_runtime_config",
+            HostingError::FrameworkMissingFailure.value(),
+        );
+
+        let errors = Rc::new(RefCell::new(Vec::new()));
+        let writer_errors = Rc::clone(&errors);
+        hostfxr.set_error_writer(Some(Box::new(move |message| {
+            writer_errors.borrow_mut().push(message.to_string_lossy());
+        })))
+        .unwrap();
+
+        let result = hostfxr.initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"));
+        let err = result.unwrap_err();
+        assert_eq!(err.code(), HostingError::FrameworkMissingFailure);
+        assert_eq!(errors.borrow().len(), 1);
+        assert_eq!(mock.active_contexts(), 0);
+
+        assert_eq!(err.operation(), "hostfxr_initialize_for_runtime_config");
+        assert_eq!(
+            err.arg("runtime_config_path"),
+            Some(pdcstr!("App.runtimeconfig.json"))
+        );
+        assert_eq!(err.messages(), errors.borrow().as_slice());
+        assert!(err.to_string().contains("App.runtimeconfig.json"));
+    }
+
+    #[test]
+    fn initialize_error_has_context() {
+        // hostfxr is installed in a versioned directory, which determines its version.
+        let root_dir = std::env::temp_dir().join(format!("netcorehost-mock-{}", std::process::id()));
+        let fxr_dir = root_dir
+            .join("host")
+            .join("fxr")
+            .join("9.0.1");
+        std::fs::create_dir_all(&fxr_dir).unwrap();
+        let library_path = MockHostfxr::library_path().unwrap();
+        let hostfxr_path = fxr_dir.join(library_path.file_name().unwrap());
+        std::fs::copy(library_path, &hostfxr_path).unwrap();
+
+        let mock = MockHostfxr::load_from_path(&hostfxr_path).unwrap();
+        mock.reset();
+        mock.set_status(
+            "hostfxr_initialize_for_dotnet_command_line",
+            HostingError::FrameworkMissingFailure.value(),
+        );
+        let hostfxr = Hostfxr::load_from_path(&hostfxr_path).unwrap();
+        assert_eq!(hostfxr.version(), Some("9.0.1"));
+
+        let parameters_root = pdcstr!("/usr/share/dotnet");
+        let err = hostfxr
+            .initialize_for_dotnet_command_line_with_args_and_dotnet_root(
+                pdcstr!("App.dll"),
+                [pdcstr!("--verbose")].into_iter(),
+                parameters_root,
+            )
+            .unwrap_err();
+        assert_eq!(err, HostingError::FrameworkMissingFailure);
+        assert_eq!(err.hostfxr_version(), Some("9.0.1"));
+        assert_eq!(
+            err.args(),
+            [
+                ("app_path", PdCString::from(pdcstr!("App.dll"))),
+                ("dotnet_root", PdCString::from(parameters_root)),
+            ]
+        );
+        assert_eq!(err.messages().len(), 1);
+        assert_eq!(
+            HostingError::from(err),
+            HostingError::FrameworkMissingFailure
+        );
+
+        let _ = std::fs::remove_dir_all(root_dir);
+    }
+
+    #[test]
+    fn hostfxr_api() {
+        let (mock, hostfxr) = setup();
+        mock.set_exit_code(5);
+
+        let context = HostfxrApi::initialize_for_dotnet_command_line_with_args(
+            &hostfxr,
+            pdcstr!("App.dll"),
+            &[pdcstr!("--verbose")],
+        )
+        .unwrap();
+        assert!(HostfxrContextApi::is_primary(&context));
+        assert_eq!(HostfxrCommandLineContextApi::run_app(context).value(), 5);
+        assert_eq!(mock.calls()[1].args, ["App.dll", "--verbose"]);
+    }
+
+    #[test]
+    #[cfg(feature = "net6_0")]
+    fn missing_export() {
+        let (_mock, hostfxr) = setup();
+        assert_eq!(
+            hostfxr.get_dotnet_environment_info().unwrap_err(),
+            HostingError::MissingExport {
+                name: "hostfxr_get_dotnet_environment_info"
+            }
+        );
+    }
+}
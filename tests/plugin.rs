@@ -0,0 +1,88 @@
+#![cfg(feature = "net5_0")]
+
+use common::helper::{
+    COLLECTIONS, CONTEXTS, FREED, KEEP_ALIVE, LOADED, UNLOADED, load_fake_helper,
+};
+use netcorehost::{hostfxr::PluginHandle, pdcstr};
+use rusty_fork::rusty_fork_test;
+use std::sync::atomic::Ordering;
+
+mod common;
+
+rusty_fork_test! {
+    #[test]
+    fn unload_plugin() {
+        let helper = load_fake_helper();
+
+        let plugin = PluginHandle::load(&helper, pdcstr!("/plugins/Plugin.dll")).unwrap();
+        assert_eq!(plugin.assembly_path(), pdcstr!("/plugins/Plugin.dll"));
+        assert_eq!(plugin.context_name(), pdcstr!("Plugin"));
+        assert_eq!(*CONTEXTS.lock().unwrap(), [("Plugin".to_string(), true)]);
+        assert_eq!(*LOADED.lock().unwrap(), [(1, "/plugins/Plugin.dll".to_string())]);
+
+        let run = plugin
+            .get_function_with_unmanaged_callers_only::<fn() -> i32>(
+                pdcstr!("Plugin.Entry, Plugin"),
+                pdcstr!("Run"),
+            )
+            .unwrap();
+        assert_eq!(run.call(()), 42);
+
+        assert!(plugin.unload().unwrap());
+        assert_eq!(*UNLOADED.lock().unwrap(), [1]);
+        assert_eq!(COLLECTIONS.load(Ordering::SeqCst), 1);
+        // only the weak handle is left to be freed after unloading.
+        assert_eq!(FREED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn unload_plugin_that_is_not_collected() {
+        let helper = load_fake_helper();
+        KEEP_ALIVE.store(true, Ordering::SeqCst);
+
+        let plugin = PluginHandle::load(&helper, pdcstr!("Plugin.dll")).unwrap();
+        assert!(!plugin.unload().unwrap());
+        assert_eq!(
+            COLLECTIONS.load(Ordering::SeqCst),
+            PluginHandle::COLLECTION_ATTEMPTS as usize
+        );
+    }
+
+    #[test]
+    fn drop_unloads_plugin() {
+        let helper = load_fake_helper();
+
+        let plugin = PluginHandle::load(&helper, pdcstr!("Plugin.dll")).unwrap();
+        drop(plugin);
+        assert_eq!(*UNLOADED.lock().unwrap(), [1]);
+        assert_eq!(FREED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "nethost")]
+    fn unload_library_plugin() {
+        common::setup();
+        common::build_helper_project();
+
+        let hostfxr = netcorehost::nethost::load_hostfxr().unwrap();
+        let context = hostfxr
+            .initialize_for_runtime_config(common::test_runtime_config_path())
+            .unwrap();
+        let helper = netcorehost::hostfxr::AssemblyLoadContextHelper::load(
+            &context.get_delegate_loader().unwrap(),
+            common::helper_dll_path(),
+        )
+        .unwrap();
+
+        let plugin = PluginHandle::load(&helper, common::library_dll_path()).unwrap();
+        let hello = plugin
+            .get_function_with_unmanaged_callers_only::<fn() -> i32>(
+                pdcstr!("ClassLibrary.Library, ClassLibrary"),
+                pdcstr!("Hello"),
+            )
+            .unwrap();
+        assert_eq!(hello.call(()), 42);
+
+        assert!(plugin.unload().unwrap());
+    }
+}
@@ -25,7 +25,7 @@ rusty_fork_test! {
         common::setup();
 
         let hostfxr = nethost::load_hostfxr().unwrap();
-        let result = hostfxr.run_app(&common::test_dll_path());
+        let result = hostfxr.run_app(&common::test_dll_path()).unwrap();
         result.as_hosting_exit_code().unwrap();
         assert_eq!(result.value(), 42);
     }
@@ -45,8 +45,9 @@ fn list_sdks() {
         .parent()
         .unwrap();
 
-    let mut sdks =
-        hostfxr.get_available_sdks_with_dotnet_path(&PdCString::from_os_str(sdks_dir).unwrap());
+    let mut sdks = hostfxr
+        .get_available_sdks_with_dotnet_path(&PdCString::from_os_str(sdks_dir).unwrap())
+        .unwrap();
     sdks.sort();
     actual_sdks.sort();
     assert_eq!(actual_sdks, sdks);
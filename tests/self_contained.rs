@@ -0,0 +1,149 @@
+use netcorehost::hostfxr::{
+    Hostfxr, LoadSelfContainedError, SelfContainedApp, SelfContainedComponent,
+    SelfContainedLayoutError,
+};
+use std::{
+    env::consts::{DLL_PREFIX, DLL_SUFFIX},
+    fs,
+    path::{Path, PathBuf},
+};
+
+const SELF_CONTAINED_CONFIG: &str = r#"{
+  "runtimeOptions": {
+    "tfm": "net10.0",
+    "includedFrameworks": [
+      { "name": "Microsoft.NETCore.App", "version": "10.0.0" }
+    ]
+  }
+}"#;
+
+const FRAMEWORK_DEPENDENT_CONFIG: &str = r#"{
+  "runtimeOptions": {
+    "tfm": "net10.0",
+    "framework": { "name": "Microsoft.NETCore.App", "version": "10.0.0" }
+  }
+}"#;
+
+fn app_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "netcorehost-self-contained-{}-{name}",
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_layout(dir: &Path, runtime_config: &str) {
+    fs::write(dir.join("App.runtimeconfig.json"), runtime_config).unwrap();
+    fs::write(dir.join("App.dll"), []).unwrap();
+    for lib in ["hostfxr", "hostpolicy", "coreclr"] {
+        fs::write(dir.join(format!("{DLL_PREFIX}{lib}{DLL_SUFFIX}")), []).unwrap();
+    }
+}
+
+#[test]
+fn locates_valid_layout() {
+    let dir = app_dir("valid");
+    write_layout(&dir, SELF_CONTAINED_CONFIG);
+
+    let app = SelfContainedApp::locate(&dir).unwrap();
+    assert_eq!(app.app_name(), "App");
+    assert_eq!(app.app_dir(), dir);
+    assert_eq!(app.app_path(), dir.join("App.dll"));
+    assert_eq!(
+        app.runtime_config_path(),
+        dir.join("App.runtimeconfig.json")
+    );
+    assert_eq!(
+        app.hostfxr_path(),
+        dir.join(format!("{DLL_PREFIX}hostfxr{DLL_SUFFIX}"))
+    );
+}
+
+#[test]
+fn rejects_framework_dependent_app() {
+    let dir = app_dir("framework-dependent");
+    write_layout(&dir, FRAMEWORK_DEPENDENT_CONFIG);
+
+    let err = SelfContainedApp::locate(&dir).unwrap_err();
+    assert!(matches!(err, SelfContainedLayoutError::NotSelfContained(_)));
+}
+
+#[test]
+fn only_included_frameworks_of_runtime_options_are_considered() {
+    for (name, runtime_config) in [
+        (
+            "string",
+            r#"{ "runtimeOptions": { "configProperties": { "Note": "\"includedFrameworks\"" } } }"#,
+        ),
+        (
+            "unrelated-section",
+            r#"{ "other": { "includedFrameworks": [] }, "runtimeOptions": { "framework": {} } }"#,
+        ),
+    ] {
+        let dir = app_dir(name);
+        write_layout(&dir, runtime_config);
+        let err = SelfContainedApp::locate(&dir).unwrap_err();
+        assert!(
+            matches!(err, SelfContainedLayoutError::NotSelfContained(_)),
+            "{name}"
+        );
+    }
+
+    let dir = app_dir("comments");
+    write_layout(
+        &dir,
+        r#"{
+  // published with --self-contained
+  "runtimeOptions": {
+    /* "framework": {}, */
+    "tfm": "net10.0",
+    "includedFrameworks": [{ "name": "Microsoft.NETCore.App", "version": "10.0.0" },],
+  },
+}"#,
+    );
+    SelfContainedApp::locate(&dir).unwrap();
+}
+
+#[test]
+fn rejects_missing_component() {
+    let dir = app_dir("missing-hostpolicy");
+    write_layout(&dir, SELF_CONTAINED_CONFIG);
+    fs::remove_file(dir.join(format!("{DLL_PREFIX}hostpolicy{DLL_SUFFIX}"))).unwrap();
+
+    let err = SelfContainedApp::locate(&dir).unwrap_err();
+    assert!(matches!(
+        err,
+        SelfContainedLayoutError::MissingComponent {
+            component: SelfContainedComponent::Hostpolicy,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn rejects_ambiguous_app() {
+    let dir = app_dir("ambiguous");
+    write_layout(&dir, SELF_CONTAINED_CONFIG);
+    fs::write(dir.join("Other.runtimeconfig.json"), SELF_CONTAINED_CONFIG).unwrap();
+
+    let err = SelfContainedApp::locate(&dir).unwrap_err();
+    assert!(
+        matches!(err, SelfContainedLayoutError::AmbiguousApp(apps) if apps == ["App", "Other"])
+    );
+
+    SelfContainedApp::locate_with_name(&dir, "App").unwrap();
+}
+
+#[test]
+fn load_validates_layout() {
+    let dir = app_dir("load-missing");
+    let result = Hostfxr::load_self_contained(&dir);
+    assert!(matches!(
+        result,
+        Err(LoadSelfContainedError::Layout(
+            SelfContainedLayoutError::MissingRuntimeConfig(_)
+        ))
+    ));
+}
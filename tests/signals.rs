@@ -0,0 +1,97 @@
+#![cfg(all(unix, feature = "utils"))]
+
+use netcorehost::utils::signals::{ChainPolicy, SignalHandler, SignalSnapshot};
+use rusty_fork::rusty_fork_test;
+use std::sync::Mutex;
+
+static INVOCATIONS: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+extern "C" fn host_handler(_signal: libc::c_int) {
+    INVOCATIONS.lock().unwrap().push("host");
+}
+
+extern "C" fn runtime_handler(
+    _signal: libc::c_int,
+    _info: *mut libc::siginfo_t,
+    _context: *mut libc::c_void,
+) {
+    INVOCATIONS.lock().unwrap().push("runtime");
+}
+
+fn handler(address: usize, flags: libc::c_int) -> SignalHandler {
+    let mut action = unsafe { std::mem::zeroed::<libc::sigaction>() };
+    action.sa_sigaction = address;
+    action.sa_flags = flags;
+    SignalHandler::from_raw(action)
+}
+
+fn install_host_and_runtime_handlers() -> SignalSnapshot {
+    unsafe { handler(host_handler as *const () as usize, 0).install(libc::SIGUSR1) }.unwrap();
+    let snapshot = SignalSnapshot::capture(&[libc::SIGUSR1, libc::SIGUSR2]).unwrap();
+    unsafe {
+        handler(runtime_handler as *const () as usize, libc::SA_SIGINFO).install(libc::SIGUSR1)
+    }
+    .unwrap();
+    snapshot
+}
+
+fn raise_and_collect() -> Vec<&'static str> {
+    unsafe { libc::raise(libc::SIGUSR1) };
+    std::mem::take(&mut *INVOCATIONS.lock().unwrap())
+}
+
+rusty_fork_test! {
+    #[test]
+    fn detects_changes() {
+        let snapshot = install_host_and_runtime_handlers();
+        let changes = snapshot.changes().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].signal, libc::SIGUSR1);
+        assert_eq!(changes[0].before, *snapshot.get(libc::SIGUSR1).unwrap());
+        assert_eq!(
+            changes[0].after,
+            SignalHandler::current(libc::SIGUSR1).unwrap()
+        );
+        assert!(snapshot.get(libc::SIGUSR2).unwrap().is_default());
+    }
+
+    #[test]
+    fn keeps_runtime_handler() {
+        let snapshot = install_host_and_runtime_handlers();
+        unsafe { snapshot.apply(ChainPolicy::Runtime) }.unwrap();
+        assert_eq!(raise_and_collect(), ["runtime"]);
+    }
+
+    #[test]
+    fn restores_host_handler() {
+        let snapshot = install_host_and_runtime_handlers();
+        unsafe { snapshot.apply(ChainPolicy::Host) }.unwrap();
+        assert_eq!(raise_and_collect(), ["host"]);
+        assert!(snapshot.changes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn chains_handlers() {
+        let snapshot = install_host_and_runtime_handlers();
+        unsafe { snapshot.apply(ChainPolicy::HostThenRuntime) }.unwrap();
+        assert_eq!(raise_and_collect(), ["host", "runtime"]);
+    }
+
+    #[test]
+    fn chain_keeps_handler_flags() {
+        unsafe { handler(host_handler as *const () as usize, libc::SA_RESTART).install(libc::SIGUSR1) }
+            .unwrap();
+        let snapshot = SignalSnapshot::capture(&[libc::SIGUSR1]).unwrap();
+        unsafe {
+            handler(runtime_handler as *const () as usize, libc::SA_SIGINFO | libc::SA_NODEFER)
+                .install(libc::SIGUSR1)
+        }
+        .unwrap();
+
+        unsafe { snapshot.apply(ChainPolicy::HostThenRuntime) }.unwrap();
+        let flags = SignalHandler::current(libc::SIGUSR1).unwrap().flags();
+        assert_eq!(flags & libc::SA_RESTART, libc::SA_RESTART);
+        assert_eq!(flags & libc::SA_NODEFER, libc::SA_NODEFER);
+        assert_eq!(raise_and_collect(), ["host", "runtime"]);
+    }
+}
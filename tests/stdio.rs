@@ -0,0 +1,72 @@
+#![cfg(all(unix, feature = "utils"))]
+
+use netcorehost::utils::stdio::{self, Redirection, Stream};
+use rusty_fork::rusty_fork_test;
+use std::io::{ErrorKind, Read};
+
+mod common;
+
+fn write_fd(fd: libc::c_int, data: &[u8]) {
+    let written = unsafe { libc::write(fd, data.as_ptr().cast(), data.len()) };
+    assert_eq!(written, data.len().cast_signed());
+}
+
+rusty_fork_test! {
+    #[test]
+    fn captures_stdout_and_stderr() {
+        let (result, output) = stdio::capture(|| {
+            write_fd(libc::STDOUT_FILENO, b"to stdout");
+            write_fd(libc::STDERR_FILENO, b"to stderr");
+            42
+        })
+        .unwrap();
+
+        assert_eq!(result, 42);
+        assert_eq!(output.stdout, b"to stdout");
+        assert_eq!(output.stderr, b"to stderr");
+    }
+
+    #[test]
+    fn captures_more_than_pipe_capacity() {
+        let data = vec![b'x'; 1024 * 1024];
+        let ((), output) = stdio::capture(|| write_fd(libc::STDOUT_FILENO, &data)).unwrap();
+        assert_eq!(output.stdout.len(), data.len());
+    }
+
+    #[test]
+    fn restores_original_fds() {
+        let mut redirection = Redirection::start(&[Stream::Stdout]).unwrap();
+        assert_eq!(
+            Redirection::start(&[Stream::Stderr]).err().map(|err| err.kind()),
+            Some(ErrorKind::ResourceBusy)
+        );
+        let mut reader = redirection.take_reader(Stream::Stdout).unwrap();
+        assert!(redirection.take_reader(Stream::Stdout).is_none());
+        assert!(redirection.take_reader(Stream::Stderr).is_none());
+
+        write_fd(libc::STDOUT_FILENO, b"captured");
+        redirection.finish().unwrap();
+
+        let mut captured = String::new();
+        reader.read_to_string(&mut captured).unwrap();
+        assert_eq!(captured, "captured");
+
+        // a new redirection can be started after the previous one finished.
+        let ((), output) = stdio::capture(|| write_fd(libc::STDOUT_FILENO, b"again")).unwrap();
+        assert_eq!(output.stdout, b"again");
+    }
+
+    #[test]
+    #[cfg(all(feature = "netcore3_0", feature = "nethost"))]
+    fn captures_managed_output() {
+        common::setup();
+
+        let hostfxr = netcorehost::nethost::load_hostfxr().unwrap();
+        let context = hostfxr
+            .initialize_for_dotnet_command_line(common::test_dll_path())
+            .unwrap();
+        let (result, output) = stdio::capture(|| context.run_app()).unwrap();
+        assert_eq!(result.exit_code(), Some(42));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Hello from C#!"));
+    }
+}
@@ -0,0 +1,171 @@
+use netcorehost::{
+    metadata::AssemblyVersion,
+    pdcstr,
+    type_name::{AssemblyName, AssemblyQualifiedName, TypeNameError},
+};
+
+#[test]
+fn parses_assembly_qualified_names() {
+    let name: AssemblyQualifiedName =
+        "Namespace.Inner.Cache`2+Entry+Node`1, Library, Version=1.2, \
+         Culture=neutral, PublicKeyToken=B77A5C561934E089"
+            .parse()
+            .unwrap();
+    assert_eq!(name.namespace(), Some("Namespace.Inner"));
+    assert_eq!(name.name(), "Node");
+    assert_eq!(
+        name.enclosing_types().collect::<Vec<_>>(),
+        ["Cache", "Entry"]
+    );
+    assert_eq!(name.generic_arity(), 3);
+    assert_eq!(name.full_name(), "Namespace.Inner.Cache`2+Entry+Node`1");
+
+    let assembly = name.assembly();
+    assert_eq!(assembly.name(), "Library");
+    assert_eq!(assembly.version(), Some(AssemblyVersion::new(1, 2, 0, 0)));
+    assert_eq!(assembly.culture(), Some("neutral"));
+    assert_eq!(
+        assembly.public_key_token(),
+        Some([0xb7, 0x7a, 0x5c, 0x56, 0x19, 0x34, 0xe0, 0x89])
+    );
+    assert_eq!(
+        name.to_string(),
+        "Namespace.Inner.Cache`2+Entry+Node`1, Library, Version=1.2.0.0, Culture=neutral, \
+         PublicKeyToken=b77a5c561934e089"
+    );
+
+    let name: AssemblyQualifiedName = "Program,App,PublicKeyToken=null".parse().unwrap();
+    assert_eq!(name.namespace(), None);
+    assert_eq!(name.assembly().public_key_token(), None);
+    assert_eq!(name.as_pdcstr(), pdcstr!("Program, App"));
+}
+
+#[test]
+fn constructs_assembly_qualified_names() {
+    let assembly = AssemblyName::new("ExampleProject")
+        .unwrap()
+        .with_version(AssemblyVersion::new(1, 0, 0, 0))
+        .with_culture("en-US")
+        .unwrap()
+        .with_public_key_token([1, 2, 3, 4, 5, 6, 7, 8]);
+    let program = AssemblyQualifiedName::new("ExampleProject.Program", assembly).unwrap();
+    let nested = program.nested("Nested`1").unwrap();
+    assert_eq!(
+        nested.as_pdcstr(),
+        pdcstr!(
+            "ExampleProject.Program+Nested`1, ExampleProject, Version=1.0.0.0, Culture=en-US, PublicKeyToken=0102030405060708"
+        )
+    );
+    assert_eq!(
+        nested.to_string().parse::<AssemblyQualifiedName>(),
+        Ok(nested)
+    );
+}
+
+#[test]
+fn rejects_invalid_names() {
+    fn parse(name: &str) -> TypeNameError {
+        name.parse::<AssemblyQualifiedName>().unwrap_err()
+    }
+
+    assert!(matches!(
+        parse("Namespace.Program"),
+        TypeNameError::MissingAssemblyName(_)
+    ));
+    for name in [
+        ", App",
+        "Namespace..Program, App",
+        "Namespace.Program+, App",
+        "Namespace.Program+Nested.Type, App",
+        "Namespace.List`1[[System.Int32]], App",
+        "Namespace.Program*, App",
+    ] {
+        assert!(
+            matches!(parse(name), TypeNameError::InvalidTypeName(_)),
+            "{name}"
+        );
+    }
+    for name in ["List`0, App", "List`, App", "List`01, App", "List`x, App"] {
+        assert!(
+            matches!(parse(name), TypeNameError::InvalidGenericArity(_)),
+            "{name}"
+        );
+    }
+    assert!(matches!(
+        parse("Program, "),
+        TypeNameError::InvalidAssemblyName(_)
+    ));
+    assert!(matches!(
+        parse("Program, App, Version"),
+        TypeNameError::InvalidAssemblyName(_)
+    ));
+    assert_eq!(
+        parse("Program, App, Retargetable=Yes"),
+        TypeNameError::UnknownProperty("Retargetable".to_string())
+    );
+    assert_eq!(
+        parse("Program, App, Version=1.0, version=2.0"),
+        TypeNameError::DuplicateProperty("Version")
+    );
+    for (name, property) in [
+        ("Program, App, Version=1", "Version"),
+        ("Program, App, Version=1.2.3.4.5", "Version"),
+        ("Program, App, Version=1.x", "Version"),
+        ("Program, App, Culture=", "Culture"),
+        ("Program, App, PublicKeyToken=b77a5c56", "PublicKeyToken"),
+        (
+            "Program, App, PublicKeyToken=b77a5c561934e08g",
+            "PublicKeyToken",
+        ),
+        (
+            "Program, App, PublicKeyToken=+77a5c561934e089",
+            "PublicKeyToken",
+        ),
+        (
+            "Program, App, PublicKeyToken=b77a5c561934e0+9",
+            "PublicKeyToken",
+        ),
+    ] {
+        assert!(
+            matches!(parse(name), TypeNameError::InvalidProperty { property: p, .. } if p == property),
+            "{name}"
+        );
+    }
+
+    assert!(AssemblyName::new("App, Version=1.0.0.0").is_err());
+    let program = AssemblyQualifiedName::new("Program", AssemblyName::new("App").unwrap()).unwrap();
+    assert!(program.nested("Outer+Inner").is_err());
+}
+
+#[cfg(feature = "net5_0")]
+mod delegate_loader {
+    use netcorehost::{hostfxr::Hostfxr, pdcstr, type_name::AssemblyQualifiedName};
+    use netcorehost_mock::MockHostfxr;
+    use rusty_fork::rusty_fork_test;
+
+    rusty_fork_test! {
+        #[test]
+        fn accepts_assembly_qualified_names() {
+            let mock = MockHostfxr::load().unwrap();
+            mock.reset();
+            mock.register_closure("App.Program+Nested, App", "Run", |_args, size| size + 1);
+
+            let hostfxr = Hostfxr::load_from_path(mock.path()).unwrap();
+            let context = hostfxr
+                .initialize_for_runtime_config(pdcstr!("App.runtimeconfig.json"))
+                .unwrap();
+            let loader = context.get_delegate_loader().unwrap();
+
+            let program: AssemblyQualifiedName = "App.Program, App".parse().unwrap();
+            let run = loader
+                .get_function_with_default_signature(program.nested("Nested").unwrap(), pdcstr!("Run"))
+                .unwrap();
+            assert_eq!(unsafe { run(std::ptr::null(), 1) }, 2);
+            assert!(
+                loader
+                    .get_function_with_default_signature(&program, pdcstr!("Run"))
+                    .is_err()
+            );
+        }
+    }
+}